@@ -11,6 +11,10 @@ defmodule ExCompileGraph do
     :ok
   end
 
+  # Every path of a dependent is counted up to the first limit, only the shortest ones are sent
+  @max_counted_paths 100
+  @max_chains 10
+
   def get_graph() do
     manifest = Mix.Project.manifest_path() <> "/compile.elixir"
     graph = __MODULE__.Graph.build(manifest)
@@ -22,12 +26,33 @@ defmodule ExCompileGraph do
         dependencies =
           __MODULE__.Dependency.recompile_dependencies(graph, vertex.id)
           |> Enum.flat_map(fn {reason, dependents} ->
+            files = Enum.map(dependents, &elem(&1, 0))
+
+            all_paths =
+              __MODULE__.Dependency.dependency_paths(graph, vertex.id, reason, files,
+                limit: @max_counted_paths
+              )
+
             for {file, chain} <- dependents do
+              # The path found first is kept should the search come back empty
+              {paths, more_paths} =
+                case Map.get(all_paths, file, {[], false}) do
+                  {[], more_paths} -> {[chain], more_paths}
+                  found -> found
+                end
+
+              chains =
+                for path <- Enum.take(paths, @max_chains),
+                    do: format_dependency_chain(path, vertex.id)
+
               %{
                 id: "#{file}_#{reason}",
                 path: file,
                 reason: reason,
-                dependency_chain: format_dependency_chain(chain, vertex.id)
+                dependency_chain: hd(chains),
+                dependency_chains: chains,
+                path_count: length(paths),
+                more_paths: more_paths
               }
             end
           end)
@@ -66,23 +91,25 @@ defmodule ExCompileGraph do
     Enum.each(graph_summary, fn vertex ->
       Enum.each(vertex.recompile_dependencies, fn dependent ->
         key = {:dependency_path, vertex.id, dependent.path, dependent.reason}
-        :ets.insert(__MODULE__.Cache, {key, dependent.dependency_chain})
+        :ets.insert(__MODULE__.Cache, {key, dependent.dependency_chains})
       end)
     end)
   end
 
+  # The chain is the index of the path among the ones sent for the dependent, the shortest is 0
   @spec get_recompile_dependency_causes(
           file_path,
           file_path,
-          __MODULE__.Dependency.dependency_reason()
+          __MODULE__.Dependency.dependency_reason(),
+          non_neg_integer()
         ) :: any()
-  def get_recompile_dependency_causes(source_file, sink_file, reason) do
-    case :ets.lookup(__MODULE__.Cache, {:dependency_path, sink_file, source_file, reason}) do
-      [{_, path}] ->
-        get_detailed_explanation(path ++ [{:eof, sink_file, nil}])
-
-      [] ->
-        []
+  def get_recompile_dependency_causes(source_file, sink_file, reason, chain \\ 0) do
+    with [{_, chains}] <-
+           :ets.lookup(__MODULE__.Cache, {:dependency_path, sink_file, source_file, reason}),
+         path when is_list(path) <- Enum.at(chains, chain) do
+      get_detailed_explanation(path ++ [{:eof, sink_file, nil}])
+    else
+      _ -> []
     end
   end
 
@@ -151,6 +178,7 @@ defmodule ExCompileGraph do
     to = min(from + length(lines) - 1, to)
 
     %{
+      origin_file: file,
       content: Enum.join(lines),
       lines_span: [from, to],
       highlight: Tuple.to_list(lines_span)
@@ -124,6 +124,170 @@ defmodule ExCompileGraph.Dependency do
         )
   end
 
+  @doc """
+  Given a target file, a recompile reason and the files recompiling for that reason, find the
+  paths making each of them recompile, the shortest first. A file often recompiles through
+  several paths, all of them have to be broken for it to stop recompiling
+
+  Paths are searched in the same shape as `recompile_dependencies/2`: `:compile` follows compile
+  dependencies, `:exports` a single exports dependency, `:exports_then_compile` a single exports
+  dependency then compile dependencies and `:compile_then_runtime` compile dependencies then
+  runtime dependencies. A path never goes through the same file twice
+
+  Each file comes with whether it has more paths than the ones found: the search stopped at the
+  limit, or dropped paths that didn't fit in the queue
+
+  ## Options
+
+    * `limit`: the number of paths found for each file, the search stops there. Default: 100
+    * `max_queue`: the number of paths extended at each level of the search, the longer ones are
+      dropped past it. The number of paths grows exponentially with their length in dense graphs,
+      the cap keeps the search bounded. Default: 1000
+  """
+  @spec dependency_paths(:digraph.graph(), ExCompileGraph.file_path(), dependency_reason, [
+          ExCompileGraph.file_path()
+        ]) :: %{ExCompileGraph.file_path() => {[dependency_path], boolean}}
+  def dependency_paths(graph, target_file, reason, source_files, opts \\ []) do
+    limit = Keyword.get(opts, :limit, 100)
+    max_queue = Keyword.get(opts, :max_queue, 1000)
+
+    stages =
+      case reason do
+        :compile -> [compile: :transitive]
+        :exports -> [exports: :direct]
+        :exports_then_compile -> [exports: :direct, compile: :transitive]
+        :compile_then_runtime -> [compile: :transitive, runtime: :transitive]
+      end
+
+    stages = with_finishing_files(stages, graph, target_file)
+    walk = %{graph: graph, target: target_file, limit: limit, max_queue: max_queue}
+
+    for file <- source_files, into: %{} do
+      start = {file, stages, 0, [], MapSet.new([file])}
+      {file, shortest_paths(walk, [start], [], false)}
+    end
+  end
+
+  # Every stage only goes through the files from which it and the following stages can reach the
+  # target, so that the search doesn't wander off in the graph
+  defp with_finishing_files(stages, graph, target_file) do
+    stages
+    |> Enum.reverse()
+    |> Enum.map_reduce(MapSet.new([target_file]), fn {type, mode}, ends ->
+      finishing = reaching_files(graph, ends, type, mode)
+      {{type, mode, finishing}, finishing}
+    end)
+    |> elem(0)
+    |> Enum.reverse()
+  end
+
+  # The files reaching any of the given files through dependencies of the type: in one step when
+  # direct, in any number of steps when transitive
+  defp reaching_files(graph, files, type, mode) do
+    direct = files |> Enum.flat_map(&source_files(graph, &1, type)) |> MapSet.new()
+
+    if mode == :direct,
+      do: direct,
+      else: reach_transitively(graph, type, MapSet.to_list(direct), direct)
+  end
+
+  defp reach_transitively(_graph, _type, [], reached), do: reached
+
+  defp reach_transitively(graph, type, [file | files], reached) do
+    sources = source_files(graph, file, type) |> Enum.reject(&MapSet.member?(reached, &1))
+    reach_transitively(graph, type, sources ++ files, MapSet.union(reached, MapSet.new(sources)))
+  end
+
+  # Breadth first, so that the paths are found shortest first: each level extends the paths by
+  # one dependency. One path more than the limit is searched to tell whether there are more, and
+  # only the first `max_queue` paths of a level are extended
+  defp shortest_paths(walk, paths, found, dropped?) do
+    if paths == [] or length(found) > walk.limit do
+      more? = dropped? or length(found) > walk.limit
+      {found |> Enum.reverse() |> Enum.take(walk.limit), more?}
+    else
+      {found, next_paths} =
+        Enum.reduce_while(paths, {found, []}, fn path, acc ->
+          {found, _} = acc = step(walk, path, acc)
+          if length(found) > walk.limit, do: {:halt, acc}, else: {:cont, acc}
+        end)
+
+      next_paths = Enum.reverse(next_paths)
+      dropped? = dropped? or length(next_paths) > walk.max_queue
+      shortest_paths(walk, Enum.take(next_paths, walk.max_queue), found, dropped?)
+    end
+  end
+
+  # Each step follows a dependency of the current stage type, or moves on to the next stage once
+  # the current one has a dependency. Reaching the target in the last stage completes the path
+  defp step(walk, {file, [{type, mode, _} | next_stages] = stages, steps, path, visited}, acc) do
+    acc =
+      case next_stages do
+        [{_, _, next_finishing} | _] when steps > 0 ->
+          if MapSet.member?(next_finishing, file),
+            do: step(walk, {file, next_stages, 0, path, visited}, acc),
+            else: acc
+
+        _ ->
+          acc
+      end
+
+    if steps == 0 or mode == :transitive do
+      sink_files(walk.graph, file, type)
+      |> Enum.reject(&MapSet.member?(visited, &1))
+      |> Enum.reduce(acc, fn sink, {found, next_paths} ->
+        path = [{type, file} | path]
+
+        cond do
+          sink == walk.target and next_stages == [] ->
+            {[Enum.reverse(path) | found], next_paths}
+
+          sink != walk.target and finishes?(sink, stages) ->
+            next_path = {sink, stages, steps + 1, path, MapSet.put(visited, sink)}
+            {found, [next_path | next_paths]}
+
+          true ->
+            {found, next_paths}
+        end
+      end)
+    else
+      acc
+    end
+  end
+
+  # Whether the target can still be reached from the file, by going on with the stage or with the
+  # next one
+  defp finishes?(file, [{_, mode, finishing} | next_stages]) do
+    continues? = mode == :transitive and MapSet.member?(finishing, file)
+
+    case next_stages do
+      [{_, _, next_finishing} | _] -> continues? or MapSet.member?(next_finishing, file)
+      [] -> continues?
+    end
+  end
+
+  defp source_files(graph, sink_file, dependency_type) do
+    :digraph.in_edges(graph, sink_file)
+    |> Enum.flat_map(fn edge ->
+      case :digraph.edge(graph, edge) do
+        {_, source, _, ^dependency_type} -> [source]
+        _ -> []
+      end
+    end)
+    |> Enum.uniq()
+  end
+
+  defp sink_files(graph, source_file, dependency_type) do
+    :digraph.out_edges(graph, source_file)
+    |> Enum.flat_map(fn edge ->
+      case :digraph.edge(graph, edge) do
+        {_, _, sink, ^dependency_type} -> [sink]
+        _ -> []
+      end
+    end)
+    |> Enum.uniq()
+  end
+
   @type dependency_causes_params :: %{
           source_file: ExCompileGraph.file_path(),
           sink_file: ExCompileGraph.file_path(),
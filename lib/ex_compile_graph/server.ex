@@ -26,7 +26,7 @@ defmodule ExCompileGraph.Server do
                 :get_files
 
               %{"type" => "get_dependency_causes"} = params ->
-                {:get_dependency_causes, Map.take(params, ["source", "sink", "reason"])}
+                {:get_dependency_causes, Map.take(params, ["source", "sink", "reason", "chain"])}
             end
 
           response = dispatch(request)
@@ -56,8 +56,10 @@ defmodule ExCompileGraph.Server do
           ExCompileGraph.get_graph() do
       recompile_dependencies =
         Enum.map(recompile_dependencies, fn dependency ->
-          Map.update!(dependency, :dependency_chain, fn chain ->
-            Enum.map(chain, &Tuple.to_list/1)
+          dependency
+          |> Map.update!(:dependency_chain, &Enum.map(&1, fn link -> Tuple.to_list(link) end))
+          |> Map.update!(:dependency_chains, fn chains ->
+            for chain <- chains, do: Enum.map(chain, &Tuple.to_list/1)
           end)
         end)
 
@@ -66,11 +68,16 @@ defmodule ExCompileGraph.Server do
     |> Enum.sort_by(& length(&1.recompile_dependencies), :desc)
   end
 
+  # A failure is reported to the client rather than crashing the server, the causes of other
+  # files can still be requested
   def dispatch({:get_dependency_causes, params}) do
     ExCompileGraph.get_recompile_dependency_causes(
       params["source"],
       params["sink"],
-      String.to_existing_atom(params["reason"])
+      String.to_existing_atom(params["reason"]),
+      params["chain"] || 0
     )
+  rescue
+    error -> %{error: Exception.message(error)}
   end
 end
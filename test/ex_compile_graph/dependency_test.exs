@@ -247,6 +247,80 @@ defmodule ExCompileGraph.DependencyTest do
         end
         """,
         "lib/recompile/D4.ex"
+      },
+      {
+        """
+        defmodule Recompile.E1 do
+          Recompile.E2.x()
+          Recompile.E3.x()
+        end
+        """,
+        "lib/recompile/E1.ex"
+      },
+      {
+        """
+        defmodule Recompile.E2 do
+          Recompile.E4.x()
+
+          def x(), do: 1
+        end
+        """,
+        "lib/recompile/E2.ex"
+      },
+      {
+        """
+        defmodule Recompile.E3 do
+          Recompile.E4.x()
+
+          def x(), do: 1
+        end
+        """,
+        "lib/recompile/E3.ex"
+      },
+      {
+        """
+        defmodule Recompile.E4 do
+          def x(), do: 1
+        end
+        """,
+        "lib/recompile/E4.ex"
+      },
+      {
+        """
+        defmodule Recompile.F1 do
+          Recompile.F2.x()
+          Recompile.F4.x()
+        end
+        """,
+        "lib/recompile/F1.ex"
+      },
+      {
+        """
+        defmodule Recompile.F2 do
+          Recompile.F3.x()
+
+          def x(), do: 1
+        end
+        """,
+        "lib/recompile/F2.ex"
+      },
+      {
+        """
+        defmodule Recompile.F3 do
+          Recompile.F4.x()
+
+          def x(), do: 1
+        end
+        """,
+        "lib/recompile/F3.ex"
+      },
+      {
+        """
+        defmodule Recompile.F4 do
+          def x(), do: 1
+        end
+        """,
+        "lib/recompile/F4.ex"
       }
     ]
 
@@ -526,6 +600,93 @@ defmodule ExCompileGraph.DependencyTest do
     end
   end
 
+  # Use sources_set_3
+  describe "ExCompileGraph.Dependency.dependency_paths/5" do
+    test "Every compile path", %{graph: graph} do
+      assert %{"lib/recompile/E1.ex" => {paths, false}} =
+               Dependency.dependency_paths(graph, "lib/recompile/E4.ex", :compile, [
+                 "lib/recompile/E1.ex"
+               ])
+
+      assert [
+               [compile: "lib/recompile/E1.ex", compile: "lib/recompile/E2.ex"],
+               [compile: "lib/recompile/E1.ex", compile: "lib/recompile/E3.ex"]
+             ] = Enum.sort(paths)
+    end
+
+    test "Paths in the shape of the reason", %{graph: graph} do
+      assert %{
+               "lib/recompile/C1.ex" =>
+                 {[
+                    [
+                      compile: "lib/recompile/C1.ex",
+                      runtime: "lib/recompile/C2.ex",
+                      runtime: "lib/recompile/C3.ex"
+                    ]
+                  ], false}
+             } =
+               Dependency.dependency_paths(graph, "lib/recompile/C4.ex", :compile_then_runtime, [
+                 "lib/recompile/C1.ex"
+               ])
+
+      assert %{
+               "lib/recompile/D2.ex" =>
+                 {[[exports: "lib/recompile/D2.ex", compile: "lib/recompile/D3.ex"]], false}
+             } =
+               Dependency.dependency_paths(graph, "lib/recompile/D4.ex", :exports_then_compile, [
+                 "lib/recompile/D2.ex"
+               ])
+    end
+
+    test "The search stops at the limit", %{graph: graph} do
+      assert %{"lib/recompile/E1.ex" => {[_], true}} =
+               Dependency.dependency_paths(
+                 graph,
+                 "lib/recompile/E4.ex",
+                 :compile,
+                 ["lib/recompile/E1.ex"],
+                 limit: 1
+               )
+    end
+
+    test "The paths past the queue size are dropped", %{graph: graph} do
+      assert %{"lib/recompile/E1.ex" => {[_], true}} =
+               Dependency.dependency_paths(
+                 graph,
+                 "lib/recompile/E4.ex",
+                 :compile,
+                 ["lib/recompile/E1.ex"],
+                 max_queue: 1
+               )
+    end
+
+    test "The shortest paths are found first", %{graph: graph} do
+      assert %{"lib/recompile/F1.ex" => {[[compile: "lib/recompile/F1.ex"]], true}} =
+               Dependency.dependency_paths(
+                 graph,
+                 "lib/recompile/F4.ex",
+                 :compile,
+                 ["lib/recompile/F1.ex"],
+                 limit: 1
+               )
+
+      assert %{
+               "lib/recompile/F1.ex" =>
+                 {[
+                    [compile: "lib/recompile/F1.ex"],
+                    [
+                      compile: "lib/recompile/F1.ex",
+                      compile: "lib/recompile/F2.ex",
+                      compile: "lib/recompile/F3.ex"
+                    ]
+                  ], false}
+             } =
+               Dependency.dependency_paths(graph, "lib/recompile/F4.ex", :compile, [
+                 "lib/recompile/F1.ex"
+               ])
+    end
+  end
+
   # Use sources_set_4
   describe "ExCompileGraph.Dependency.dependency_causes/1" do
     test "Exports dependency causes" do
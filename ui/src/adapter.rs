@@ -1,5 +1,6 @@
 use anyhow::{anyhow, Result};
 use regex::Regex;
+use serde::Deserialize;
 use serde_json::json;
 use std::io::Read;
 use std::io::{BufRead, BufReader, Write};
@@ -28,13 +29,14 @@ pub trait ServerAdapter {
         source: &FilePath,
         sink: &FilePath,
         reason: &RecomplileDependencyReason,
-        callback: Box<dyn FnOnce(Vec<DependencyCause>) -> ()>,
+        chain: usize,
+        callback: Box<dyn FnOnce(Result<Vec<DependencyCause>>) -> ()>,
     );
 }
 
 enum RequestCallback {
     GetFiles(Box<dyn FnOnce(Vec<FileEntry>) -> ()>),
-    GetDependencyCauses(Box<dyn FnOnce(Vec<DependencyCause>) -> ()>),
+    GetDependencyCauses(Box<dyn FnOnce(Result<Vec<DependencyCause>>) -> ()>),
 }
 
 impl Adapter {
@@ -87,8 +89,7 @@ impl Adapter {
                 }
 
                 Some(RequestCallback::GetDependencyCauses(callback)) => {
-                    let causes = serde_json::from_str::<Vec<DependencyCause>>(&response).unwrap();
-                    callback(causes);
+                    callback(parse_dependency_causes(&response));
                 }
 
                 None => (),
@@ -116,6 +117,21 @@ impl Adapter {
     }
 }
 
+// The server answers with an error instead of the causes when it fails to find them
+#[derive(Deserialize)]
+struct ServerError {
+    error: String,
+}
+
+fn parse_dependency_causes(response: &str) -> Result<Vec<DependencyCause>> {
+    if let Ok(server_error) = serde_json::from_str::<ServerError>(response) {
+        return Err(anyhow!(server_error.error));
+    }
+
+    serde_json::from_str::<Vec<DependencyCause>>(response)
+        .map_err(|error| anyhow!("Invalid response from the server: {}", error))
+}
+
 fn wait_for_response(stdout: &mut BufReader<ChildStdout>, request_id: usize) -> Result<String> {
     let mut response = String::new();
     stdout.read_line(&mut response)?;
@@ -173,9 +189,10 @@ impl ServerAdapter for Adapter {
         source: &FilePath,
         sink: &FilePath,
         reason: &RecomplileDependencyReason,
-        callback: Box<dyn FnOnce(Vec<DependencyCause>) -> ()>,
+        chain: usize,
+        callback: Box<dyn FnOnce(Result<Vec<DependencyCause>>) -> ()>,
     ) {
-        let payload = json!({ "type": "get_dependency_causes", "source": source, "sink": sink, "reason": reason });
+        let payload = json!({ "type": "get_dependency_causes", "source": source, "sink": sink, "reason": reason, "chain": chain });
 
         self.pending_requests.push((
             self.request_sequence_id,
@@ -208,7 +225,79 @@ impl ServerAdapter for NoopAdapter {
         _source: &FilePath,
         _sink: &FilePath,
         _reason: &RecomplileDependencyReason,
-        _callback: Box<dyn FnOnce(Vec<DependencyCause>) -> ()>,
+        _chain: usize,
+        _callback: Box<dyn FnOnce(Result<Vec<DependencyCause>>) -> ()>,
     ) {
     }
 }
+
+#[cfg(test)]
+mod adapter_tests {
+    use super::*;
+
+    #[test]
+    fn dependency_causes_response() {
+        let causes = parse_dependency_causes(
+            r#"[{"source": "lib/a.ex", "sink": "lib/b.ex", "snippets": [], "type": "compile"}]"#,
+        )
+        .unwrap();
+        assert_eq!(causes[0].sink, "lib/b.ex");
+
+        let error = parse_dependency_causes(r#"{"error": "no such file"}"#).unwrap_err();
+        assert_eq!(error.to_string(), "no such file");
+
+        assert!(parse_dependency_causes("nonsense").is_err());
+    }
+
+    #[test]
+    fn files_response_with_every_path() {
+        let files: Vec<FileEntry> = serde_json::from_str(
+            r#"[{"path": "lib/c.ex", "recompile_dependencies": [{
+                "id": "lib/a.ex_compile",
+                "path": "lib/a.ex",
+                "reason": "compile",
+                "dependency_chain": [["compile", "lib/a.ex", "lib/c.ex"]],
+                "dependency_chains": [
+                    [["compile", "lib/a.ex", "lib/c.ex"]],
+                    [["compile", "lib/a.ex", "lib/b.ex"], ["compile", "lib/b.ex", "lib/c.ex"]]
+                ],
+                "path_count": 2,
+                "more_paths": false
+            }]}]"#,
+        )
+        .unwrap();
+
+        let mut dependent = files[0].recompile_dependencies[0].clone();
+        assert_eq!(dependent.paths_label().unwrap(), "path 1/2");
+
+        assert!(dependent.cycle_chain(true));
+        assert_eq!(dependent.chain_index(), 1);
+        assert_eq!(dependent.dependency_chain[0].sink, "lib/b.ex");
+        assert_eq!(dependent.paths_label().unwrap(), "path 2/2");
+
+        dependent.paths.more_paths = true;
+        assert_eq!(dependent.paths_label().unwrap(), "path 2/2+");
+
+        // Going back from the first path wraps around to the last one
+        assert!(dependent.cycle_chain(false));
+        assert!(dependent.cycle_chain(false));
+        assert_eq!(dependent.chain_index(), 1);
+    }
+
+    #[test]
+    fn files_response_with_a_single_path() {
+        let files: Vec<FileEntry> = serde_json::from_str(
+            r#"[{"path": "lib/b.ex", "recompile_dependencies": [{
+                "id": "lib/a.ex_compile",
+                "path": "lib/a.ex",
+                "reason": "compile",
+                "dependency_chain": [["compile", "lib/a.ex", "lib/b.ex"]]
+            }]}]"#,
+        )
+        .unwrap();
+
+        let mut dependent = files[0].recompile_dependencies[0].clone();
+        assert_eq!(dependent.paths_label(), None);
+        assert!(!dependent.cycle_chain(true));
+    }
+}
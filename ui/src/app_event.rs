@@ -1,23 +1,100 @@
-use crate::{DependencyCause, DependencyLink, FileEntry, RecomplileDependency};
+use anyhow::Result;
+
+use crate::command::Command;
+use crate::components::search_input;
+use crate::{
+    DependencyCause, DependencyLink, FileEntry, FilePath, RecomplileDependency,
+    RecomplileDependencyReason,
+};
 
 #[derive(Debug)]
 pub enum AppEvent {
     UpButtonPressed,
     DownButtonPressed,
 
+    FocusFile(usize),
     SelectFile(FileEntry),
     SelectDependentFile(RecomplileDependency),
+    // (Index of the file, Index of the link in its dependency chain)
+    FocusDependentFile(usize, Option<usize>),
     ViewDependentFile(DependencyLink),
     StopViewDependentFile(DependencyLink),
+    OpenInEditor(FilePath, usize),
+    TogglePin(FilePath),
+    // Mark a file as changed for the what if report, or unmark it
+    ToggleMark(FilePath),
+    ClearMarks,
+    CycleFileSort,
+    ReverseFileSort,
+    // Change the width of the left panel by a number of percentage points
+    ResizePanel(i16),
+    PivotDependencySource(FilePath),
+    // Switch the dependents between the list and the tree
+    ToggleDependentsTree,
+    // Switch the files between the list and the directory tree
+    ToggleDirectoryTree,
+    // Group the dependents by recompile reason, or list them flat
+    ToggleDependentGrouping,
+    ExpandAllDependents,
+    CollapseAllDependents,
+    // Show the next path of a dependent, or the previous one: (Id of the dependent, forward)
+    CycleDependencyChain(String, bool),
+    // Fold or unfold the group of a reason, selecting its header
+    ToggleDependentGroup(RecomplileDependencyReason),
+    // Show or hide the dependents recompiled for a reason
+    ToggleReason(RecomplileDependencyReason),
+    // Apply the reason filter to the counts of the file panel, or not
+    ToggleFilteredCounts,
+    ToggleTreeNode(FilePath),
+    FocusTreeRow(usize),
+    // Reset the cause panel, then show the causes of a dependent of a source and the link being
+    // viewed, e.g. after going back in the history
+    ResetDependencyCauses(
+        Option<(FilePath, RecomplileDependency)>,
+        Option<DependencyLink>,
+    ),
+    ScrollCausesDown,
+    ScrollCausesUp,
+    ScrollWhyReportDown,
+    ScrollWhyReportUp,
+    CloseWhyReport,
+    ToggleImpactReport,
+    ScrollImpactReportDown,
+    ScrollImpactReportUp,
+    ToggleChangedFiles,
+    ScrollChangedFilesDown,
+    ScrollChangedFilesUp,
+    // Mark the files changed against the git ref, along with the files already marked
+    MarkChangedFiles,
 
     EnterSearch,
     SearchInput(char),
     SearchInputDelete,
+    SearchInputEdit(search_input::Edit),
     SubmitSearch,
 
+    EnterCommand,
+    CommandInput(char),
+    CommandInputDelete,
+    CommandInputEdit(search_input::Edit),
+    CompleteCommand,
+    SubmitCommand,
+    CancelCommand,
+    RunCommand(Command),
+
     GetFilesDone(Vec<FileEntry>),
-    GetDependencyCausesDone(Vec<DependencyCause>),
+    // (Id of the request, the causes or why they couldn't be collected)
+    GetDependencyCausesDone(usize, Result<Vec<DependencyCause>>),
+    // (Id of the why query, index of the relation, index of its chain, the causes of the chain)
+    GetWhyCausesDone(usize, usize, usize, Result<Vec<DependencyCause>>),
+    // (The git ref, the files changed since it or why git failed)
+    GetDiffDone(String, Result<Vec<FilePath>>),
+
+    HistoryBack,
+    HistoryForward,
 
+    ToggleHelp,
+    ToggleDashboard,
     Cancel,
     Quit,
 }
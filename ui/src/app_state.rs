@@ -1,14 +1,34 @@
 use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
 use ratatui::widgets::StatefulWidget;
+use std::collections::HashSet;
+use std::path::Path;
 use std::sync::mpsc;
+use std::thread;
 
 use crate::adapter::ServerAdapter;
 use crate::app_event::AppEvent;
-use crate::components::{dependency_cause_panel, file_dependent_panel, file_panel, search_input};
-use crate::{FileEntry, HandleEvent, ProduceEvent};
-
-#[derive(PartialEq, Debug)]
+use crate::command::{self, Command};
+use crate::components::file_dependent_panel::Cursor;
+use crate::components::message_line::Message;
+use crate::components::why_report::WhyReport;
+use crate::components::{
+    changed_files_panel, dependency_cause_panel, dependents_tree, file_dependent_panel, file_panel,
+    file_tree, impact_report, search_input, why_report,
+};
+use crate::export;
+use crate::file_sort::FileSort;
+use crate::git::{self, Diff};
+use crate::history::History;
+use crate::keymap::{self, Action, Scope};
+use crate::reason_filter::ReasonFilter;
+use crate::utils;
+use crate::{
+    DependencyLink, FileEntry, FilePath, HandleEvent, ProduceEvent, RecomplileDependency,
+    PROJECT_DIR,
+};
+
+#[derive(PartialEq, Debug, Clone)]
 pub enum StateMachine {
     FilePanelView,
     FileDependentsView,
@@ -17,15 +37,88 @@ pub enum StateMachine {
 pub struct GlobalState {
     pub state_machine: StateMachine,
     pub selected_dependency_source: Option<FileEntry>,
+    // Changes whenever the dependents of the selected file change: another file is selected, the
+    // files are collected again or a dependent shows another of its chains
+    pub dependents_generation: u64,
     pub file_panel_search: search_input::State,
     pub file_dependent_panel_search: search_input::State,
     pub files_list: Option<Vec<FileEntry>>,
+    // Changes whenever the files are collected again, what is computed from them is only
+    // computed again then
+    pub files_generation: u64,
+    pub show_help: bool,
+    // The statistics dashboard is drawn over the panels
+    pub show_dashboard: bool,
+    // The what if report of the marked files is drawn over the panels
+    pub show_impact: bool,
+    // The files of the why report drawn over the panels: the dependent, then the changed file
+    pub why_query: Option<(FilePath, FilePath)>,
+    pub history: History<View>,
+    // Earlier queries of each search, recalled in the prompt
+    pub file_panel_search_history: search_input::SearchHistory,
+    pub file_dependent_panel_search_history: search_input::SearchHistory,
+    // The `:` prompt of the command palette
+    pub command_prompt: search_input::State,
+    pub command_history: search_input::SearchHistory,
+    // Shown in the footer until the next key press
+    pub message: Option<Message>,
+    pub file_sort: FileSort,
+    pub file_sort_reversed: bool,
+    // The files are grouped by directory rather than listed
+    pub show_directory_tree: bool,
+    // Only the dependents recompiled for the reasons shown are listed
+    pub reason_filter: ReasonFilter,
+    // The counts of the file panel only include the dependents shown by the reason filter
+    pub filter_file_counts: bool,
+    // The dependents are shown as a tree rather than a list
+    pub show_dependents_tree: bool,
+    // The dependents are listed under a header for each recompile reason
+    pub group_dependents: bool,
+    // Pinned files are listed first in the file panel
+    pub pinned_files: Vec<FilePath>,
+    // Files marked as changed, the what if report shows the files they recompile
+    pub marked_files: Vec<FilePath>,
+    // Changes whenever the marks change, the what if report is only computed again then
+    pub marks_revision: u64,
+    // The files changed against a git ref by the last `:diff`, kept apart from the marks
+    pub diff: Option<Diff>,
+    // Changes with every diff, what the changed files recompile is only computed again then
+    pub diff_revision: u64,
+    // The changed files panel is drawn over the panels
+    pub show_changed_files: bool,
+    pub left_panel_percentage: u16,
+}
+
+const DEFAULT_PANEL_PERCENTAGE: u16 = 50;
+const PANEL_RESIZE_STEP: i16 = 5;
+const MIN_PANEL_PERCENTAGE: u16 = 20;
+const MAX_PANEL_PERCENTAGE: u16 = 80;
+
+pub fn clamp_panel_percentage(percentage: u16) -> u16 {
+    percentage.clamp(MIN_PANEL_PERCENTAGE, MAX_PANEL_PERCENTAGE)
+}
+
+/// A snapshot of what is on screen, recorded in the history when navigating away from it
+#[derive(Debug, Clone)]
+pub struct View {
+    state_machine: StateMachine,
+    dependency_source: Option<FileEntry>,
+    file_panel_search: search_input::State,
+    file_dependent_panel_search: search_input::State,
+    selected_file_index: usize,
+    selected_dependent: Cursor,
+    expanded_dependents: HashSet<String>,
 }
 
 pub struct AppState {
     pub file_panel: file_panel::State,
     pub file_dependent_panel: file_dependent_panel::State,
     pub dependency_cause_panel: dependency_cause_panel::State,
+    pub dependents_tree: dependents_tree::State,
+    pub file_tree: file_tree::State,
+    pub why_report: why_report::State,
+    pub impact_report: impact_report::State,
+    pub changed_files_panel: changed_files_panel::State,
     pub global: GlobalState,
 }
 
@@ -35,17 +128,281 @@ impl AppState {
             file_panel: file_panel::State::new(),
             file_dependent_panel: file_dependent_panel::State::new(),
             dependency_cause_panel: dependency_cause_panel::State::new(),
+            dependents_tree: dependents_tree::State::new(),
+            file_tree: file_tree::State::new(),
+            why_report: why_report::State::new(),
+            impact_report: impact_report::State::new(),
+            changed_files_panel: changed_files_panel::State::new(),
             global: GlobalState {
                 state_machine: StateMachine::FilePanelView,
                 selected_dependency_source: None,
+                dependents_generation: 0,
 
                 file_panel_search: search_input::State::default(),
                 file_dependent_panel_search: search_input::State::default(),
 
                 files_list: None,
+                files_generation: 0,
+                show_help: false,
+                show_dashboard: false,
+                show_impact: false,
+                why_query: None,
+                history: History::new(),
+                file_panel_search_history: search_input::SearchHistory::new(),
+                file_dependent_panel_search_history: search_input::SearchHistory::new(),
+                command_prompt: search_input::State::default(),
+                command_history: search_input::SearchHistory::new(),
+                message: None,
+                file_sort: FileSort::default(),
+                file_sort_reversed: false,
+                show_directory_tree: false,
+                reason_filter: ReasonFilter::new(),
+                filter_file_counts: false,
+                show_dependents_tree: false,
+                group_dependents: false,
+                pinned_files: vec![],
+                marked_files: vec![],
+                marks_revision: 0,
+                diff: None,
+                diff_revision: 0,
+                show_changed_files: false,
+                left_panel_percentage: DEFAULT_PANEL_PERCENTAGE,
             },
         }
     }
+
+    fn view(&self) -> View {
+        View {
+            state_machine: self.global.state_machine.clone(),
+            dependency_source: self.global.selected_dependency_source.clone(),
+            file_panel_search: self.global.file_panel_search.clone(),
+            file_dependent_panel_search: self.global.file_dependent_panel_search.clone(),
+            selected_file_index: self.file_panel.selected_file_index,
+            selected_dependent: self.file_dependent_panel.selected(),
+            expanded_dependents: self.file_dependent_panel.expanded().clone(),
+        }
+    }
+
+    // Put a view back on screen, then let the cause panel catch up with the restored selection
+    fn restore_view(&mut self, view: View, dispatcher: mpsc::Sender<AppEvent>) {
+        let (dependent, viewing_link) = restored_causes(
+            &view,
+            &self.global.reason_filter,
+            self.global.group_dependents,
+        );
+        let source = view
+            .dependency_source
+            .as_ref()
+            .map(|source| source.path.clone());
+
+        self.global.state_machine = view.state_machine;
+        self.global.select_dependency_source(view.dependency_source);
+        self.global.file_panel_search = view.file_panel_search;
+        self.global.file_dependent_panel_search = view.file_dependent_panel_search;
+        self.file_panel.selected_file_index = view.selected_file_index;
+        self.file_dependent_panel = file_dependent_panel::State::restore(
+            view.selected_dependent,
+            view.expanded_dependents,
+            dependent.as_ref().map(|dependent| dependent.id.clone()),
+        );
+
+        let dependent = source.zip(dependent);
+        dispatcher
+            .send(AppEvent::ResetDependencyCauses(dependent, viewing_link))
+            .unwrap();
+    }
+
+    fn pivot(&mut self, path: &FilePath, dispatcher: mpsc::Sender<AppEvent>) {
+        let same_source = self
+            .global
+            .selected_dependency_source
+            .as_ref()
+            .is_some_and(|source| source.path == *path);
+
+        let file_entry = self
+            .global
+            .files_list
+            .as_ref()
+            .and_then(|files| files.iter().find(|file| file.path == *path));
+
+        if let (false, Some(file_entry)) = (same_source, file_entry) {
+            let view = View {
+                state_machine: StateMachine::FileDependentsView,
+                dependency_source: Some(file_entry.clone()),
+                file_dependent_panel_search: search_input::State::default(),
+                selected_dependent: Cursor::File(0),
+                expanded_dependents: HashSet::new(),
+                ..self.view()
+            };
+
+            self.global.history.push(self.view());
+            self.restore_view(view, dispatcher);
+        }
+    }
+
+    fn set_reason_filter(&mut self, filter: ReasonFilter, dispatcher: mpsc::Sender<AppEvent>) {
+        self.global.reason_filter = filter;
+        // The selection indexes into the list which is now filtered differently
+        self.reset_dependent_selection(dispatcher);
+    }
+
+    fn reset_dependent_selection(&mut self, dispatcher: mpsc::Sender<AppEvent>) {
+        self.file_dependent_panel = file_dependent_panel::State::new();
+        dispatcher
+            .send(AppEvent::ResetDependencyCauses(None, None))
+            .unwrap();
+    }
+
+    fn run_command(
+        &mut self,
+        command: &Command,
+        adapter: &mut impl ServerAdapter,
+        dispatcher: mpsc::Sender<AppEvent>,
+    ) {
+        match command {
+            Command::Open(path) => {
+                let exists = self
+                    .global
+                    .files_list
+                    .as_ref()
+                    .is_some_and(|files| files.iter().any(|file| file.path == *path));
+
+                if exists {
+                    self.pivot(path, dispatcher);
+                } else {
+                    self.global.message = Some(Message::Error(format!("No file {}", path)));
+                }
+            }
+
+            Command::Sort(sort) => {
+                self.global.file_sort = *sort;
+                self.global.file_sort_reversed = false;
+            }
+
+            Command::Filter(reason) => {
+                let filter = match reason {
+                    Some(reason) => ReasonFilter::only(reason),
+                    None => ReasonFilter::new(),
+                };
+
+                self.set_reason_filter(filter, dispatcher);
+            }
+
+            Command::ExportDot(path) => {
+                // The graph of the selected file, or of the whole project
+                let files = match (
+                    &self.global.selected_dependency_source,
+                    &self.global.files_list,
+                ) {
+                    (Some(source), _) => vec![source.clone()],
+                    (None, Some(files)) => files.clone(),
+                    (None, None) => vec![],
+                };
+
+                self.global.message = Some(match std::fs::write(path, export::dot(&files)) {
+                    Ok(()) => Message::Info(format!("Exported to {}", path.display())),
+                    Err(error) => {
+                        Message::Error(format!("Failed to write {}: {}", path.display(), error))
+                    }
+                });
+            }
+
+            Command::Why(dependent, changed) => {
+                let files = self.global.files_list.as_deref().unwrap_or_default();
+                let unknown = [dependent, changed]
+                    .into_iter()
+                    .find(|path| !files.iter().any(|file| file.path == **path));
+
+                match unknown {
+                    Some(path) => {
+                        self.global.message = Some(Message::Error(format!("No file {}", path)));
+                    }
+                    None => {
+                        let report = WhyReport::new(dependent.clone(), changed.clone(), files);
+                        self.why_report.query(&report, adapter, dispatcher);
+                        self.global.why_query = Some((dependent.clone(), changed.clone()));
+                    }
+                }
+            }
+
+            // The changed files are matched against the files of the graph
+            Command::Diff(_) if self.global.files_list.is_none() => {
+                self.global.message = Some(Message::Error(String::from("Files are still loading")));
+            }
+
+            // git can take a while on a large repository, the interface keeps responding meanwhile
+            Command::Diff(base) => {
+                let base = base.clone();
+                self.global.message = Some(Message::Info(format!("Diffing against {}", base)));
+
+                thread::spawn(move || {
+                    let changed = git::changed_files(Path::new(PROJECT_DIR), &base);
+                    dispatcher
+                        .send(AppEvent::GetDiffDone(base, changed))
+                        .unwrap();
+                });
+            }
+
+            Command::ExpandAll | Command::CollapseAll
+                if self.global.state_machine != StateMachine::FileDependentsView =>
+            {
+                self.global.message = Some(Message::Error(String::from("No dependents shown")));
+            }
+
+            Command::ExpandAll => dispatcher.send(AppEvent::ExpandAllDependents).unwrap(),
+            Command::CollapseAll => dispatcher.send(AppEvent::CollapseAllDependents).unwrap(),
+
+            Command::Refresh => {
+                adapter.get_files(Box::new(move |files| {
+                    dispatcher.send(AppEvent::GetFilesDone(files)).unwrap();
+                }));
+                self.global.message = Some(Message::Info(String::from("Refreshing files")));
+            }
+
+            Command::Quit => dispatcher.send(AppEvent::Quit).unwrap(),
+        }
+    }
+}
+
+// The dependent the cause panel shows when a view is put back on screen: the one the cursor is
+// in when it is expanded, or else the first expanded one. Also the link the cursor is on
+fn restored_causes(
+    view: &View,
+    reason_filter: &ReasonFilter,
+    grouped: bool,
+) -> (Option<RecomplileDependency>, Option<DependencyLink>) {
+    let source = match (&view.state_machine, &view.dependency_source) {
+        (StateMachine::FileDependentsView, Some(source)) => source,
+        _ => return (None, None),
+    };
+
+    // The cursor indexes into the dependents list as filtered by the search and the reason
+    let mut dependents = reason_filter.filter(utils::filter_files_list(
+        &source.recompile_dependencies,
+        &view.file_dependent_panel_search,
+    ));
+    if grouped {
+        file_dependent_panel::sort_into_groups(&mut dependents);
+    }
+
+    let is_expanded =
+        |dependent: &&RecomplileDependency| view.expanded_dependents.contains(&dependent.id);
+    let cursor_dependent = view
+        .selected_dependent
+        .file_index()
+        .and_then(|index| dependents.get(index))
+        .filter(is_expanded);
+    let dependent = cursor_dependent.or_else(|| dependents.iter().find(is_expanded));
+
+    let viewing_link = match view.selected_dependent {
+        Cursor::Link(index, link) => dependents
+            .get(index)
+            .and_then(|dependent| dependent.dependency_chain.get(link))
+            .cloned(),
+        _ => None,
+    };
+
+    (dependent.cloned(), viewing_link)
 }
 
 pub struct NoopWidget;
@@ -63,19 +420,239 @@ impl HandleEvent for AppState {
         &mut self,
         event: &AppEvent,
         _widget: &Self::Widget,
-        _adapter: &mut impl ServerAdapter,
-        _dispatcher: mpsc::Sender<AppEvent>,
+        adapter: &mut impl ServerAdapter,
+        dispatcher: mpsc::Sender<AppEvent>,
     ) {
         match event {
             AppEvent::SelectFile(file_entry) => {
+                self.global.history.push(self.view());
                 self.global.state_machine = StateMachine::FileDependentsView;
-                self.global.selected_dependency_source = Some(file_entry.clone());
+                self.global
+                    .select_dependency_source(Some(file_entry.clone()));
+            }
+
+            AppEvent::PivotDependencySource(path) => self.pivot(path, dispatcher),
+
+            AppEvent::HistoryBack if self.global.history.can_go_back() => {
+                let current = self.view();
+                if let Some(view) = self.global.history.back(current) {
+                    self.restore_view(view, dispatcher);
+                }
+            }
+
+            AppEvent::HistoryForward if self.global.history.can_go_forward() => {
+                let current = self.view();
+                if let Some(view) = self.global.history.forward(current) {
+                    self.restore_view(view, dispatcher);
+                }
+            }
+
+            AppEvent::TogglePin(path) => {
+                let pinned_files = &mut self.global.pinned_files;
+
+                match pinned_files.iter().position(|pinned| pinned == path) {
+                    Some(index) => {
+                        pinned_files.remove(index);
+                    }
+                    None => pinned_files.push(path.clone()),
+                }
             }
 
+            AppEvent::ToggleMark(path) => {
+                self.global.marks_revision += 1;
+                let marked_files = &mut self.global.marked_files;
+
+                match marked_files.iter().position(|marked| marked == path) {
+                    Some(index) => {
+                        marked_files.remove(index);
+                    }
+                    None => marked_files.push(path.clone()),
+                }
+            }
+
+            AppEvent::ClearMarks => {
+                self.global.marked_files.clear();
+                self.global.marks_revision += 1;
+            }
+
+            AppEvent::ResizePanel(delta) => {
+                let percentage = self.global.left_panel_percentage as i16 + delta;
+                self.global.left_panel_percentage =
+                    clamp_panel_percentage(percentage.max(0) as u16);
+            }
+
+            AppEvent::CycleFileSort => {
+                self.global.file_sort = self.global.file_sort.next();
+                self.global.file_sort_reversed = false;
+            }
+
+            AppEvent::ReverseFileSort => {
+                self.global.file_sort_reversed = !self.global.file_sort_reversed;
+            }
+
+            AppEvent::ToggleReason(reason) => {
+                let mut filter = self.global.reason_filter.clone();
+                filter.toggle(reason);
+                self.set_reason_filter(filter, dispatcher);
+            }
+
+            AppEvent::ToggleFilteredCounts => {
+                self.global.filter_file_counts = !self.global.filter_file_counts;
+            }
+
+            AppEvent::ToggleDirectoryTree => {
+                self.global.show_directory_tree = !self.global.show_directory_tree;
+            }
+
+            AppEvent::ToggleDependentsTree => {
+                self.global.show_dependents_tree = !self.global.show_dependents_tree;
+            }
+
+            AppEvent::CycleDependencyChain(id, forward) => {
+                // The chain shown is kept with the dependents, e.g. across the history
+                if let Some(ref mut source) = self.global.selected_dependency_source {
+                    let dependents = &mut source.recompile_dependencies;
+                    if let Some(dependent) = dependents.iter_mut().find(|d| d.id == *id) {
+                        dependent.cycle_chain(*forward);
+                        self.global.dependents_generation += 1;
+                    }
+                }
+            }
+
+            AppEvent::ToggleDependentGrouping => {
+                self.global.group_dependents = !self.global.group_dependents;
+                self.reset_dependent_selection(dispatcher);
+            }
+
+            AppEvent::ToggleHelp => {
+                self.global.show_help = !self.global.show_help;
+            }
+
+            AppEvent::ToggleDashboard => {
+                self.global.show_dashboard = !self.global.show_dashboard;
+            }
+
+            AppEvent::ToggleImpactReport => {
+                self.global.show_impact = !self.global.show_impact;
+            }
+
+            AppEvent::ToggleChangedFiles if self.global.diff.is_none() => {
+                self.global.message = Some(Message::Error(String::from(
+                    "No changed files, diff against a git ref first",
+                )));
+            }
+
+            AppEvent::ToggleChangedFiles => {
+                self.global.show_changed_files = !self.global.show_changed_files;
+            }
+
+            AppEvent::MarkChangedFiles => {
+                if let Some(ref diff) = self.global.diff {
+                    let marked_files = &mut self.global.marked_files;
+                    for path in &diff.files {
+                        if !marked_files.contains(path) {
+                            marked_files.push(path.clone());
+                        }
+                    }
+
+                    self.global.marks_revision += 1;
+                    self.global.message = Some(Message::Info(format!(
+                        "Marked {} changed files",
+                        diff.files.len()
+                    )));
+                }
+            }
+
+            AppEvent::CloseWhyReport => self.global.why_query = None,
+
+            AppEvent::GetDiffDone(base, changed) => match changed {
+                Ok(changed) => {
+                    let files = self.global.files_list.as_deref().unwrap_or_default();
+                    self.global.diff = Some(Diff {
+                        base: base.clone(),
+                        files: git::changed_sources(changed, files),
+                    });
+                    self.global.diff_revision += 1;
+                    self.global.message = Some(Message::Info(format!(
+                        "{} files changed against {}",
+                        changed.len(),
+                        base
+                    )));
+
+                    // Each diff is shown from the top, in place of the what if report
+                    self.global.show_impact = false;
+                    self.global.show_changed_files = true;
+                    self.changed_files_panel = changed_files_panel::State::new();
+                }
+                Err(error) => self.global.message = Some(Message::Error(format!("{:#}", error))),
+            },
+
             AppEvent::GetFilesDone(files) => {
+                // After a refresh, the selected file shows its new dependents
+                if let Some(ref source) = self.global.selected_dependency_source {
+                    if let Some(file) = files.iter().find(|file| file.path == source.path) {
+                        self.global.select_dependency_source(Some(file.clone()));
+                    }
+                }
+
                 self.global.files_list = Some(files.clone());
+                self.global.files_generation += 1;
             }
 
+            AppEvent::EnterCommand => self.global.command_prompt.prompt_begin(),
+            AppEvent::CommandInput(char) => self
+                .global
+                .command_prompt
+                .prompt_add(*char, &mut self.global.command_history),
+            AppEvent::CommandInputDelete => self
+                .global
+                .command_prompt
+                .prompt_remove(&mut self.global.command_history),
+            AppEvent::CommandInputEdit(edit) => self
+                .global
+                .command_prompt
+                .edit(*edit, &mut self.global.command_history),
+
+            AppEvent::CompleteCommand => {
+                let files = self.global.files_list.as_deref().unwrap_or_default();
+                let completed = self
+                    .global
+                    .command_prompt
+                    .prompt_input()
+                    .and_then(|input| command::complete(&input, files));
+
+                if let Some(completed) = completed {
+                    self.global.command_history.reset();
+                    self.global.command_prompt = search_input::State::prompt(&completed);
+                }
+            }
+
+            AppEvent::SubmitCommand => {
+                let input = self
+                    .global
+                    .command_prompt
+                    .prompt_input()
+                    .unwrap_or_default();
+                self.global
+                    .command_prompt
+                    .search(&mut self.global.command_history);
+                self.global
+                    .command_prompt
+                    .cancel(&mut self.global.command_history);
+
+                match Command::parse(&input) {
+                    Ok(command) => dispatcher.send(AppEvent::RunCommand(command)).unwrap(),
+                    Err(error) => self.global.message = Some(Message::Error(error.to_string())),
+                }
+            }
+
+            AppEvent::CancelCommand => self
+                .global
+                .command_prompt
+                .cancel(&mut self.global.command_history),
+
+            AppEvent::RunCommand(command) => self.run_command(command, adapter, dispatcher),
+
             AppEvent::EnterSearch => match self.global.state_machine {
                 StateMachine::FilePanelView => {
                     self.global.file_panel_search.prompt_begin();
@@ -88,43 +665,72 @@ impl HandleEvent for AppState {
 
             AppEvent::SearchInput(char) => match self.global.state_machine {
                 StateMachine::FilePanelView => {
-                    self.global.file_panel_search.prompt_add(*char);
+                    self.global
+                        .file_panel_search
+                        .prompt_add(*char, &mut self.global.file_panel_search_history);
                 }
 
                 StateMachine::FileDependentsView => {
-                    self.global.file_dependent_panel_search.prompt_add(*char);
+                    self.global
+                        .file_dependent_panel_search
+                        .prompt_add(*char, &mut self.global.file_dependent_panel_search_history);
                 }
             },
 
             AppEvent::SearchInputDelete => match self.global.state_machine {
                 StateMachine::FilePanelView => {
-                    self.global.file_panel_search.prompt_remove();
+                    self.global
+                        .file_panel_search
+                        .prompt_remove(&mut self.global.file_panel_search_history);
                 }
 
                 StateMachine::FileDependentsView => {
-                    self.global.file_dependent_panel_search.prompt_remove();
+                    self.global
+                        .file_dependent_panel_search
+                        .prompt_remove(&mut self.global.file_dependent_panel_search_history);
                 }
             },
 
+            AppEvent::SearchInputEdit(edit) => match self.global.state_machine {
+                StateMachine::FilePanelView => self
+                    .global
+                    .file_panel_search
+                    .edit(*edit, &mut self.global.file_panel_search_history),
+
+                StateMachine::FileDependentsView => self
+                    .global
+                    .file_dependent_panel_search
+                    .edit(*edit, &mut self.global.file_dependent_panel_search_history),
+            },
+
             AppEvent::SubmitSearch => match self.global.state_machine {
-                StateMachine::FilePanelView => self.global.file_panel_search.search(),
-                StateMachine::FileDependentsView => {
-                    self.global.file_dependent_panel_search.search()
-                }
+                StateMachine::FilePanelView => self
+                    .global
+                    .file_panel_search
+                    .search(&mut self.global.file_panel_search_history),
+
+                StateMachine::FileDependentsView => self
+                    .global
+                    .file_dependent_panel_search
+                    .search(&mut self.global.file_dependent_panel_search_history),
             },
 
             AppEvent::Cancel if self.global.state_machine == StateMachine::FilePanelView => {
                 if self.global.file_panel_search.is_active() {
-                    self.global.file_panel_search.cancel();
+                    self.global
+                        .file_panel_search
+                        .cancel(&mut self.global.file_panel_search_history);
                 }
             }
 
             AppEvent::Cancel if self.global.state_machine == StateMachine::FileDependentsView => {
                 if self.global.file_dependent_panel_search.is_active() {
-                    self.global.file_dependent_panel_search.cancel();
+                    self.global
+                        .file_dependent_panel_search
+                        .cancel(&mut self.global.file_dependent_panel_search_history);
                 } else {
                     self.global.state_machine = StateMachine::FilePanelView;
-                    self.global.selected_dependency_source = None;
+                    self.global.select_dependency_source(None);
                 }
             }
 
@@ -133,6 +739,37 @@ impl HandleEvent for AppState {
     }
 }
 
+impl GlobalState {
+    /// Select the file the dependents panel lists the dependents of
+    pub fn select_dependency_source(&mut self, source: Option<FileEntry>) {
+        self.selected_dependency_source = source;
+        self.dependents_generation += 1;
+    }
+
+    pub fn is_prompting(&self) -> bool {
+        self.command_prompt.is_prompting()
+            || self.file_panel_search.is_prompting()
+            || self.file_dependent_panel_search.is_prompting()
+    }
+
+    /// Returns the keymap scopes which are active in the current state. A prompt takes over
+    /// every other scope while it's active
+    pub fn active_scopes(&self) -> Vec<Scope> {
+        if self.is_prompting() {
+            return vec![Scope::Prompt];
+        }
+
+        match self.state_machine {
+            StateMachine::FilePanelView => vec![Scope::Global, Scope::FilePanel],
+            StateMachine::FileDependentsView => vec![
+                Scope::Global,
+                Scope::FileDependentPanel,
+                Scope::DependencyCausePanel,
+            ],
+        }
+    }
+}
+
 impl ProduceEvent for GlobalState {
     type Widget = NoopWidget;
 
@@ -143,40 +780,119 @@ impl ProduceEvent for GlobalState {
     ) -> Option<AppEvent> {
         if let crossterm::event::Event::Key(key) = terminal_event {
             if key.kind == crossterm::event::KeyEventKind::Press {
-                return match key.code {
-                    crossterm::event::KeyCode::Char(char)
-                        if self.file_panel_search.is_prompting()
-                            || self.file_dependent_panel_search.is_prompting() =>
-                    {
-                        Some(AppEvent::SearchInput(char))
-                    }
+                let keymap = keymap::current();
+                self.message = None;
+
+                // The help overlay swallows all keys, except the ones closing it
+                if self.show_help {
+                    return match keymap.action(Scope::Global, key) {
+                        Some(Action::ToggleHelp) | Some(Action::Cancel) => {
+                            Some(AppEvent::ToggleHelp)
+                        }
+                        _ => None,
+                    };
+                }
 
-                    crossterm::event::KeyCode::Backspace
-                        if self.file_panel_search.is_prompting()
-                            || self.file_dependent_panel_search.is_prompting() =>
-                    {
-                        Some(AppEvent::SearchInputDelete)
-                    }
+                // So does the dashboard, it can't be navigated
+                if self.show_dashboard {
+                    return match keymap.action(Scope::Global, key) {
+                        Some(Action::ToggleDashboard) | Some(Action::Cancel) => {
+                            Some(AppEvent::ToggleDashboard)
+                        }
+                        Some(Action::Quit) => Some(AppEvent::Quit),
+                        _ => None,
+                    };
+                }
 
-                    crossterm::event::KeyCode::Enter
-                        if self.file_panel_search.is_prompting()
-                            || self.file_dependent_panel_search.is_prompting() =>
-                    {
-                        Some(AppEvent::SubmitSearch)
-                    }
+                // The what if report can only be scrolled, the marks are changed in the file panel
+                if self.show_impact {
+                    return match keymap.action(Scope::Global, key) {
+                        Some(Action::MoveDown) => Some(AppEvent::ScrollImpactReportDown),
+                        Some(Action::MoveUp) => Some(AppEvent::ScrollImpactReportUp),
+                        Some(Action::ToggleImpact) | Some(Action::Cancel) => {
+                            Some(AppEvent::ToggleImpactReport)
+                        }
+                        Some(Action::Quit) => Some(AppEvent::Quit),
+                        _ => None,
+                    };
+                }
 
-                    crossterm::event::KeyCode::Char('j') | crossterm::event::KeyCode::Down => {
-                        Some(AppEvent::DownButtonPressed)
+                // The changed files panel can be scrolled, and its files marked with the key
+                // marking files in the file panel
+                if self.show_changed_files {
+                    if keymap.action(Scope::FilePanel, key) == Some(Action::Mark) {
+                        return Some(AppEvent::MarkChangedFiles);
                     }
 
-                    crossterm::event::KeyCode::Char('k') | crossterm::event::KeyCode::Up => {
-                        Some(AppEvent::UpButtonPressed)
-                    }
+                    return match keymap.action(Scope::Global, key) {
+                        Some(Action::MoveDown) => Some(AppEvent::ScrollChangedFilesDown),
+                        Some(Action::MoveUp) => Some(AppEvent::ScrollChangedFilesUp),
+                        Some(Action::ToggleChangedFiles) | Some(Action::Cancel) => {
+                            Some(AppEvent::ToggleChangedFiles)
+                        }
+                        Some(Action::Quit) => Some(AppEvent::Quit),
+                        _ => None,
+                    };
+                }
+
+                if self.command_prompt.is_prompting() {
+                    return match keymap.action(Scope::Prompt, key) {
+                        Some(Action::SearchDelete) => Some(AppEvent::CommandInputDelete),
+                        Some(Action::SubmitSearch) => Some(AppEvent::SubmitCommand),
+                        Some(Action::Complete) => Some(AppEvent::CompleteCommand),
+                        Some(Action::Cancel) => Some(AppEvent::CancelCommand),
+                        Some(action) => prompt_edit(action).map(AppEvent::CommandInputEdit),
+                        None => match key.code {
+                            crossterm::event::KeyCode::Char(char) => {
+                                Some(AppEvent::CommandInput(char))
+                            }
+                            _ => None,
+                        },
+                    };
+                }
+
+                // The why report is scrolled, or replaced by another from the command prompt
+                if self.why_query.is_some() {
+                    return match keymap.action(Scope::Global, key) {
+                        Some(Action::MoveDown) => Some(AppEvent::ScrollWhyReportDown),
+                        Some(Action::MoveUp) => Some(AppEvent::ScrollWhyReportUp),
+                        Some(Action::Command) => Some(AppEvent::EnterCommand),
+                        Some(Action::Cancel) => Some(AppEvent::CloseWhyReport),
+                        Some(Action::Quit) => Some(AppEvent::Quit),
+                        _ => None,
+                    };
+                }
 
-                    crossterm::event::KeyCode::Char('/') => Some(AppEvent::EnterSearch),
-                    crossterm::event::KeyCode::Esc => Some(AppEvent::Cancel),
+                if self.is_prompting() {
+                    return match keymap.action(Scope::Prompt, key) {
+                        Some(Action::SearchDelete) => Some(AppEvent::SearchInputDelete),
+                        Some(Action::SubmitSearch) => Some(AppEvent::SubmitSearch),
+                        Some(Action::Cancel) => Some(AppEvent::Cancel),
+                        Some(action) => prompt_edit(action).map(AppEvent::SearchInputEdit),
+                        None => match key.code {
+                            crossterm::event::KeyCode::Char(char) => {
+                                Some(AppEvent::SearchInput(char))
+                            }
+                            _ => None,
+                        },
+                    };
+                }
 
-                    crossterm::event::KeyCode::Char('q') => Some(AppEvent::Quit),
+                return match keymap.action(Scope::Global, key) {
+                    Some(Action::MoveDown) => Some(AppEvent::DownButtonPressed),
+                    Some(Action::MoveUp) => Some(AppEvent::UpButtonPressed),
+                    Some(Action::Search) => Some(AppEvent::EnterSearch),
+                    Some(Action::Command) => Some(AppEvent::EnterCommand),
+                    Some(Action::HistoryBack) => Some(AppEvent::HistoryBack),
+                    Some(Action::ShrinkPanel) => Some(AppEvent::ResizePanel(-PANEL_RESIZE_STEP)),
+                    Some(Action::GrowPanel) => Some(AppEvent::ResizePanel(PANEL_RESIZE_STEP)),
+                    Some(Action::HistoryForward) => Some(AppEvent::HistoryForward),
+                    Some(Action::ToggleHelp) => Some(AppEvent::ToggleHelp),
+                    Some(Action::ToggleDashboard) => Some(AppEvent::ToggleDashboard),
+                    Some(Action::ToggleImpact) => Some(AppEvent::ToggleImpactReport),
+                    Some(Action::ToggleChangedFiles) => Some(AppEvent::ToggleChangedFiles),
+                    Some(Action::Cancel) => Some(AppEvent::Cancel),
+                    Some(Action::Quit) => Some(AppEvent::Quit),
                     _ => None,
                 };
             }
@@ -186,6 +902,20 @@ impl ProduceEvent for GlobalState {
     }
 }
 
+fn prompt_edit(action: Action) -> Option<search_input::Edit> {
+    match action {
+        Action::CursorLeft => Some(search_input::Edit::CursorLeft),
+        Action::CursorRight => Some(search_input::Edit::CursorRight),
+        Action::CursorHome => Some(search_input::Edit::CursorHome),
+        Action::CursorEnd => Some(search_input::Edit::CursorEnd),
+        Action::DeleteWord => Some(search_input::Edit::DeleteWord),
+        Action::ClearLine => Some(search_input::Edit::ClearLine),
+        Action::PreviousQuery => Some(search_input::Edit::PreviousQuery),
+        Action::NextQuery => Some(search_input::Edit::NextQuery),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod handle_event_tests {
     use super::*;
@@ -253,7 +983,7 @@ mod handle_event_tests {
     #[test]
     fn search_input() {
         let mut state = AppState::new();
-        state.global.file_panel_search = search_input::State::Prompt(String::new());
+        state.global.file_panel_search = search_input::State::prompt("");
 
         let event_a = AppEvent::SearchInput('f');
         let event_b = AppEvent::SearchInput('o');
@@ -274,7 +1004,7 @@ mod handle_event_tests {
     #[test]
     fn search_input_delete() {
         let mut state = AppState::new();
-        state.global.file_panel_search = search_input::State::Prompt(String::from("foo"));
+        state.global.file_panel_search = search_input::State::prompt("foo");
 
         let (tx, rx) = mpsc::channel::<AppEvent>();
         state.handle_event(
@@ -327,7 +1057,7 @@ mod handle_event_tests {
     #[test]
     fn search_submit() {
         let mut state = AppState::new();
-        state.global.file_panel_search = search_input::State::Prompt(String::from("foo"));
+        state.global.file_panel_search = search_input::State::prompt("foo");
 
         let (tx, rx) = mpsc::channel::<AppEvent>();
         state.handle_event(
@@ -346,7 +1076,7 @@ mod handle_event_tests {
     #[test]
     fn cancel_search() {
         let mut state = AppState::new();
-        state.global.file_panel_search = search_input::State::Prompt(String::from("foo"));
+        state.global.file_panel_search = search_input::State::prompt("foo");
 
         let event = AppEvent::Cancel;
         let (tx, rx) = mpsc::channel::<AppEvent>();
@@ -359,7 +1089,7 @@ mod handle_event_tests {
     #[test]
     fn submit_search_select_file_then_search_again() {
         let mut state = AppState::new();
-        state.global.file_panel_search = search_input::State::Prompt(String::from("foo"));
+        state.global.file_panel_search = search_input::State::prompt("foo");
 
         let (tx, rx) = mpsc::channel::<AppEvent>();
 
@@ -401,7 +1131,7 @@ mod handle_event_tests {
     #[test]
     fn submit_search_select_file_then_cancel_search() {
         let mut state = AppState::new();
-        state.global.file_panel_search = search_input::State::Prompt(String::from("foo"));
+        state.global.file_panel_search = search_input::State::prompt("foo");
 
         let (tx, rx) = mpsc::channel::<AppEvent>();
 
@@ -430,4 +1160,732 @@ mod handle_event_tests {
         assert_eq!(state.global.state_machine, StateMachine::FilePanelView);
         assert_eq!(collect_events(rx).len(), 0);
     }
+
+    #[test]
+    fn toggle_pin() {
+        let mut state = AppState::new();
+
+        let (tx, _) = mpsc::channel::<AppEvent>();
+        let events = [
+            AppEvent::TogglePin(String::from("foo")),
+            AppEvent::TogglePin(String::from("bar")),
+            AppEvent::TogglePin(String::from("foo")),
+        ];
+        dispatch_events(&mut state, &events, tx);
+
+        assert_eq!(state.global.pinned_files, vec![String::from("bar")]);
+    }
+
+    #[test]
+    fn toggle_and_clear_marks() {
+        let mut state = AppState::new();
+
+        let (tx, _) = mpsc::channel::<AppEvent>();
+        let events = [
+            AppEvent::ToggleMark(String::from("foo")),
+            AppEvent::ToggleMark(String::from("bar")),
+            AppEvent::ToggleMark(String::from("baz")),
+            AppEvent::ToggleMark(String::from("foo")),
+        ];
+        dispatch_events(&mut state, &events, tx.clone());
+        assert_eq!(
+            state.global.marked_files,
+            vec![String::from("bar"), String::from("baz")]
+        );
+        assert_eq!(state.global.marks_revision, 4);
+
+        dispatch_events(&mut state, &[AppEvent::ClearMarks], tx);
+        assert!(state.global.marked_files.is_empty());
+        assert_eq!(state.global.marks_revision, 5);
+    }
+
+    #[test]
+    fn resize_panel() {
+        let mut state = AppState::new();
+
+        let (tx, _) = mpsc::channel::<AppEvent>();
+        dispatch_events(&mut state, &[AppEvent::ResizePanel(5)], tx.clone());
+        assert_eq!(state.global.left_panel_percentage, 55);
+
+        dispatch_events(&mut state, &[AppEvent::ResizePanel(-100)], tx);
+        assert_eq!(state.global.left_panel_percentage, MIN_PANEL_PERCENTAGE);
+    }
+
+    mod history {
+        use super::*;
+        use crate::{DependencyPaths, RecomplileDependency, RecomplileDependencyReason};
+
+        fn dependent(path: &str, chain: &[&str]) -> RecomplileDependency {
+            let files = [&["source"], chain].concat();
+            RecomplileDependency::fixture(path).with_chain_through(&files)
+        }
+
+        // "a" is recompiled by "c" through "b", "b" is recompiled by "c"
+        fn state_viewing_a() -> AppState {
+            let mut state = AppState::new();
+            state.global.files_list = Some(vec![
+                FileEntry::fixture("a", vec![dependent("c", &["b", "c"])]),
+                FileEntry::fixture("b", vec![dependent("c", &["c"])]),
+                FileEntry::fixture("c", vec![]),
+            ]);
+
+            let (tx, _) = mpsc::channel::<AppEvent>();
+            let a = state.global.files_list.as_ref().unwrap()[0].clone();
+            dispatch_events(&mut state, &[AppEvent::SelectFile(a)], tx);
+
+            state.file_dependent_panel = file_dependent_panel::State::restore(
+                Cursor::Link(0, 0),
+                HashSet::from([String::from("c_compile")]),
+                Some(String::from("c_compile")),
+            );
+            state.global.file_dependent_panel_search =
+                search_input::State::Search(String::from("c"));
+
+            state
+        }
+
+        fn source_path(state: &AppState) -> Option<String> {
+            state
+                .global
+                .selected_dependency_source
+                .as_ref()
+                .map(|source| source.path.clone())
+        }
+
+        #[test]
+        fn pivot() {
+            let mut state = state_viewing_a();
+
+            let (tx, rx) = mpsc::channel::<AppEvent>();
+            let event = AppEvent::PivotDependencySource(String::from("b"));
+            dispatch_events(&mut state, &[event], tx);
+
+            assert_eq!(source_path(&state), Some(String::from("b")));
+            assert_eq!(state.global.state_machine, StateMachine::FileDependentsView);
+            assert_eq!(
+                state.global.file_dependent_panel_search,
+                search_input::State::None
+            );
+            assert_eq!(state.file_dependent_panel.selected(), Cursor::File(0));
+            assert!(state.file_dependent_panel.expanded().is_empty());
+            assert!(matches!(
+                &collect_events(rx)[..],
+                [AppEvent::ResetDependencyCauses(None, None)]
+            ));
+        }
+
+        #[test]
+        fn chain_shown_is_kept_in_history() {
+            let mut state = state_viewing_a();
+            let source = state.global.selected_dependency_source.as_mut().unwrap();
+            source.recompile_dependencies[0].paths = DependencyPaths {
+                dependency_chains: vec![
+                    dependent("c", &["b", "c"]).dependency_chain,
+                    dependent("c", &["c"]).dependency_chain,
+                ],
+                path_count: 2,
+                ..Default::default()
+            };
+
+            let (tx, _rx) = mpsc::channel::<AppEvent>();
+            let events = [
+                AppEvent::CycleDependencyChain(String::from("c_compile"), true),
+                AppEvent::PivotDependencySource(String::from("b")),
+                AppEvent::HistoryBack,
+            ];
+            dispatch_events(&mut state, &events, tx);
+
+            let source = state.global.selected_dependency_source.unwrap();
+            assert_eq!(source.path, "a");
+            assert_eq!(source.recompile_dependencies[0].chain_index(), 1);
+            assert_eq!(source.recompile_dependencies[0].dependency_chain.len(), 1);
+        }
+
+        #[test]
+        fn shown_chains_change_the_dependents() {
+            let mut state = state_viewing_a();
+            let generation = state.global.dependents_generation;
+
+            let (tx, _rx) = mpsc::channel::<AppEvent>();
+            let event = AppEvent::CycleDependencyChain(String::from("c_compile"), true);
+            dispatch_events(&mut state, &[event], tx.clone());
+            assert!(state.global.dependents_generation > generation);
+
+            let generation = state.global.dependents_generation;
+            dispatch_events(&mut state, &[AppEvent::HistoryBack], tx);
+            assert!(state.global.dependents_generation > generation);
+        }
+
+        #[test]
+        fn pivot_to_unknown_file() {
+            let mut state = state_viewing_a();
+
+            let (tx, rx) = mpsc::channel::<AppEvent>();
+            let event = AppEvent::PivotDependencySource(String::from("unknown"));
+            dispatch_events(&mut state, &[event], tx);
+
+            assert_eq!(source_path(&state), Some(String::from("a")));
+            assert!(collect_events(rx).is_empty());
+        }
+
+        #[test]
+        fn back_restores_view() {
+            let mut state = state_viewing_a();
+
+            let (tx, rx) = mpsc::channel::<AppEvent>();
+            let events = [
+                AppEvent::PivotDependencySource(String::from("b")),
+                AppEvent::HistoryBack,
+            ];
+            dispatch_events(&mut state, &events, tx);
+
+            assert_eq!(source_path(&state), Some(String::from("a")));
+            assert_eq!(
+                state.global.file_dependent_panel_search,
+                search_input::State::Search(String::from("c"))
+            );
+            assert_eq!(state.file_dependent_panel.selected(), Cursor::Link(0, 0));
+            assert!(state.file_dependent_panel.expanded().contains("c_compile"));
+
+            let events = collect_events(rx);
+            match &events[..] {
+                [_, AppEvent::ResetDependencyCauses(Some((source, dependent)), Some(link))] => {
+                    assert_eq!(source, "a");
+                    assert_eq!(dependent.id, "c_compile");
+                    assert_eq!(link.sink, "b");
+                }
+
+                _ => panic!("Expected ResetDependencyCauses event"),
+            }
+        }
+
+        #[test]
+        fn back_restores_causes_of_grouped_dependents() {
+            // Listed by recompile reason, "e" comes before "d"
+            let d = dependent("d", &["d"]).with_reason(RecomplileDependencyReason::Exports);
+            let e = dependent("e", &["e"]);
+
+            let mut state = AppState::new();
+            state.global.group_dependents = true;
+            state.global.files_list = Some(vec![
+                FileEntry::fixture("a", vec![d, e]),
+                FileEntry::fixture("b", vec![]),
+            ]);
+
+            let (tx, rx) = mpsc::channel::<AppEvent>();
+            let a = state.global.files_list.as_ref().unwrap()[0].clone();
+            dispatch_events(&mut state, &[AppEvent::SelectFile(a)], tx.clone());
+            state.file_dependent_panel = file_dependent_panel::State::restore(
+                Cursor::File(0),
+                HashSet::from([String::from("d_exports"), String::from("e_compile")]),
+                Some(String::from("e_compile")),
+            );
+
+            let events = [
+                AppEvent::PivotDependencySource(String::from("b")),
+                AppEvent::HistoryBack,
+            ];
+            dispatch_events(&mut state, &events, tx);
+
+            match collect_events(rx).last() {
+                Some(AppEvent::ResetDependencyCauses(Some((source, dependent)), None)) => {
+                    assert_eq!(source, "a");
+                    assert_eq!(dependent.id, "e_compile");
+                }
+
+                _ => panic!("Expected ResetDependencyCauses event"),
+            }
+        }
+
+        #[test]
+        fn back_to_file_panel_and_forward() {
+            let mut state = state_viewing_a();
+            state.file_panel.selected_file_index = 0;
+
+            let (tx, _rx) = mpsc::channel::<AppEvent>();
+            dispatch_events(&mut state, &[AppEvent::HistoryBack], tx.clone());
+            assert_eq!(state.global.state_machine, StateMachine::FilePanelView);
+            assert!(state.global.selected_dependency_source.is_none());
+
+            dispatch_events(&mut state, &[AppEvent::HistoryForward], tx.clone());
+            assert_eq!(state.global.state_machine, StateMachine::FileDependentsView);
+            assert_eq!(source_path(&state), Some(String::from("a")));
+            assert_eq!(state.file_dependent_panel.selected(), Cursor::Link(0, 0));
+
+            // Nothing left to go forward to
+            dispatch_events(&mut state, &[AppEvent::HistoryForward], tx);
+            assert_eq!(source_path(&state), Some(String::from("a")));
+        }
+    }
+
+    mod command {
+        use super::*;
+        use crate::RecomplileDependencyReason;
+
+        fn type_command(state: &mut AppState, input: &str, tx: mpsc::Sender<AppEvent>) {
+            let mut events = vec![AppEvent::EnterCommand];
+            events.extend(input.chars().map(AppEvent::CommandInput));
+            dispatch_events(state, &events, tx);
+        }
+
+        fn state_with_files() -> AppState {
+            let mut state = AppState::new();
+            state.global.files_list = Some(
+                ["lib/a.ex", "lib/b.ex"]
+                    .iter()
+                    .map(|path| FileEntry::fixture(path, vec![]))
+                    .collect(),
+            );
+
+            state
+        }
+
+        #[test]
+        fn submit_command() {
+            let mut state = AppState::new();
+
+            let (tx, rx) = mpsc::channel::<AppEvent>();
+            type_command(&mut state, "sort path", tx.clone());
+            dispatch_events(&mut state, &[AppEvent::SubmitCommand], tx.clone());
+            assert_eq!(state.global.command_prompt, search_input::State::None);
+
+            let events = collect_events(rx);
+            assert!(matches!(
+                &events[..],
+                [AppEvent::RunCommand(Command::Sort(FileSort::Path))]
+            ));
+
+            dispatch_events(&mut state, &events, tx);
+            assert_eq!(state.global.file_sort, FileSort::Path);
+        }
+
+        #[test]
+        fn cycle_and_reverse_sort() {
+            let mut state = AppState::new();
+            let (tx, _rx) = mpsc::channel::<AppEvent>();
+
+            dispatch_events(
+                &mut state,
+                &[AppEvent::ReverseFileSort, AppEvent::CycleFileSort],
+                tx.clone(),
+            );
+            assert_eq!(state.global.file_sort, FileSort::Compile);
+            assert!(!state.global.file_sort_reversed);
+
+            dispatch_events(&mut state, &[AppEvent::ReverseFileSort], tx);
+            assert!(state.global.file_sort_reversed);
+        }
+
+        #[test]
+        fn invalid_command_shows_error() {
+            let mut state = AppState::new();
+
+            let (tx, rx) = mpsc::channel::<AppEvent>();
+            type_command(&mut state, "frobnicate", tx.clone());
+            dispatch_events(&mut state, &[AppEvent::SubmitCommand], tx);
+
+            assert!(matches!(state.global.message, Some(Message::Error(_))));
+            assert!(collect_events(rx).is_empty());
+        }
+
+        #[test]
+        fn complete_command() {
+            let mut state = state_with_files();
+
+            let (tx, _) = mpsc::channel::<AppEvent>();
+            type_command(&mut state, "op", tx.clone());
+            dispatch_events(&mut state, &[AppEvent::CompleteCommand], tx.clone());
+            assert_eq!(
+                state.global.command_prompt,
+                search_input::State::prompt("open ")
+            );
+
+            let events = [AppEvent::CommandInput('l'), AppEvent::CompleteCommand];
+            dispatch_events(&mut state, &events, tx);
+            assert_eq!(
+                state.global.command_prompt,
+                search_input::State::prompt("open lib/")
+            );
+        }
+
+        #[test]
+        fn open_file() {
+            let mut state = state_with_files();
+
+            let (tx, _rx) = mpsc::channel::<AppEvent>();
+            let event = AppEvent::RunCommand(Command::Open(String::from("lib/b.ex")));
+            dispatch_events(&mut state, &[event], tx.clone());
+            assert_eq!(state.global.state_machine, StateMachine::FileDependentsView);
+            assert_eq!(
+                state
+                    .global
+                    .selected_dependency_source
+                    .as_ref()
+                    .unwrap()
+                    .path,
+                "lib/b.ex"
+            );
+
+            let event = AppEvent::RunCommand(Command::Open(String::from("lib/c.ex")));
+            dispatch_events(&mut state, &[event], tx);
+            assert_eq!(
+                state.global.message,
+                Some(Message::Error(String::from("No file lib/c.ex")))
+            );
+        }
+
+        #[test]
+        fn why_between_two_files() {
+            let mut state = state_with_files();
+
+            let (tx, _rx) = mpsc::channel::<AppEvent>();
+            let why = Command::Why(String::from("lib/a.ex"), String::from("lib/c.ex"));
+            dispatch_events(&mut state, &[AppEvent::RunCommand(why)], tx.clone());
+            assert_eq!(
+                state.global.message,
+                Some(Message::Error(String::from("No file lib/c.ex")))
+            );
+            assert!(state.global.why_query.is_none());
+
+            let why = Command::Why(String::from("lib/a.ex"), String::from("lib/b.ex"));
+            dispatch_events(&mut state, &[AppEvent::RunCommand(why)], tx.clone());
+            assert_eq!(
+                state.global.why_query,
+                Some((String::from("lib/a.ex"), String::from("lib/b.ex")))
+            );
+
+            dispatch_events(&mut state, &[AppEvent::CloseWhyReport], tx);
+            assert!(state.global.why_query.is_none());
+        }
+
+        #[test]
+        fn diff_shows_the_changed_sources() {
+            let mut state = state_with_files();
+            state.global.marked_files = vec![String::from("lib/a.ex")];
+            state.global.show_impact = true;
+
+            let (tx, _rx) = mpsc::channel::<AppEvent>();
+            let changed = ["mix.exs", "lib/b.ex"].map(String::from).to_vec();
+            let diff_done = AppEvent::GetDiffDone(String::from("main"), Ok(changed));
+            dispatch_events(&mut state, &[diff_done], tx);
+
+            assert_eq!(
+                state.global.diff,
+                Some(Diff {
+                    base: String::from("main"),
+                    files: vec![String::from("lib/b.ex")],
+                })
+            );
+            assert_eq!(state.global.marked_files, vec![String::from("lib/a.ex")]);
+            assert!(state.global.show_changed_files);
+            assert!(!state.global.show_impact);
+        }
+
+        #[test]
+        fn mark_the_changed_files() {
+            let mut state = state_with_files();
+            state.global.marked_files = vec![String::from("lib/a.ex")];
+            state.global.diff = Some(Diff {
+                base: String::from("main"),
+                files: vec![String::from("lib/a.ex"), String::from("lib/b.ex")],
+            });
+
+            let (tx, _rx) = mpsc::channel::<AppEvent>();
+            dispatch_events(&mut state, &[AppEvent::MarkChangedFiles], tx);
+
+            assert_eq!(
+                state.global.marked_files,
+                vec![String::from("lib/a.ex"), String::from("lib/b.ex")]
+            );
+            assert_eq!(state.global.marks_revision, 1);
+        }
+
+        #[test]
+        fn changed_files_before_any_diff() {
+            let mut state = state_with_files();
+
+            let (tx, _rx) = mpsc::channel::<AppEvent>();
+            dispatch_events(&mut state, &[AppEvent::ToggleChangedFiles], tx);
+
+            assert!(!state.global.show_changed_files);
+            assert_eq!(
+                state.global.message,
+                Some(Message::Error(String::from(
+                    "No changed files, diff against a git ref first"
+                )))
+            );
+        }
+
+        #[test]
+        fn diff_while_files_are_loading() {
+            let mut state = AppState::new();
+
+            let (tx, rx) = mpsc::channel::<AppEvent>();
+            let diff = Command::Diff(String::from("main"));
+            dispatch_events(&mut state, &[AppEvent::RunCommand(diff)], tx);
+
+            assert_eq!(
+                state.global.message,
+                Some(Message::Error(String::from("Files are still loading")))
+            );
+            assert!(collect_events(rx).is_empty());
+        }
+
+        #[test]
+        fn diff_with_invalid_ref() {
+            let mut state = state_with_files();
+            state.global.marked_files = vec![String::from("lib/a.ex")];
+
+            let (tx, rx) = mpsc::channel::<AppEvent>();
+            let diff = Command::Diff(String::from("--cached"));
+            dispatch_events(&mut state, &[AppEvent::RunCommand(diff)], tx.clone());
+
+            // git runs on another thread
+            let diff_done = rx.recv().unwrap();
+            dispatch_events(&mut state, &[diff_done], tx);
+
+            assert_eq!(
+                state.global.message,
+                Some(Message::Error(String::from("Invalid git ref \"--cached\"")))
+            );
+            assert!(state.global.diff.is_none());
+            assert!(!state.global.show_changed_files);
+        }
+
+        #[test]
+        fn filter_resets_dependents_selection() {
+            let mut state = state_with_files();
+            state.file_dependent_panel = file_dependent_panel::State::restore(
+                Cursor::Link(1, 0),
+                HashSet::from([String::from("x")]),
+                None,
+            );
+
+            let (tx, rx) = mpsc::channel::<AppEvent>();
+            let event =
+                AppEvent::RunCommand(Command::Filter(Some(RecomplileDependencyReason::Exports)));
+            dispatch_events(&mut state, &[event], tx);
+
+            assert_eq!(
+                state.global.reason_filter,
+                ReasonFilter::only(&RecomplileDependencyReason::Exports)
+            );
+            assert_eq!(state.file_dependent_panel.selected(), Cursor::File(0));
+            assert!(matches!(
+                &collect_events(rx)[..],
+                [AppEvent::ResetDependencyCauses(None, None)]
+            ));
+        }
+
+        #[test]
+        fn toggle_reasons() {
+            let mut state = state_with_files();
+
+            let (tx, rx) = mpsc::channel::<AppEvent>();
+            let event = AppEvent::ToggleReason(RecomplileDependencyReason::Compile);
+            dispatch_events(&mut state, &[event], tx);
+            assert!(!state
+                .global
+                .reason_filter
+                .is_shown(&RecomplileDependencyReason::Compile));
+            assert_eq!(collect_events(rx).len(), 1);
+
+            let (tx, _rx) = mpsc::channel::<AppEvent>();
+            let event = AppEvent::ToggleReason(RecomplileDependencyReason::Compile);
+            dispatch_events(&mut state, &[event], tx);
+            assert_eq!(state.global.reason_filter, ReasonFilter::new());
+        }
+
+        #[test]
+        fn quit() {
+            let mut state = AppState::new();
+
+            let (tx, rx) = mpsc::channel::<AppEvent>();
+            dispatch_events(&mut state, &[AppEvent::RunCommand(Command::Quit)], tx);
+            assert!(matches!(&collect_events(rx)[..], [AppEvent::Quit]));
+        }
+    }
+}
+
+#[cfg(test)]
+mod produce_event_tests {
+    use super::*;
+    use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+    fn key_event(code: KeyCode) -> Event {
+        Event::Key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    #[test]
+    fn toggle_help() {
+        let mut state = AppState::new();
+
+        let event = state
+            .global
+            .produce_event(&key_event(KeyCode::Char('?')), &NoopWidget {});
+        assert!(matches!(event, Some(AppEvent::ToggleHelp)));
+    }
+
+    #[test]
+    fn help_swallows_other_keys() {
+        let mut state = AppState::new();
+        state.global.show_help = true;
+
+        let event = state
+            .global
+            .produce_event(&key_event(KeyCode::Char('q')), &NoopWidget {});
+        assert!(event.is_none());
+
+        let event = state
+            .global
+            .produce_event(&key_event(KeyCode::Esc), &NoopWidget {});
+        assert!(matches!(event, Some(AppEvent::ToggleHelp)));
+    }
+
+    #[test]
+    fn dashboard_swallows_other_keys() {
+        let mut state = AppState::new();
+
+        let event = state
+            .global
+            .produce_event(&key_event(KeyCode::Char('d')), &NoopWidget {});
+        assert!(matches!(event, Some(AppEvent::ToggleDashboard)));
+
+        state.global.show_dashboard = true;
+
+        let event = state
+            .global
+            .produce_event(&key_event(KeyCode::Char('j')), &NoopWidget {});
+        assert!(event.is_none());
+
+        let event = state
+            .global
+            .produce_event(&key_event(KeyCode::Esc), &NoopWidget {});
+        assert!(matches!(event, Some(AppEvent::ToggleDashboard)));
+    }
+
+    #[test]
+    fn impact_report_takes_over_moves() {
+        let mut state = AppState::new();
+
+        let event = state
+            .global
+            .produce_event(&key_event(KeyCode::Char('i')), &NoopWidget {});
+        assert!(matches!(event, Some(AppEvent::ToggleImpactReport)));
+
+        state.global.show_impact = true;
+
+        let event = state
+            .global
+            .produce_event(&key_event(KeyCode::Char('j')), &NoopWidget {});
+        assert!(matches!(event, Some(AppEvent::ScrollImpactReportDown)));
+
+        let event = state
+            .global
+            .produce_event(&key_event(KeyCode::Char('/')), &NoopWidget {});
+        assert!(event.is_none());
+
+        let event = state
+            .global
+            .produce_event(&key_event(KeyCode::Esc), &NoopWidget {});
+        assert!(matches!(event, Some(AppEvent::ToggleImpactReport)));
+    }
+
+    #[test]
+    fn changed_files_panel_takes_over_keys() {
+        let mut state = AppState::new();
+
+        let event = state
+            .global
+            .produce_event(&key_event(KeyCode::Char('c')), &NoopWidget {});
+        assert!(matches!(event, Some(AppEvent::ToggleChangedFiles)));
+
+        state.global.show_changed_files = true;
+
+        let event = state
+            .global
+            .produce_event(&key_event(KeyCode::Char('j')), &NoopWidget {});
+        assert!(matches!(event, Some(AppEvent::ScrollChangedFilesDown)));
+
+        let event = state
+            .global
+            .produce_event(&key_event(KeyCode::Char(' ')), &NoopWidget {});
+        assert!(matches!(event, Some(AppEvent::MarkChangedFiles)));
+
+        let event = state
+            .global
+            .produce_event(&key_event(KeyCode::Esc), &NoopWidget {});
+        assert!(matches!(event, Some(AppEvent::ToggleChangedFiles)));
+    }
+
+    #[test]
+    fn why_report_takes_over_moves() {
+        let mut state = AppState::new();
+        state.global.why_query = Some((String::from("lib/a.ex"), String::from("lib/b.ex")));
+
+        let event = state
+            .global
+            .produce_event(&key_event(KeyCode::Char('j')), &NoopWidget {});
+        assert!(matches!(event, Some(AppEvent::ScrollWhyReportDown)));
+
+        let event = state
+            .global
+            .produce_event(&key_event(KeyCode::Esc), &NoopWidget {});
+        assert!(matches!(event, Some(AppEvent::CloseWhyReport)));
+    }
+
+    #[test]
+    fn prompt_takes_over_bound_characters() {
+        let mut state = AppState::new();
+        state.global.file_panel_search = search_input::State::prompt("");
+
+        let event = state
+            .global
+            .produce_event(&key_event(KeyCode::Char('q')), &NoopWidget {});
+        assert!(matches!(event, Some(AppEvent::SearchInput('q'))));
+        assert_eq!(state.global.active_scopes(), vec![Scope::Prompt]);
+    }
+
+    #[test]
+    fn command_prompt() {
+        let mut state = AppState::new();
+
+        let event = state
+            .global
+            .produce_event(&key_event(KeyCode::Char(':')), &NoopWidget {});
+        assert!(matches!(event, Some(AppEvent::EnterCommand)));
+
+        state.global.command_prompt = search_input::State::prompt("op");
+        state.global.message = Some(Message::Error(String::from("error")));
+
+        let event = state
+            .global
+            .produce_event(&key_event(KeyCode::Tab), &NoopWidget {});
+        assert!(matches!(event, Some(AppEvent::CompleteCommand)));
+        assert_eq!(state.global.message, None);
+
+        let event = state
+            .global
+            .produce_event(&key_event(KeyCode::Esc), &NoopWidget {});
+        assert!(matches!(event, Some(AppEvent::CancelCommand)));
+    }
+
+    #[test]
+    fn active_scopes_follow_state_machine() {
+        let mut state = AppState::new();
+        assert_eq!(
+            state.global.active_scopes(),
+            vec![Scope::Global, Scope::FilePanel]
+        );
+
+        state.global.state_machine = StateMachine::FileDependentsView;
+        assert_eq!(
+            state.global.active_scopes(),
+            vec![
+                Scope::Global,
+                Scope::FileDependentPanel,
+                Scope::DependencyCausePanel
+            ]
+        );
+    }
 }
@@ -0,0 +1,295 @@
+use anyhow::{anyhow, Result};
+use std::path::PathBuf;
+
+use crate::file_sort::FileSort;
+use crate::query::Query;
+use crate::{FileEntry, FilePath, RecomplileDependencyReason};
+
+/// Commands run from the `:` prompt. They expose the features which don't have a key of their
+/// own
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    // Show the dependents of a file
+    Open(FilePath),
+    Sort(FileSort),
+    // Only show the dependents recompiled for a reason, or all of them
+    Filter(Option<RecomplileDependencyReason>),
+    // Explain why the first file recompiles when the second one changes
+    Why(FilePath, FilePath),
+    // Mark the files changed in the working tree since a git ref, and show what they recompile
+    Diff(String),
+    // Write the dependency graph to a Graphviz file
+    ExportDot(PathBuf),
+    // Expand or collapse the chains of all dependents
+    ExpandAll,
+    CollapseAll,
+    // Collect the files from the server again
+    Refresh,
+    Quit,
+}
+
+const COMMANDS: &[&str] = &[
+    "open", "why", "diff", "sort", "filter", "export", "expand", "collapse", "refresh", "quit",
+];
+const EXPORT_FORMATS: &[&str] = &["dot"];
+const REASONS: &[(&str, RecomplileDependencyReason)] = &[
+    ("compile", RecomplileDependencyReason::Compile),
+    ("exports", RecomplileDependencyReason::Exports),
+    (
+        "exports_then_compile",
+        RecomplileDependencyReason::ExportsThenCompile,
+    ),
+    (
+        "compile_then_runtime",
+        RecomplileDependencyReason::CompileThenRuntime,
+    ),
+];
+
+impl Command {
+    pub fn parse(input: &str) -> Result<Self> {
+        let mut words = input.split_whitespace();
+        let name = words.next().ok_or(anyhow!("No command given"))?;
+        let args: Vec<&str> = words.collect();
+
+        let command = match (name, &args[..]) {
+            ("open", [path]) => Command::Open(path.to_string()),
+            ("open", _) => return Err(anyhow!("Usage: open <path>")),
+
+            ("why", [dependent, changed]) => {
+                Command::Why(dependent.to_string(), changed.to_string())
+            }
+            ("why", _) => return Err(anyhow!("Usage: why <path> <changed path>")),
+
+            // Uncommitted changes by default
+            ("diff", []) => Command::Diff(String::from("HEAD")),
+            ("diff", [base]) => Command::Diff(base.to_string()),
+            ("diff", _) => return Err(anyhow!("Usage: diff [<git ref>]")),
+
+            ("sort", [name]) => Command::Sort(
+                FileSort::from_name(name).ok_or(anyhow!("Unknown sort mode {:?}", name))?,
+            ),
+            ("sort", _) => {
+                let names: Vec<&str> = FileSort::ALL.iter().map(FileSort::name).collect();
+                return Err(anyhow!("Usage: sort {}", names.join("|")));
+            }
+
+            ("filter", []) => Command::Filter(None),
+            ("filter", [filter]) => {
+                let reason = filter
+                    .strip_prefix("reason=")
+                    .and_then(|name| REASONS.iter().find(|(reason, _)| *reason == name))
+                    .ok_or(anyhow!("Unknown filter {:?}", filter))?;
+
+                Command::Filter(Some(reason.1.clone()))
+            }
+            ("filter", _) => return Err(anyhow!("Usage: filter [reason=<reason>]")),
+
+            ("export", ["dot", path]) => Command::ExportDot(PathBuf::from(path)),
+            ("export", _) => return Err(anyhow!("Usage: export dot <file>")),
+
+            ("expand", []) => Command::ExpandAll,
+            ("collapse", []) => Command::CollapseAll,
+            ("refresh", []) => Command::Refresh,
+            ("quit", []) => Command::Quit,
+            ("expand" | "collapse" | "refresh" | "quit", _) => {
+                return Err(anyhow!("{} takes no arguments", name))
+            }
+
+            _ => return Err(anyhow!("Unknown command {:?}", name)),
+        };
+
+        Ok(command)
+    }
+}
+
+/// Complete the last word of a command line, as far as all candidates agree. Returns the
+/// completed input, or None when nothing matches
+pub fn complete(input: &str, files: &[FileEntry]) -> Option<String> {
+    let words: Vec<&str> = input.split_whitespace().collect();
+    let completing_new_word = input.is_empty() || input.ends_with(char::is_whitespace);
+
+    let (previous, word) = match (completing_new_word, words.split_last()) {
+        (true, _) => (&words[..], ""),
+        (false, Some((word, previous))) => (previous, *word),
+        (false, None) => (&words[..], ""),
+    };
+
+    let reasons: Vec<String> = REASONS
+        .iter()
+        .map(|(name, _)| format!("reason={}", name))
+        .collect();
+
+    let paths = || files.iter().map(|file| file.path.as_str()).collect();
+    let candidates: Vec<&str> = match previous {
+        [] => COMMANDS.to_vec(),
+        ["open"] | ["why"] | ["why", _] => paths(),
+        ["sort"] => FileSort::ALL.iter().map(FileSort::name).collect(),
+        ["filter"] => reasons.iter().map(String::as_str).collect(),
+        ["export"] => EXPORT_FORMATS.to_vec(),
+        _ => vec![],
+    };
+
+    let mut matching: Vec<&str> = candidates
+        .iter()
+        .copied()
+        .filter(|candidate| candidate.starts_with(word))
+        .collect();
+
+    // Paths are picked fuzzily when none starts with the word, the best match wins
+    let picks_path = matches!(previous, ["open"] | ["why"] | ["why", _]);
+    if matching.is_empty() && picks_path && !word.is_empty() {
+        if let Ok(query) = Query::parse(word) {
+            matching.extend(query.filter(&candidates).first());
+        }
+    }
+
+    let completed = match matching[..] {
+        [] => return None,
+        // A complete word is followed by a space, ready for the next argument
+        [candidate] => format!("{} ", candidate),
+        _ => common_prefix(&matching),
+    };
+
+    let mut line = previous.join(" ");
+    if !line.is_empty() {
+        line.push(' ');
+    }
+    line.push_str(&completed);
+
+    Some(line)
+}
+
+fn common_prefix(words: &[&str]) -> String {
+    let mut prefix = words[0].to_string();
+
+    for word in &words[1..] {
+        let length = prefix
+            .chars()
+            .zip(word.chars())
+            .take_while(|(a, b)| a == b)
+            .map(|(a, _)| a.len_utf8())
+            .sum();
+
+        prefix.truncate(length);
+    }
+
+    prefix
+}
+
+#[cfg(test)]
+mod command_tests {
+    use super::*;
+
+    fn files(paths: &[&str]) -> Vec<FileEntry> {
+        paths
+            .iter()
+            .map(|path| FileEntry {
+                path: path.to_string(),
+                recompile_dependencies: vec![],
+            })
+            .collect()
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(
+            Command::parse("open lib/a.ex").unwrap(),
+            Command::Open(String::from("lib/a.ex"))
+        );
+        assert_eq!(
+            Command::parse(" sort  path ").unwrap(),
+            Command::Sort(FileSort::Path)
+        );
+        assert_eq!(
+            Command::parse("filter reason=exports_then_compile").unwrap(),
+            Command::Filter(Some(RecomplileDependencyReason::ExportsThenCompile))
+        );
+        assert_eq!(
+            Command::parse("sort chain").unwrap(),
+            Command::Sort(FileSort::ChainLength)
+        );
+        assert_eq!(Command::parse("filter").unwrap(), Command::Filter(None));
+        assert_eq!(
+            Command::parse("export dot graph.dot").unwrap(),
+            Command::ExportDot(PathBuf::from("graph.dot"))
+        );
+        assert_eq!(
+            Command::parse("why lib/a.ex lib/b.ex").unwrap(),
+            Command::Why(String::from("lib/a.ex"), String::from("lib/b.ex"))
+        );
+        assert_eq!(
+            Command::parse("diff main").unwrap(),
+            Command::Diff(String::from("main"))
+        );
+        assert_eq!(
+            Command::parse("diff").unwrap(),
+            Command::Diff(String::from("HEAD"))
+        );
+        assert_eq!(Command::parse("expand").unwrap(), Command::ExpandAll);
+        assert_eq!(Command::parse("collapse").unwrap(), Command::CollapseAll);
+        assert_eq!(Command::parse("refresh").unwrap(), Command::Refresh);
+        assert_eq!(Command::parse("quit").unwrap(), Command::Quit);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(Command::parse("").is_err());
+        assert!(Command::parse("frobnicate").is_err());
+        assert!(Command::parse("open").is_err());
+        assert!(Command::parse("why lib/a.ex").is_err());
+        assert!(Command::parse("diff main HEAD").is_err());
+        assert!(Command::parse("sort size").is_err());
+        assert!(Command::parse("filter reason=runtime").is_err());
+        assert!(Command::parse("export png a.png").is_err());
+        assert!(Command::parse("quit now").is_err());
+        assert!(Command::parse("expand all").is_err());
+    }
+
+    #[test]
+    fn complete_command() {
+        assert_eq!(complete("", &[]), Some(String::new()));
+        assert_eq!(complete("s", &[]), Some(String::from("sort ")));
+        assert_eq!(complete("x", &[]), None);
+        assert_eq!(complete("sort p", &[]), Some(String::from("sort path ")));
+        assert_eq!(complete("sort c", &[]), Some(String::from("sort c")));
+        assert_eq!(
+            complete("filter reason=e", &[]),
+            Some(String::from("filter reason=exports"))
+        );
+        assert_eq!(
+            complete("filter reason=exports_", &[]),
+            Some(String::from("filter reason=exports_then_compile "))
+        );
+    }
+
+    #[test]
+    fn complete_path() {
+        let files = files(&["lib/web/router.ex", "lib/web/endpoint.ex", "lib/app.ex"]);
+
+        assert_eq!(
+            complete("open lib/w", &files),
+            Some(String::from("open lib/web/"))
+        );
+        assert_eq!(
+            complete("open lib/web/r", &files),
+            Some(String::from("open lib/web/router.ex "))
+        );
+        assert_eq!(complete("open test/", &files), None);
+    }
+
+    #[test]
+    fn complete_why_paths() {
+        let files = files(&["lib/web/router.ex", "lib/web/endpoint.ex", "lib/app.ex"]);
+
+        assert_eq!(
+            complete("why lib/a", &files),
+            Some(String::from("why lib/app.ex "))
+        );
+        // No path starts with the word, the best fuzzy match is picked
+        assert_eq!(
+            complete("why lib/app.ex rtr", &files),
+            Some(String::from("why lib/app.ex lib/web/router.ex "))
+        );
+        assert_eq!(complete("why lib/app.ex lib/web/router.ex x", &files), None);
+    }
+}
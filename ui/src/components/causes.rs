@@ -0,0 +1,84 @@
+use ratatui::style::{Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+
+use crate::theme;
+use crate::{highlight, CodeSnippet, DependencyCause, DependencyLink, DependencyType, FilePath};
+
+/// The causes of the links of a chain, as far as the server answered. Each request has its own
+/// id, a response to an earlier request is ignored
+#[derive(Debug)]
+pub enum Causes {
+    NotRequested,
+    Loading(usize),
+    Loaded(Vec<DependencyCause>),
+    Failed(String),
+}
+
+/// Why no cause was found for the link. Only compile time dependencies are caused by code the
+/// server can point at
+pub fn missing_cause_reason(link: &DependencyLink) -> &'static str {
+    match link.dependency_type {
+        DependencyType::Runtime => "Runtime link – no compile-time cause",
+        DependencyType::Compile | DependencyType::Exports => {
+            "The server couldn't locate the code causing this link"
+        }
+    }
+}
+
+/// The lines of a snippet of the source file, with line numbers and the cause highlighted
+pub fn code_snippet_text(source_file: FilePath, snippet: &CodeSnippet) -> Vec<Line> {
+    let header_line = Line::from(vec![
+        Span::from("-- File: "),
+        Span::from(source_file).add_modifier(Modifier::BOLD),
+    ]);
+
+    let max_line_number_len = snippet.lines_span.1.to_string().len();
+    // Snippet content ends with a line break, which shouldn't be rendered as an extra line
+    let content = snippet
+        .content
+        .strip_suffix('\n')
+        .unwrap_or(&snippet.content);
+    let highlighted_lines = highlight::highlight_elixir(content);
+
+    let content_lines = highlighted_lines
+        .into_iter()
+        .enumerate()
+        .map(|(index, tokens)| {
+            let line_number = index + snippet.lines_span.0;
+            let is_highlight =
+                line_number >= snippet.highlight.0 && line_number <= snippet.highlight.1;
+
+            let line_number_span = if is_highlight {
+                Span::styled(
+                    format!("{: >width$} =>", line_number, width = max_line_number_len),
+                    theme::current().code_highlight,
+                )
+            } else {
+                Span::styled(
+                    format!("{: >width$}   ", line_number, width = max_line_number_len),
+                    theme::current().line_number,
+                )
+            };
+
+            let mut spans = vec![line_number_span, Span::from(" │ ")];
+            spans.extend(
+                tokens
+                    .into_iter()
+                    .map(|(kind, text)| Span::styled(text, kind.style())),
+            );
+
+            let mut line = Line::from(spans);
+            if is_highlight {
+                line.patch_style(Style::default().add_modifier(Modifier::BOLD));
+            }
+
+            line
+        });
+
+    let mut result = vec![header_line, Line::from("")];
+    result.extend(content_lines);
+    // Snippets separator
+    result.push(Line::from(""));
+
+    result
+}
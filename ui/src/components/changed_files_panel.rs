@@ -0,0 +1,225 @@
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, BorderType, Borders, Clear, Paragraph, StatefulWidget, Widget};
+use std::rc::Rc;
+use std::sync::mpsc;
+
+use crate::adapter::ServerAdapter;
+use crate::app_event::AppEvent;
+use crate::components::impact_report;
+use crate::impact::Impact;
+use crate::keymap::{self, Action, Scope};
+use crate::theme;
+use crate::utils;
+use crate::HandleEvent;
+
+/// The files changed against a git ref by the last diff, and every file they recompile. Drawn
+/// over the panels. The changed files are kept apart from the marks until they are marked here
+#[derive(Clone)]
+pub struct ChangedFilesPanel {
+    impact: Rc<Impact>,
+    base: String,
+}
+
+impl ChangedFilesPanel {
+    pub fn new(impact: Rc<Impact>, base: String) -> Self {
+        Self { impact, base }
+    }
+}
+
+pub struct State {
+    scroll_offset: usize,
+    // The number of lines in the last render, the panel can't be scrolled past them
+    lines_count: usize,
+}
+
+impl State {
+    pub fn new() -> Self {
+        Self {
+            scroll_offset: 0,
+            lines_count: 0,
+        }
+    }
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HandleEvent for State {
+    type Widget = ChangedFilesPanel;
+
+    fn handle_event(
+        &mut self,
+        event: &AppEvent,
+        _widget: &Self::Widget,
+        _adapter: &mut impl ServerAdapter,
+        _dispatcher: mpsc::Sender<AppEvent>,
+    ) {
+        match event {
+            AppEvent::ScrollChangedFilesDown if self.scroll_offset + 1 < self.lines_count => {
+                self.scroll_offset += 1;
+            }
+
+            AppEvent::ScrollChangedFilesUp => {
+                self.scroll_offset = self.scroll_offset.saturating_sub(1);
+            }
+
+            AppEvent::ToggleChangedFiles => self.scroll_offset = 0,
+            _ => (),
+        }
+    }
+}
+
+impl StatefulWidget for ChangedFilesPanel {
+    type State = State;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut State) {
+        Clear.render(area, buf);
+
+        Block::default()
+            .borders(Borders::ALL)
+            .title(format!("Changed files against {}", self.base))
+            .border_type(BorderType::Rounded)
+            .border_style(theme::current().border)
+            .render(area, buf);
+
+        let lines = self.lines();
+        state.lines_count = lines.len();
+        state.scroll_offset = state.scroll_offset.min(lines.len().saturating_sub(1));
+
+        Paragraph::new(lines)
+            .style(theme::current().text)
+            .scroll((state.scroll_offset as u16, 0))
+            .render(utils::padding(&area, 2, 1), buf);
+    }
+}
+
+impl ChangedFilesPanel {
+    fn lines(&self) -> Vec<Line<'static>> {
+        let impact = &self.impact;
+
+        if impact.changed.is_empty() {
+            return vec![Line::styled(
+                format!(
+                    "No Elixir file of the project changed against {}",
+                    self.base
+                ),
+                theme::current().hint,
+            )];
+        }
+
+        let key = keymap::current()
+            .keys(Scope::FilePanel, Action::Mark)
+            .first()
+            .map(|key| key.to_string())
+            .unwrap_or_default();
+
+        let mut lines = vec![Line::styled(
+            format!("Changed against {}:", self.base),
+            Style::default().add_modifier(Modifier::BOLD),
+        )];
+        lines.extend(
+            impact
+                .changed
+                .iter()
+                .map(|path| Line::styled(format!("  {}", path), theme::current().title)),
+        );
+        lines.push(Line::from(""));
+        lines.push(Line::styled(
+            format!("Press {} to mark them for the what if report", key),
+            theme::current().hint,
+        ));
+        lines.push(Line::from(""));
+        lines.extend(impact_report::recompiled_lines(impact));
+
+        lines
+    }
+}
+
+#[cfg(test)]
+mod changed_files_panel_tests {
+    use super::*;
+    use crate::adapter::NoopAdapter;
+    use crate::{FileEntry, RecomplileDependency};
+
+    fn panel(changed: &[&str]) -> ChangedFilesPanel {
+        let files = vec![
+            FileEntry::fixture("a", vec![RecomplileDependency::fixture("c")]),
+            FileEntry::fixture("b", vec![]),
+        ];
+
+        let changed: Vec<String> = changed.iter().map(|path| path.to_string()).collect();
+        ChangedFilesPanel::new(
+            Rc::new(Impact::compute(&changed, &files)),
+            String::from("main"),
+        )
+    }
+
+    fn text(lines: &[Line]) -> Vec<String> {
+        lines
+            .iter()
+            .map(|line| {
+                line.spans
+                    .iter()
+                    .map(|span| span.content.as_ref())
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn changed_files_then_what_they_recompile() {
+        let lines = text(&panel(&["a", "b"]).lines());
+
+        assert_eq!(
+            lines,
+            [
+                "Changed against main:",
+                "  a",
+                "  b",
+                "",
+                "Press space to mark them for the what if report",
+                "",
+                "Files recompiled: 1 – 1 definite, 0 indefinite",
+                "",
+                "c",
+                "  ← a (compile)",
+            ]
+        );
+    }
+
+    #[test]
+    fn nothing_changed() {
+        let lines = text(&panel(&[]).lines());
+        assert_eq!(
+            lines,
+            ["No Elixir file of the project changed against main"]
+        );
+    }
+
+    #[test]
+    fn scroll_is_bounded() {
+        let panel = panel(&["a", "b"]);
+        let mut state = State::new();
+
+        let area = Rect::new(0, 0, 60, 20);
+        let mut buffer = Buffer::empty(area);
+        panel.clone().render(area, &mut buffer, &mut state);
+
+        let (tx, _rx) = mpsc::channel::<AppEvent>();
+        for _ in 0..20 {
+            let event = AppEvent::ScrollChangedFilesDown;
+            state.handle_event(&event, &panel, &mut NoopAdapter::new(), tx.clone());
+        }
+        assert_eq!(state.scroll_offset, 9);
+
+        let event = AppEvent::ToggleChangedFiles;
+        state.handle_event(&event, &panel, &mut NoopAdapter::new(), tx);
+        assert_eq!(state.scroll_offset, 0);
+    }
+}
@@ -0,0 +1,148 @@
+use ratatui::buffer::Buffer;
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{BarChart, Block, BorderType, Borders, Clear, Paragraph, Widget};
+use std::rc::Rc;
+
+use crate::stats::{Stats, TOP_FILES_COUNT};
+use crate::theme;
+use crate::utils;
+
+/// Statistics of the whole project, drawn over the panels
+#[derive(Clone)]
+pub struct Dashboard {
+    // None while the files are being collected
+    stats: Option<Rc<Stats>>,
+}
+
+impl Dashboard {
+    pub fn new(stats: Option<Rc<Stats>>) -> Self {
+        Self { stats }
+    }
+}
+
+impl Widget for Dashboard {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        Clear.render(area, buf);
+        section("Statistics").render(area, buf);
+
+        let rect = utils::padding(&area, 1, 1);
+        let stats = match self.stats {
+            Some(stats) => stats,
+            None => return Paragraph::new("Collecting files...").render(rect, buf),
+        };
+
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![
+                Constraint::Length(2),
+                Constraint::Length(12),
+                Constraint::Min(0),
+            ])
+            .split(rect);
+
+        let charts = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(vec![Constraint::Percentage(60), Constraint::Percentage(40)])
+            .split(layout[1]);
+
+        render_summary(&stats, layout[0], buf);
+        render_histogram(&stats, charts[0], buf);
+        render_reasons(&stats, charts[1], buf);
+        render_top_files(&stats, layout[2], buf);
+    }
+}
+
+fn section(title: &str) -> Block<'_> {
+    Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .border_type(BorderType::Rounded)
+        .border_style(theme::current().border)
+}
+
+fn render_summary(stats: &Stats, area: Rect, buf: &mut Buffer) {
+    let entry = |label: &str, count: usize| {
+        Line::from(vec![
+            Span::from(format!("{: <24}", label)),
+            Span::styled(count.to_string(), theme::current().count),
+        ])
+    };
+
+    Paragraph::new(vec![
+        entry("Files", stats.total_files),
+        entry("Files with dependents", stats.files_with_dependents),
+    ])
+    .style(theme::current().text)
+    .render(utils::padding(&area, 1, 0), buf);
+}
+
+fn render_histogram(stats: &Stats, area: Rect, buf: &mut Buffer) {
+    let bars_count = stats.histogram.len() as u16;
+    // Spread the bars over the width, keeping room for the widest label
+    let bar_width = (area.width.saturating_sub(2 + bars_count) / bars_count.max(1)).max(6);
+
+    BarChart::default()
+        .block(section("Files by number of dependents"))
+        .data(stats.histogram.as_slice())
+        .bar_width(bar_width)
+        .bar_gap(1)
+        .bar_style(theme::current().count)
+        .value_style(theme::current().selection)
+        .label_style(theme::current().text)
+        .render(area, buf);
+}
+
+fn render_reasons(stats: &Stats, area: Rect, buf: &mut Buffer) {
+    let total: usize = stats.reasons.iter().map(|(_, count)| count).sum();
+
+    let lines: Vec<Line> = stats
+        .reasons
+        .iter()
+        .map(|(reason, count)| {
+            let percentage = (*count * 100).checked_div(total).unwrap_or(0);
+
+            Line::from(vec![
+                Span::from(format!("{: <22}", reason.to_string())),
+                Span::styled(format!("{: >7}", count), theme::current().count),
+                Span::styled(format!(" {: >3}%", percentage), theme::current().hint),
+            ])
+        })
+        .collect();
+
+    section("Recompile dependencies by reason").render(area, buf);
+    Paragraph::new(lines)
+        .style(theme::current().text)
+        .render(utils::padding(&area, 2, 1), buf);
+}
+
+fn render_top_files(stats: &Stats, area: Rect, buf: &mut Buffer) {
+    let rect = utils::padding(&area, 2, 1);
+    let count_width = 7;
+    let max_width = (rect.width as usize).saturating_sub(count_width + 5);
+
+    let lines: Vec<Line> = stats
+        .top_files
+        .iter()
+        .enumerate()
+        .map(|(index, (path, count))| {
+            Line::from(vec![
+                Span::styled(format!("{: >2}. ", index + 1), theme::current().hint),
+                Span::from(format!(
+                    "{: <width$}",
+                    utils::compact_file_path(path, max_width),
+                    width = max_width
+                )),
+                Span::styled(
+                    format!("{: >width$}", count, width = count_width),
+                    theme::current().count,
+                ),
+            ])
+        })
+        .collect();
+
+    section(&format!("Top {} files by blast radius", TOP_FILES_COUNT)).render(area, buf);
+    Paragraph::new(lines)
+        .style(theme::current().text)
+        .render(rect, buf);
+}
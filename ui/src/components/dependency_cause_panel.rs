@@ -1,13 +1,21 @@
+use crossterm::event::MouseEventKind;
 use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
-use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::style::{Modifier, Style};
 use ratatui::text::Line;
 use ratatui::text::Span;
 use ratatui::widgets::{Block, BorderType, Borders, Paragraph, StatefulWidget, Widget};
 use std::sync::mpsc;
 
 use crate::adapter::ServerAdapter;
-use crate::{utils, AppEvent, CodeSnippet, DependencyCause, FilePath, HandleEvent};
+use crate::components::causes::{code_snippet_text, missing_cause_reason, Causes};
+use crate::components::loading_icon::LoadingIcon;
+use crate::keymap::{self, Action, Scope};
+use crate::theme;
+use crate::{
+    utils, AppEvent, DependencyCause, DependencyLink, FilePath, HandleEvent, ProduceEvent,
+    RecomplileDependency,
+};
 
 #[derive(Clone)]
 pub struct DependencyCausePanel {
@@ -21,17 +29,72 @@ impl DependencyCausePanel {
 }
 
 pub struct State {
-    dependency_causes: Vec<DependencyCause>,
-    viewing_recompile_dependency_file: Option<FilePath>,
+    causes: Causes,
+    // The id of the last request, kept when the panel is reset so that ids are never reused
+    request_id: usize,
+    viewing_link: Option<DependencyLink>,
+    scroll_offset: usize,
+    // The area of the panel and the number of snippet lines in the last render, used to handle
+    // the scroll wheel
+    area: Rect,
+    lines_count: usize,
 }
 
 impl State {
     pub fn new() -> Self {
         Self {
-            dependency_causes: vec![],
-            viewing_recompile_dependency_file: None,
+            causes: Causes::NotRequested,
+            request_id: 0,
+            viewing_link: None,
+            scroll_offset: 0,
+            area: Rect::default(),
+            lines_count: 0,
         }
     }
+
+    // Start over, without forgetting the request ids already used
+    fn reset(&mut self) {
+        *self = Self {
+            request_id: self.request_id,
+            ..Self::new()
+        };
+    }
+
+    fn viewing_cause(&self) -> Option<&DependencyCause> {
+        let viewing_link = self.viewing_link.as_ref()?;
+
+        match self.causes {
+            Causes::Loaded(ref causes) => {
+                causes.iter().find(|cause| cause.sink == viewing_link.sink)
+            }
+            _ => None,
+        }
+    }
+
+    fn fetch_dependency_causes(
+        &mut self,
+        source: &FilePath,
+        recompile_dependency: &RecomplileDependency,
+        adapter: &mut impl ServerAdapter,
+        dispatcher: mpsc::Sender<AppEvent>,
+    ) {
+        self.request_id += 1;
+        self.causes = Causes::Loading(self.request_id);
+
+        let request_id = self.request_id;
+        // The source and sink is reverse in this case
+        adapter.get_dependency_causes(
+            &recompile_dependency.path,
+            source,
+            &recompile_dependency.reason,
+            recompile_dependency.chain_index(),
+            Box::new(move |causes| {
+                dispatcher
+                    .send(AppEvent::GetDependencyCausesDone(request_id, causes))
+                    .unwrap();
+            }),
+        );
+    }
 }
 
 impl HandleEvent for State {
@@ -47,45 +110,103 @@ impl HandleEvent for State {
         match event {
             AppEvent::SelectDependentFile(recompile_dependency) => {
                 match widget.source_file {
-                    Some(ref source) => {
-                        // The source and sink is reverse in this case
-                        adapter.get_dependency_causes(
-                            &recompile_dependency.path,
-                            source,
-                            &recompile_dependency.reason,
-                            Box::new(move |causes| {
-                                dispatcher
-                                    .send(AppEvent::GetDependencyCausesDone(causes))
-                                    .unwrap();
-                            }),
-                        );
-                    }
+                    Some(ref source) => self.fetch_dependency_causes(
+                        source,
+                        recompile_dependency,
+                        adapter,
+                        dispatcher,
+                    ),
 
                     None => unreachable!(),
                 };
             }
 
-            AppEvent::GetDependencyCausesDone(causes) => {
-                self.dependency_causes = causes.clone();
+            AppEvent::ResetDependencyCauses(dependent, viewing_link) => {
+                self.reset();
+
+                if let Some((source, recompile_dependency)) = dependent {
+                    self.fetch_dependency_causes(source, recompile_dependency, adapter, dispatcher);
+                }
+
+                self.viewing_link = viewing_link.clone();
+            }
+
+            AppEvent::GetDependencyCausesDone(request_id, causes) => {
+                if matches!(self.causes, Causes::Loading(id) if id == *request_id) {
+                    self.causes = match causes {
+                        Ok(causes) => Causes::Loaded(causes.clone()),
+                        Err(error) => Causes::Failed(format!("{:#}", error)),
+                    };
+                }
             }
 
             AppEvent::ViewDependentFile(dependency_link) => {
-                self.viewing_recompile_dependency_file = Some(dependency_link.sink.clone());
+                self.viewing_link = Some(dependency_link.clone());
+                self.scroll_offset = 0;
             }
 
             AppEvent::StopViewDependentFile(_) => {
-                self.viewing_recompile_dependency_file = None;
+                self.viewing_link = None;
+                self.scroll_offset = 0;
+            }
+
+            AppEvent::ScrollCausesDown if self.scroll_offset + 1 < self.lines_count => {
+                self.scroll_offset += 1;
             }
 
-            AppEvent::Cancel => {
-                *self = Self::new();
+            AppEvent::ScrollCausesUp => {
+                self.scroll_offset = self.scroll_offset.saturating_sub(1);
             }
 
+            AppEvent::Cancel => self.reset(),
+
             _ => (),
         }
     }
 }
 
+impl ProduceEvent for State {
+    type Widget = DependencyCausePanel;
+
+    fn produce_event(
+        &mut self,
+        terminal_event: &crossterm::event::Event,
+        _widget: &Self::Widget,
+    ) -> Option<AppEvent> {
+        match terminal_event {
+            crossterm::event::Event::Key(key)
+                if key.kind == crossterm::event::KeyEventKind::Press =>
+            {
+                match keymap::current().action(Scope::DependencyCausePanel, key) {
+                    Some(Action::OpenInEditor) => {
+                        // Open the origin file at the first highlighted line of the first snippet
+                        let snippet = self.viewing_cause()?.snippets.first()?;
+
+                        Some(AppEvent::OpenInEditor(
+                            snippet.origin_file.clone(),
+                            snippet.highlight.0,
+                        ))
+                    }
+
+                    _ => None,
+                }
+            }
+
+            crossterm::event::Event::Mouse(mouse)
+                if utils::contains(&self.area, mouse.column, mouse.row) =>
+            {
+                match mouse.kind {
+                    MouseEventKind::ScrollDown => Some(AppEvent::ScrollCausesDown),
+                    MouseEventKind::ScrollUp => Some(AppEvent::ScrollCausesUp),
+                    _ => None,
+                }
+            }
+
+            _ => None,
+        }
+    }
+}
+
 impl<'a> StatefulWidget for DependencyCausePanel {
     type State = State;
 
@@ -100,92 +221,68 @@ fn render_bounding_box(area: Rect, buf: &mut Buffer) {
         .borders(Borders::ALL)
         .title("Dependency causes")
         .border_type(BorderType::Rounded)
-        .border_style(Style::default().fg(Color::White))
+        .border_style(theme::current().border)
         .render(area, buf);
 }
 
 fn render_cause_snippets(area: Rect, buf: &mut Buffer, state: &mut State) {
-    if let Some(ref viewing_file) = state.viewing_recompile_dependency_file {
-        let lines = match state
-            .dependency_causes
-            .iter()
-            .find(|cause| cause.sink == *viewing_file)
-        {
-            Some(cause) if cause.snippets.len() == 0 => {
-                vec![Line::styled(
-                    "No snippets",
-                    Style::default().add_modifier(Modifier::BOLD),
-                )]
-            }
-
-            Some(cause) if cause.snippets.len() > 0 => cause
-                .snippets
-                .iter()
-                .flat_map(|snippet| code_snippet_text(cause.source.clone(), snippet))
-                .collect(),
+    state.area = area;
+    state.lines_count = 0;
+
+    // The loading and failed states are shown as soon as a dependent is selected, the causes
+    // once one of its links is viewed
+    let lines = match (&state.causes, &state.viewing_link, state.viewing_cause()) {
+        (Causes::NotRequested, _, _) | (_, None, _) => return,
+
+        (Causes::Loading(_), _, _) => vec![Line::from(vec![
+            LoadingIcon::new().into(),
+            Span::from(" Loading dependency causes..."),
+        ])],
+
+        (Causes::Failed(error), _, _) => vec![
+            Line::styled("Failed to get the dependency causes", bold()),
+            Line::styled(error.clone(), theme::current().error),
+        ],
+
+        (_, _, Some(cause)) if cause.snippets.is_empty() => {
+            vec![Line::styled("No snippets", bold())]
+        }
 
-            _ => vec![],
-        };
+        (_, _, Some(cause)) => cause
+            .snippets
+            .iter()
+            .flat_map(|snippet| code_snippet_text(cause.source.clone(), snippet))
+            .collect(),
 
-        Paragraph::new(lines)
-            .style(Style::default().fg(Color::White))
-            .render(utils::padding(&area, 2, 2), buf);
-    }
-}
+        (_, Some(viewing_link), None) => vec![
+            Line::styled("No cause found", bold()),
+            Line::styled(missing_cause_reason(viewing_link), theme::current().hint),
+        ],
+    };
 
-fn code_snippet_text(source_file: FilePath, snippet: &CodeSnippet) -> Vec<Line> {
-    let header_line = Line::from(vec![
-        Span::from("-- File: "),
-        Span::from(source_file).add_modifier(Modifier::BOLD),
-    ]);
-
-    let max_line_number_len = snippet.lines_span.1.to_string().len();
-
-    let content_lines = snippet
-        .content
-        .split("\n")
-        .enumerate()
-        .map(|(index, line)| {
-            let line_number = index + snippet.lines_span.0;
-            let is_highlight =
-                line_number >= snippet.highlight.0 && line_number <= snippet.highlight.1;
-
-            let line_number_span = if is_highlight {
-                Span::from(format!(
-                    "{: >width$} =>",
-                    line_number,
-                    width = max_line_number_len
-                ))
-            } else {
-                Span::from(format!(
-                    "{: >width$}   ",
-                    line_number,
-                    width = max_line_number_len
-                ))
-            };
-
-            let mut line = Line::from(vec![line_number_span, Span::from(format!(" │ {}", line))]);
-            if is_highlight {
-                line.patch_style(Style::default().fg(Color::Green));
-            }
+    // The lines borrow from the state, so the state is only updated after rendering
+    let lines_count = lines.len();
+    let scroll_offset = state.scroll_offset.min(lines_count.saturating_sub(1));
 
-            line
-        });
+    Paragraph::new(lines)
+        .style(theme::current().text)
+        .scroll((scroll_offset as u16, 0))
+        .render(utils::padding(&area, 2, 2), buf);
 
-    let mut result = vec![header_line, Line::from("")];
-    result.extend(content_lines);
-    // Snippets separator
-    result.push(Line::from(""));
+    state.lines_count = lines_count;
+    state.scroll_offset = scroll_offset;
+}
 
-    result
+fn bold() -> Style {
+    Style::default().add_modifier(Modifier::BOLD)
 }
 
 #[cfg(test)]
 mod handle_event_tests {
     use super::*;
     use crate::{
-        adapter::NoopAdapter, DependencyLink, DependencyType, FileEntry, RecomplileDependency,
-        RecomplileDependencyReason,
+        adapter::NoopAdapter, CodeSnippet, DependencyLink, DependencyType, FileEntry,
+        RecomplileDependency, RecomplileDependencyReason,
     };
     use mpsc::Receiver;
 
@@ -212,14 +309,15 @@ mod handle_event_tests {
                 _source: &FilePath,
                 _sink: &FilePath,
                 _reason: &crate::RecomplileDependencyReason,
-                callback: Box<dyn FnOnce(Vec<DependencyCause>)>,
+                _chain: usize,
+                callback: Box<dyn FnOnce(anyhow::Result<Vec<DependencyCause>>)>,
             ) {
-                callback(vec![DependencyCause {
+                callback(Ok(vec![DependencyCause {
                     source: String::from("source"),
                     sink: String::from("sink"),
                     dependency_type: DependencyType::Compile,
                     snippets: self.snippets.clone(),
-                }])
+                }]))
             }
         }
 
@@ -233,6 +331,7 @@ mod handle_event_tests {
     #[test]
     fn select_file() {
         let snippets = vec![CodeSnippet {
+            origin_file: String::from("source"),
             content: String::from("content"),
             highlight: (2, 2),
             lines_span: (1, 3),
@@ -246,6 +345,7 @@ mod handle_event_tests {
             path: String::from("recompile_dependency"),
             reason: RecomplileDependencyReason::Compile,
             dependency_chain: vec![],
+            paths: Default::default(),
         });
         let (tx, rx) = mpsc::channel::<AppEvent>();
         state.handle_event(&event, &widget(), &mut adapter, tx.clone());
@@ -253,14 +353,73 @@ mod handle_event_tests {
         let events = collect_events(rx);
         assert_eq!(events.len(), 1);
 
-        if let AppEvent::GetDependencyCausesDone(causes) = &events[0] {
+        if let AppEvent::GetDependencyCausesDone(_, Ok(causes)) = &events[0] {
             assert_eq!(causes[0].snippets, snippets);
         } else {
             panic!("Expected GetDependencyCausesDone event");
         }
 
+        assert!(matches!(state.causes, Causes::Loading(_)));
         state.handle_event(&events[0], &widget(), &mut adapter, tx);
-        assert_eq!(state.dependency_causes[0].snippets, snippets);
+
+        match state.causes {
+            Causes::Loaded(ref causes) => assert_eq!(causes[0].snippets, snippets),
+            ref causes => panic!("Expected loaded causes, found {:?}", causes),
+        }
+    }
+
+    #[test]
+    fn late_response_is_ignored() {
+        let mut state = State::new();
+        let (tx, _rx) = mpsc::channel::<AppEvent>();
+
+        for event in [
+            AppEvent::SelectDependentFile(RecomplileDependency::fixture("first")),
+            AppEvent::SelectDependentFile(RecomplileDependency::fixture("second")),
+            AppEvent::GetDependencyCausesDone(1, Ok(vec![])),
+        ] {
+            state.handle_event(&event, &widget(), &mut NoopAdapter::new(), tx.clone());
+        }
+        assert!(matches!(state.causes, Causes::Loading(2)));
+
+        let event = AppEvent::GetDependencyCausesDone(2, Err(anyhow::anyhow!("Server error")));
+        state.handle_event(&event, &widget(), &mut NoopAdapter::new(), tx);
+        assert!(matches!(state.causes, Causes::Failed(ref error) if error == "Server error"));
+    }
+
+    #[test]
+    fn response_after_reset_is_ignored() {
+        let mut state = State::new();
+        let (tx, _rx) = mpsc::channel::<AppEvent>();
+
+        for event in [
+            AppEvent::SelectDependentFile(RecomplileDependency::fixture("first")),
+            AppEvent::ResetDependencyCauses(
+                Some((
+                    String::from("source"),
+                    RecomplileDependency::fixture("second"),
+                )),
+                None,
+            ),
+            AppEvent::GetDependencyCausesDone(1, Ok(vec![])),
+        ] {
+            state.handle_event(&event, &widget(), &mut NoopAdapter::new(), tx.clone());
+        }
+        assert!(matches!(state.causes, Causes::Loading(2)));
+
+        state.handle_event(
+            &AppEvent::Cancel,
+            &widget(),
+            &mut NoopAdapter::new(),
+            tx.clone(),
+        );
+        state.handle_event(
+            &AppEvent::GetDependencyCausesDone(2, Ok(vec![])),
+            &widget(),
+            &mut NoopAdapter::new(),
+            tx,
+        );
+        assert!(matches!(state.causes, Causes::NotRequested));
     }
 
     #[test]
@@ -276,7 +435,7 @@ mod handle_event_tests {
         state.handle_event(&event, &widget(), &mut NoopAdapter::new(), tx);
 
         assert_eq!(
-            state.viewing_recompile_dependency_file,
+            state.viewing_link.map(|link| link.sink),
             Some(String::from("sink"))
         );
         assert_eq!(collect_events(rx).len(), 0);
@@ -285,16 +444,135 @@ mod handle_event_tests {
     #[test]
     fn cancel() {
         let mut state = State::new();
-        state.dependency_causes = vec![DependencyCause {
+        state.causes = Causes::Loaded(vec![DependencyCause {
             source: String::from("source"),
             sink: String::from("sink"),
             dependency_type: DependencyType::Compile,
             snippets: vec![],
-        }];
+        }]);
 
         let (tx, rx) = mpsc::channel::<AppEvent>();
         state.handle_event(&AppEvent::Cancel, &widget(), &mut NoopAdapter::new(), tx);
-        assert_eq!(state.dependency_causes.len(), 0);
+        assert!(matches!(state.causes, Causes::NotRequested));
         assert_eq!(collect_events(rx).len(), 0);
     }
+
+    #[test]
+    fn scroll_causes() {
+        let mut state = State::new();
+        state.lines_count = 2;
+
+        let (tx, _) = mpsc::channel::<AppEvent>();
+        for event in [AppEvent::ScrollCausesDown, AppEvent::ScrollCausesDown] {
+            state.handle_event(&event, &widget(), &mut NoopAdapter::new(), tx.clone());
+        }
+        assert_eq!(state.scroll_offset, 1);
+
+        for event in [AppEvent::ScrollCausesUp, AppEvent::ScrollCausesUp] {
+            state.handle_event(&event, &widget(), &mut NoopAdapter::new(), tx.clone());
+        }
+        assert_eq!(state.scroll_offset, 0);
+    }
+}
+
+#[cfg(test)]
+mod produce_event_tests {
+    use super::*;
+    use crate::{CodeSnippet, DependencyType};
+    use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers, MouseEvent};
+
+    fn key_event(char: char) -> Event {
+        Event::Key(KeyEvent::new(KeyCode::Char(char), KeyModifiers::NONE))
+    }
+
+    fn link() -> DependencyLink {
+        DependencyLink {
+            source: String::from("source"),
+            sink: String::from("sink"),
+            dependency_type: DependencyType::Compile,
+        }
+    }
+
+    fn state_with_snippets(snippets: Vec<CodeSnippet>) -> State {
+        let mut state = State::new();
+        state.causes = Causes::Loaded(vec![DependencyCause {
+            source: String::from("source"),
+            sink: String::from("sink"),
+            dependency_type: DependencyType::Compile,
+            snippets,
+        }]);
+
+        state
+    }
+
+    #[test]
+    fn open_in_editor() {
+        let mut state = state_with_snippets(vec![CodeSnippet {
+            origin_file: String::from("lib/source.ex"),
+            content: String::from("content"),
+            highlight: (7, 8),
+            lines_span: (2, 13),
+        }]);
+        state.viewing_link = Some(link());
+
+        let widget = DependencyCausePanel::new(Some(String::from("source")));
+        match state.produce_event(&key_event('e'), &widget) {
+            Some(AppEvent::OpenInEditor(file, line)) => {
+                assert_eq!(file, "lib/source.ex");
+                assert_eq!(line, 7);
+            }
+
+            _ => panic!("Expected OpenInEditor event"),
+        }
+    }
+
+    #[test]
+    fn open_in_editor_without_snippets() {
+        let mut state = state_with_snippets(vec![]);
+        state.viewing_link = Some(link());
+
+        let widget = DependencyCausePanel::new(Some(String::from("source")));
+        assert!(state.produce_event(&key_event('e'), &widget).is_none());
+    }
+
+    #[test]
+    fn open_in_editor_when_not_viewing() {
+        let mut state = state_with_snippets(vec![CodeSnippet {
+            origin_file: String::from("lib/source.ex"),
+            content: String::from("content"),
+            highlight: (7, 8),
+            lines_span: (2, 13),
+        }]);
+
+        let widget = DependencyCausePanel::new(Some(String::from("source")));
+        assert!(state.produce_event(&key_event('e'), &widget).is_none());
+    }
+
+    #[test]
+    fn scroll_wheel_over_panel() {
+        let mut state = State::new();
+        state.area = Rect::new(40, 0, 40, 20);
+
+        let scroll = |kind, column| {
+            Event::Mouse(MouseEvent {
+                kind,
+                column,
+                row: 5,
+                modifiers: KeyModifiers::NONE,
+            })
+        };
+
+        let widget = DependencyCausePanel::new(Some(String::from("source")));
+        assert!(matches!(
+            state.produce_event(&scroll(MouseEventKind::ScrollDown, 50), &widget),
+            Some(AppEvent::ScrollCausesDown)
+        ));
+        assert!(matches!(
+            state.produce_event(&scroll(MouseEventKind::ScrollUp, 50), &widget),
+            Some(AppEvent::ScrollCausesUp)
+        ));
+        assert!(state
+            .produce_event(&scroll(MouseEventKind::ScrollDown, 10), &widget)
+            .is_none());
+    }
 }
@@ -0,0 +1,286 @@
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, BorderType, Borders, StatefulWidget, Widget};
+use std::collections::HashSet;
+use std::rc::Rc;
+use std::sync::mpsc;
+
+use crate::adapter::ServerAdapter;
+use crate::app_event::AppEvent;
+use crate::components::tree_list::TreeList;
+use crate::dependency_tree::{DependencyTree, Row};
+use crate::keymap::{self, Action, Scope};
+use crate::theme;
+use crate::utils;
+use crate::{FilePath, HandleEvent, ProduceEvent};
+
+/// The dependents of the selected file as a tree, the alternative to the flat list of
+/// `FileDependentPanel`
+#[derive(Clone)]
+pub struct DependentsTree {
+    tree: Rc<DependencyTree>,
+}
+
+impl DependentsTree {
+    pub fn new(tree: Rc<DependencyTree>) -> Self {
+        Self { tree }
+    }
+}
+
+pub struct State {
+    // The root the expanded files belong to, they are forgotten when the root changes
+    root: Option<FilePath>,
+    expanded: HashSet<FilePath>,
+    list: TreeList,
+}
+
+impl State {
+    pub fn new() -> Self {
+        Self {
+            root: None,
+            expanded: HashSet::new(),
+            list: TreeList::new(),
+        }
+    }
+
+    // Start over when the tree of another file is shown
+    fn follow_root(&mut self, tree: &DependencyTree) {
+        if self.root.as_ref() != Some(tree.root()) {
+            *self = Self {
+                root: Some(tree.root().clone()),
+                ..Self::new()
+            };
+        }
+    }
+
+    fn rows(&self, tree: &DependencyTree) -> Vec<Row> {
+        tree.rows(&self.expanded)
+    }
+
+    fn selected<'a>(&self, rows: &'a [Row]) -> Option<&'a Row> {
+        rows.get(self.list.selected_row())
+    }
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HandleEvent for State {
+    type Widget = DependentsTree;
+
+    fn handle_event(
+        &mut self,
+        event: &AppEvent,
+        widget: &Self::Widget,
+        _adapter: &mut impl ServerAdapter,
+        _dispatcher: mpsc::Sender<AppEvent>,
+    ) {
+        self.follow_root(&widget.tree);
+        let rows_count = self.rows(&widget.tree).len();
+
+        match event {
+            AppEvent::ToggleTreeNode(path) => {
+                if !self.expanded.remove(path) {
+                    self.expanded.insert(path.clone());
+                }
+
+                // Collapsing can hide the selected row, the toggled file takes the selection
+                let rows = self.rows(&widget.tree);
+                if let Some(index) = rows
+                    .iter()
+                    .position(|row| row.node.path == *path && row.expandable)
+                {
+                    self.list.select(index);
+                }
+            }
+
+            AppEvent::Cancel => *self = Self::new(),
+            event => self.list.navigate(event, rows_count),
+        }
+    }
+}
+
+impl ProduceEvent for State {
+    type Widget = DependentsTree;
+
+    fn produce_event(
+        &mut self,
+        terminal_event: &crossterm::event::Event,
+        widget: &Self::Widget,
+    ) -> Option<AppEvent> {
+        match terminal_event {
+            crossterm::event::Event::Key(key)
+                if key.kind == crossterm::event::KeyEventKind::Press =>
+            {
+                let rows = self.rows(&widget.tree);
+
+                match keymap::current().action(Scope::FileDependentPanel, key) {
+                    Some(Action::Select) => {
+                        let row = self.selected(&rows).filter(|row| row.expandable)?;
+                        Some(AppEvent::ToggleTreeNode(row.node.path.clone()))
+                    }
+
+                    Some(Action::Pivot) => {
+                        let row = self.selected(&rows)?;
+                        Some(AppEvent::PivotDependencySource(row.node.path.clone()))
+                    }
+
+                    Some(Action::ToggleTree) => Some(AppEvent::ToggleDependentsTree),
+                    _ => None,
+                }
+            }
+
+            crossterm::event::Event::Mouse(mouse) => self.list.mouse_event(mouse),
+
+            _ => None,
+        }
+    }
+}
+
+impl StatefulWidget for DependentsTree {
+    type State = State;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut State) {
+        state.follow_root(&self.tree);
+
+        let rect = utils::padding(&area, 1, 1);
+        let rows = state.rows(&self.tree);
+
+        render_bounding_box(self.tree.root(), area, buf);
+        state
+            .list
+            .render(&rows, rect, buf, |row| row_line(row, rect.width as usize));
+    }
+}
+
+// A shared file points to where its subtree is shown, with the number of files it recompiles
+fn row_line(row: &Row, width: usize) -> Line<'static> {
+    let marker = match (row.node.shared, row.expandable, row.expanded) {
+        (true, _, _) => "↻",
+        (false, true, true) => "▼",
+        (false, true, false) => "▶",
+        (false, false, _) => "•",
+    };
+
+    let indent = "  ".repeat(row.depth);
+    let count = format!("{: >4}", row.node.dependents_count);
+    let max_width = width.saturating_sub(indent.len() + count.len() + 3);
+    let file_path = utils::compact_file_path(&row.node.path, max_width);
+    let padding = max_width.saturating_sub(file_path.chars().count());
+
+    let path_style = if row.node.shared {
+        theme::current().hint
+    } else {
+        theme::current().text
+    };
+
+    Line::from(vec![
+        Span::from(format!("{}{} ", indent, marker)),
+        Span::styled(file_path, path_style),
+        Span::from(" ".repeat(padding)),
+        Span::styled(count, theme::current().count),
+        Span::from(" "),
+    ])
+}
+
+fn render_bounding_box(source_file: &FilePath, area: Rect, buf: &mut Buffer) {
+    let filename = source_file.rsplit('/').next().unwrap_or("...");
+
+    Block::default()
+        .borders(Borders::ALL)
+        .title(Line::from(format!("Dependents tree ({})", filename)))
+        .border_type(BorderType::Rounded)
+        .border_style(theme::current().border)
+        .render(area, buf);
+}
+
+#[cfg(test)]
+mod handle_event_tests {
+    use super::*;
+    use crate::adapter::NoopAdapter;
+    use crate::{FileEntry, RecomplileDependency};
+
+    // b and c depend on a, d depends on b
+    fn widget() -> DependentsTree {
+        let direct = |path: &str, source: &str| {
+            RecomplileDependency::fixture(path).with_chain_through(&[path, source])
+        };
+        let files = vec![
+            FileEntry::fixture("a", vec![direct("b", "a"), direct("c", "a")]),
+            FileEntry::fixture("b", vec![direct("d", "b")]),
+        ];
+
+        DependentsTree::new(Rc::new(DependencyTree::build(&String::from("a"), &files)))
+    }
+
+    fn dispatch_events(state: &mut State, widget: &DependentsTree, events: &[AppEvent]) {
+        let (tx, _rx) = mpsc::channel::<AppEvent>();
+
+        for event in events {
+            state.handle_event(event, widget, &mut NoopAdapter::new(), tx.clone());
+        }
+    }
+
+    #[test]
+    fn move_and_expand() {
+        let widget = widget();
+        let mut state = State::new();
+
+        dispatch_events(
+            &mut state,
+            &widget,
+            &[
+                AppEvent::ToggleTreeNode(String::from("b")),
+                AppEvent::DownButtonPressed,
+                AppEvent::DownButtonPressed,
+                AppEvent::DownButtonPressed,
+            ],
+        );
+
+        let rows = state.rows(&widget.tree);
+        assert_eq!(rows.len(), 3);
+        assert_eq!(state.selected(&rows).unwrap().node.path, "c");
+    }
+
+    #[test]
+    fn collapse_selects_toggled_file() {
+        let widget = widget();
+        let mut state = State::new();
+
+        dispatch_events(
+            &mut state,
+            &widget,
+            &[
+                AppEvent::ToggleTreeNode(String::from("b")),
+                AppEvent::FocusTreeRow(1),
+                AppEvent::ToggleTreeNode(String::from("b")),
+            ],
+        );
+
+        assert_eq!(state.list.selected_row(), 0);
+        assert_eq!(state.rows(&widget.tree).len(), 2);
+    }
+
+    #[test]
+    fn other_root_starts_over() {
+        let mut state = State::new();
+        dispatch_events(
+            &mut state,
+            &widget(),
+            &[
+                AppEvent::ToggleTreeNode(String::from("b")),
+                AppEvent::DownButtonPressed,
+            ],
+        );
+
+        let other = DependentsTree::new(Rc::new(DependencyTree::build(&String::from("b"), &[])));
+        dispatch_events(&mut state, &other, &[AppEvent::DownButtonPressed]);
+
+        assert!(state.expanded.is_empty());
+        assert_eq!(state.list.selected_row(), 0);
+    }
+}
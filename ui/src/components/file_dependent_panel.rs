@@ -1,259 +1,410 @@
+use crossterm::event::{MouseButton, MouseEventKind};
 use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
-use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::style::Style;
 use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, BorderType, Borders, Paragraph, StatefulWidget, Widget};
+use std::collections::HashSet;
+use std::rc::Rc;
 use std::sync::mpsc;
 
 use crate::adapter::ServerAdapter;
+use crate::keymap::{self, Action, Scope};
+use crate::query::Query;
+use crate::theme;
 use crate::utils;
 use crate::{
     AppEvent, DependencyLink, DependencyType, FilePath, HandleEvent, ProduceEvent,
-    RecomplileDependency,
+    RecomplileDependency, RecomplileDependencyReason,
 };
 
 #[derive(Clone)]
 pub struct FileDependentPanel {
     dependency_source: FilePath,
-    files: Vec<RecomplileDependency>,
+    // Shared with the panel cached between frames
+    files: Rc<[RecomplileDependency]>,
+    query: Query,
     panel_title: Option<String>,
+    // The dependents are listed under a collapsible header for each recompile reason
+    grouped: bool,
 }
 
 impl FileDependentPanel {
     pub fn new(
         dependency_source: FilePath,
-        files: Vec<RecomplileDependency>,
+        mut files: Vec<RecomplileDependency>,
+        query: Query,
         panel_title: Option<String>,
+        grouped: bool,
     ) -> Self {
+        if grouped {
+            sort_into_groups(&mut files);
+        }
+
         Self {
             dependency_source,
-            files,
+            files: files.into(),
+            query,
             panel_title,
+            grouped,
+        }
+    }
+
+    // The indexes of the files in each group which has any, in the order of the reasons
+    fn groups(&self) -> Vec<(usize, Vec<usize>)> {
+        (0..RecomplileDependencyReason::ALL.len())
+            .map(|group| {
+                let indexes: Vec<usize> = (0..self.files.len())
+                    .filter(|&index| group_index(&self.files[index].reason) == group)
+                    .collect();
+
+                (group, indexes)
+            })
+            .filter(|(_, indexes)| !indexes.is_empty())
+            .collect()
+    }
+}
+
+/// Order the dependents as they are listed in the grouped mode. The files of a group are next to
+/// each other, so the indexes follow the rows
+pub fn sort_into_groups(files: &mut [RecomplileDependency]) {
+    files.sort_by_key(|file| group_index(&file.reason));
+}
+
+fn group_index(reason: &RecomplileDependencyReason) -> usize {
+    RecomplileDependencyReason::ALL
+        .iter()
+        .position(|other| other == reason)
+        .unwrap_or(0)
+}
+
+// Clicking within this many columns from the left edge of a file row hits the ▶/▼ marker
+const MARKER_WIDTH: u16 = 3;
+
+/// A row of the panel, where the cursor can be
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Cursor {
+    File(usize),
+    // (Index of the file, Index of the link in its dependency chain)
+    Link(usize, usize),
+    // The header of a group, by the index of its reason in `RecomplileDependencyReason::ALL`
+    Header(usize),
+}
+
+impl Cursor {
+    /// Index of the file the cursor is on, or in the chain of
+    pub fn file_index(&self) -> Option<usize> {
+        match self {
+            Cursor::File(index) | Cursor::Link(index, _) => Some(*index),
+            Cursor::Header(_) => None,
         }
     }
 }
 
 pub struct State {
-    // (Index for the outer list, Index for the expanded inner list)
-    selected_file_index: (usize, Option<usize>),
-    expanded_file: Option<String>,
+    selected: Cursor,
+    // Ids of the expanded dependents
+    expanded: HashSet<String>,
+    folded_groups: Vec<RecomplileDependencyReason>,
+    // Id of the dependent the cause panel shows the causes of. Moving into the chain of another
+    // dependent gets its causes
+    causes_dependent: Option<String>,
+    // The area of the rows, their scroll offset and targets in the last render, used to map
+    // mouse positions back to rows
+    rows_area: Rect,
+    scroll_offset: usize,
+    row_targets: Vec<Cursor>,
 }
 
 impl State {
     pub fn new() -> Self {
         Self {
-            selected_file_index: (0, None),
-            expanded_file: None,
+            selected: Cursor::File(0),
+            expanded: HashSet::new(),
+            folded_groups: vec![],
+            causes_dependent: None,
+            rows_area: Rect::default(),
+            scroll_offset: 0,
+            row_targets: vec![],
         }
     }
-}
 
-impl HandleEvent for State {
-    type Widget = FileDependentPanel;
+    /// Restore the selection and expansion of an earlier view, with the dependent the cause
+    /// panel is reset to
+    pub fn restore(
+        selected: Cursor,
+        expanded: HashSet<String>,
+        causes_dependent: Option<String>,
+    ) -> Self {
+        Self {
+            selected,
+            expanded,
+            causes_dependent,
+            ..Self::new()
+        }
+    }
 
-    fn handle_event(
-        &mut self,
-        event: &AppEvent,
-        widget: &Self::Widget,
-        _adapter: &mut impl ServerAdapter,
-        mut dispatcher: mpsc::Sender<AppEvent>,
-    ) {
-        match event {
-            AppEvent::DownButtonPressed => {
-                handle_down_button_pressed(self, widget, &mut dispatcher)
-            }
-            AppEvent::UpButtonPressed => handle_up_button_pressed(self, widget, &mut dispatcher),
+    pub fn selected(&self) -> Cursor {
+        self.selected
+    }
 
-            AppEvent::SelectDependentFile(file) => match self.expanded_file {
-                Some(ref expanded) if expanded == &file.id => self.expanded_file = None,
-                _ => self.expanded_file = Some(file.id.clone()),
-            },
+    pub fn expanded(&self) -> &HashSet<String> {
+        &self.expanded
+    }
 
-            AppEvent::Cancel => {
-                *self = Self::new();
+    // The rows the cursor moves through, in order. Each link counts once
+    fn rows(&self, widget: &FileDependentPanel) -> Vec<Cursor> {
+        let mut rows = vec![];
+
+        if !widget.grouped {
+            self.push_file_rows(widget, 0..widget.files.len(), &mut rows);
+            return rows;
+        }
+
+        for (group, indexes) in widget.groups() {
+            rows.push(Cursor::Header(group));
+
+            if !self
+                .folded_groups
+                .contains(&RecomplileDependencyReason::ALL[group])
+            {
+                self.push_file_rows(widget, indexes, &mut rows);
             }
-            _ => (),
         }
+
+        rows
     }
-}
 
-fn handle_down_button_pressed(
-    state: &mut State,
-    widget: &FileDependentPanel,
-    dispatcher: &mut mpsc::Sender<AppEvent>,
-) {
-    enum Action {
-        NextOuterList,
-        NextExpandedList,
-        ExitExpandedList,
-    }
-
-    let outer_list_len = widget.files.len();
-    let outer_list_index = state.selected_file_index.0;
-
-    let actions: &[Action] = match state.expanded_file {
-        Some(ref expanded) => {
-            let at_expanded_file = widget.files[state.selected_file_index.0].id == *expanded;
-            let expanded_list_len = widget.files[state.selected_file_index.0]
-                .dependency_chain
-                .len();
-
-            if at_expanded_file {
-                match state.selected_file_index.1 {
-                    Some(expanded_index) if expanded_index == expanded_list_len - 1 => {
-                        // We reach the end of the list
-                        if outer_list_index == outer_list_len - 1 {
-                            &[]
-                        } else {
-                            &[Action::ExitExpandedList, Action::NextOuterList]
-                        }
-                    }
+    fn push_file_rows(
+        &self,
+        widget: &FileDependentPanel,
+        indexes: impl IntoIterator<Item = usize>,
+        rows: &mut Vec<Cursor>,
+    ) {
+        for index in indexes {
+            rows.push(Cursor::File(index));
 
-                    _ => &[Action::NextExpandedList],
-                }
-            } else {
-                &[Action::NextOuterList]
+            let file = &widget.files[index];
+            if self.expanded.contains(&file.id) {
+                let links = 0..file.dependency_chain.len();
+                rows.extend(links.map(|link| Cursor::Link(index, link)));
             }
         }
+    }
 
-        None => &[Action::NextOuterList],
-    };
+    // Keep the cursor on a row, the list shrinks as a search is typed and chains collapse. The
+    // cursor falls back to the file of a link, the header of a folded file, then the nearest file
+    fn clamp_selection(&mut self, widget: &FileDependentPanel) {
+        let rows = self.rows(widget);
+        if rows.contains(&self.selected) {
+            return;
+        }
 
-    for action in actions {
-        match action {
-            Action::NextOuterList => {
-                if widget.files.len() != 0 && state.selected_file_index.0 < widget.files.len() - 1 {
-                    state.selected_file_index.0 += 1;
+        let last_file = widget.files.len().saturating_sub(1);
+        let fallback = match self.selected {
+            Cursor::Link(index, _) | Cursor::File(index) if index <= last_file => {
+                match widget.files.get(index) {
+                    Some(file) if widget.grouped && self.folded_groups.contains(&file.reason) => {
+                        Cursor::Header(group_index(&file.reason))
+                    }
+                    _ => Cursor::File(index),
                 }
             }
+            Cursor::Link(..) | Cursor::File(_) => Cursor::File(last_file),
+            Cursor::Header(_) => Cursor::File(0),
+        };
+
+        self.selected = if rows.contains(&fallback) {
+            fallback
+        } else {
+            rows.first().copied().unwrap_or(Cursor::File(0))
+        };
+    }
 
-            Action::NextExpandedList => match state.selected_file_index.1 {
-                Some(index) => {
-                    dispatcher
-                        .send(stop_view_file_event(state, index, widget))
-                        .unwrap();
-                    dispatcher
-                        .send(view_file_event(state, index + 1, widget))
-                        .unwrap();
-                    state.selected_file_index.1 = Some(index + 1);
-                }
+    // Returns the target rendered at the given terminal position
+    fn row_target_at(&self, column: u16, row: u16) -> Option<Cursor> {
+        if !utils::contains(&self.rows_area, column, row) {
+            return None;
+        }
 
-                None => {
-                    dispatcher.send(view_file_event(state, 0, widget)).unwrap();
-                    state.selected_file_index.1 = Some(0);
-                }
-            },
+        let index = (row - self.rows_area.y) as usize + self.scroll_offset;
+        self.row_targets.get(index).copied()
+    }
 
-            Action::ExitExpandedList => {
-                dispatcher
-                    .send(stop_view_file_event(
-                        state,
-                        state.selected_file_index.1.unwrap(),
-                        widget,
-                    ))
-                    .unwrap();
-                state.selected_file_index.1 = None;
-            }
+    fn selected_group(&self) -> Option<RecomplileDependencyReason> {
+        match self.selected {
+            Cursor::Header(group) => Some(RecomplileDependencyReason::ALL[group].clone()),
+            _ => None,
         }
     }
 }
 
-fn handle_up_button_pressed(
-    state: &mut State,
-    widget: &FileDependentPanel,
-    dispatcher: &mut mpsc::Sender<AppEvent>,
-) {
-    enum Action {
-        PrevOuterList,
-        PrevExpandedList,
-        ExitExpandedList,
-    }
+impl HandleEvent for State {
+    type Widget = FileDependentPanel;
+
+    fn handle_event(
+        &mut self,
+        event: &AppEvent,
+        widget: &Self::Widget,
+        _adapter: &mut impl ServerAdapter,
+        mut dispatcher: mpsc::Sender<AppEvent>,
+    ) {
+        self.clamp_selection(widget);
 
-    let action: Action = match state.expanded_file {
-        Some(ref expanded) => {
-            let at_expanded_file = widget.files[state.selected_file_index.0].id == *expanded;
+        match event {
+            AppEvent::DownButtonPressed => move_selection(self, widget, true, &mut dispatcher),
+            AppEvent::UpButtonPressed => move_selection(self, widget, false, &mut dispatcher),
+
+            AppEvent::SelectDependentFile(file) => {
+                // The file can be selected by a click on another row than the focused one
+                if let Some(index) = widget.files.iter().position(|f| f.id == file.id) {
+                    focus(self, widget, Cursor::File(index), &mut dispatcher);
+                }
 
-            if at_expanded_file {
-                match state.selected_file_index.1 {
-                    Some(expanded_index) if expanded_index == 0 => Action::ExitExpandedList,
-                    None => Action::PrevOuterList,
-                    _ => Action::PrevExpandedList,
+                if !self.expanded.remove(&file.id) {
+                    self.expanded.insert(file.id.clone());
                 }
-            } else {
-                Action::PrevOuterList
+
+                // The cause panel gets the causes of the file
+                self.causes_dependent = Some(file.id.clone());
             }
-        }
 
-        None => Action::PrevOuterList,
-    };
+            AppEvent::FocusDependentFile(index, link) => {
+                let valid = match widget.files.get(*index) {
+                    Some(file) => match link {
+                        Some(link) => {
+                            self.expanded.contains(&file.id) && *link < file.dependency_chain.len()
+                        }
+                        None => true,
+                    },
+                    None => false,
+                };
 
-    match action {
-        Action::PrevOuterList => {
-            if state.selected_file_index.0 > 0 {
-                state.selected_file_index.0 -= 1;
+                let cursor = match link {
+                    Some(link) => Cursor::Link(*index, *link),
+                    None => Cursor::File(*index),
+                };
 
-                // If the previous item is expanded, move to the last item in the expanded list
-                if let Some(expanded) = &state.expanded_file {
-                    let selected_file = &widget.files[state.selected_file_index.0];
+                if valid {
+                    focus(self, widget, cursor, &mut dispatcher);
+                }
+            }
+
+            AppEvent::ExpandAllDependents => {
+                self.expanded = widget.files.iter().map(|file| file.id.clone()).collect();
+            }
+
+            AppEvent::CollapseAllDependents => {
+                if let Cursor::Link(index, _) = self.selected {
+                    focus(self, widget, Cursor::File(index), &mut dispatcher);
+                }
 
-                    if selected_file.id == *expanded {
-                        state.selected_file_index.1 =
-                            Some(selected_file.dependency_chain.len() - 1);
+                self.expanded.clear();
+            }
+
+            AppEvent::CycleDependencyChain(id, forward) => {
+                if let Some(index) = widget.files.iter().position(|file| file.id == *id) {
+                    let mut file = widget.files[index].clone();
+                    if !file.cycle_chain(*forward) {
+                        return;
+                    }
 
+                    // The links change with the chain, the cursor goes back to the dependent
+                    if matches!(self.selected, Cursor::Link(link_index, _) if link_index == index) {
+                        focus(self, widget, Cursor::File(index), &mut dispatcher);
+                    }
+
+                    if self.causes_dependent.as_ref() == Some(id) {
+                        let dependent = (widget.dependency_source.clone(), file);
                         dispatcher
-                            .send(view_file_event(
-                                state,
-                                selected_file.dependency_chain.len() - 1,
-                                widget,
-                            ))
+                            .send(AppEvent::ResetDependencyCauses(Some(dependent), None))
                             .unwrap();
                     }
                 }
             }
-        }
 
-        Action::PrevExpandedList => match state.selected_file_index.1 {
-            Some(index) if index > 0 => {
-                state.selected_file_index.1 = Some(index - 1);
-                dispatcher
-                    .send(stop_view_file_event(state, index, widget))
-                    .unwrap();
-                dispatcher
-                    .send(view_file_event(state, index - 1, widget))
-                    .unwrap();
+            AppEvent::ToggleDependentGroup(reason) => {
+                let group = group_index(reason);
+                focus(self, widget, Cursor::Header(group), &mut dispatcher);
+
+                match self
+                    .folded_groups
+                    .iter()
+                    .position(|folded| folded == reason)
+                {
+                    Some(index) => {
+                        self.folded_groups.remove(index);
+                    }
+                    None => self.folded_groups.push(reason.clone()),
+                }
+            }
+
+            AppEvent::Cancel => {
+                *self = Self::new();
             }
             _ => (),
-        },
-
-        Action::ExitExpandedList => {
-            dispatcher
-                .send(stop_view_file_event(
-                    state,
-                    state.selected_file_index.1.unwrap(),
-                    widget,
-                ))
-                .unwrap();
-            state.selected_file_index.1 = None;
         }
     }
 }
 
-fn view_file_event(
-    state: &State,
-    dependency_node_index: usize,
+// Move the cursor by one row. Headers are rows too in the grouped mode, and the links of every
+// expanded chain are passed through in order
+fn move_selection(
+    state: &mut State,
     widget: &FileDependentPanel,
-) -> AppEvent {
-    let selected_file = &widget.files[state.selected_file_index.0];
-    AppEvent::ViewDependentFile(selected_file.dependency_chain[dependency_node_index].clone())
+    down: bool,
+    dispatcher: &mut mpsc::Sender<AppEvent>,
+) {
+    let rows = state.rows(widget);
+
+    let target = match rows.iter().position(|row| *row == state.selected) {
+        Some(position) if down => rows.get(position + 1),
+        Some(position) => position
+            .checked_sub(1)
+            .and_then(|position| rows.get(position)),
+        None => rows.first(),
+    };
+
+    if let Some(target) = target {
+        focus(state, widget, *target, dispatcher);
+    }
 }
 
-fn stop_view_file_event(
-    state: &State,
-    dependency_node_index: usize,
+// Move the cursor to another row, swapping the viewed dependency link if needed. Moving into the
+// chain of another dependent than the one the cause panel shows resets the panel to it
+fn focus(
+    state: &mut State,
     widget: &FileDependentPanel,
-) -> AppEvent {
-    let selected_file = &widget.files[state.selected_file_index.0];
-    AppEvent::StopViewDependentFile(selected_file.dependency_chain[dependency_node_index].clone())
+    target: Cursor,
+    dispatcher: &mut mpsc::Sender<AppEvent>,
+) {
+    if state.selected == target {
+        return;
+    }
+
+    if let Cursor::Link(index, link) = state.selected {
+        let link = widget.files[index].dependency_chain[link].clone();
+        dispatcher
+            .send(AppEvent::StopViewDependentFile(link))
+            .unwrap();
+    }
+
+    state.selected = target;
+
+    if let Cursor::Link(index, link) = target {
+        let file = &widget.files[index];
+        let link = file.dependency_chain[link].clone();
+
+        let event = if state.causes_dependent.as_ref() == Some(&file.id) {
+            AppEvent::ViewDependentFile(link)
+        } else {
+            state.causes_dependent = Some(file.id.clone());
+            let dependent = (widget.dependency_source.clone(), file.clone());
+            AppEvent::ResetDependencyCauses(Some(dependent), Some(link))
+        };
+
+        dispatcher.send(event).unwrap();
+    }
 }
 
 impl ProduceEvent for State {
@@ -264,90 +415,264 @@ impl ProduceEvent for State {
         terminal_event: &crossterm::event::Event,
         widget: &Self::Widget,
     ) -> Option<AppEvent> {
-        if let crossterm::event::Event::Key(key) = terminal_event {
-            if key.kind == crossterm::event::KeyEventKind::Press {
-                return match key.code {
-                    crossterm::event::KeyCode::Enter => {
-                        let index = self.selected_file_index.0;
+        match terminal_event {
+            crossterm::event::Event::Key(key)
+                if key.kind == crossterm::event::KeyEventKind::Press =>
+            {
+                match keymap::current().action(Scope::FileDependentPanel, key) {
+                    Some(Action::Select) if self.selected_group().is_some() => {
+                        Some(AppEvent::ToggleDependentGroup(self.selected_group()?))
+                    }
+
+                    Some(Action::Select) => {
+                        let file = widget.files.get(self.selected.file_index()?)?;
 
-                        Some(AppEvent::SelectDependentFile(widget.files[index].clone()))
+                        Some(AppEvent::SelectDependentFile(file.clone()))
                     }
 
+                    Some(Action::Pivot) => {
+                        let path = match self.selected {
+                            Cursor::File(index) => &widget.files.get(index)?.path,
+                            Cursor::Link(index, link) => {
+                                &widget.files.get(index)?.dependency_chain.get(link)?.sink
+                            }
+                            Cursor::Header(_) => return None,
+                        };
+
+                        Some(AppEvent::PivotDependencySource(path.clone()))
+                    }
+
+                    Some(Action::ToggleTree) => Some(AppEvent::ToggleDependentsTree),
+                    Some(Action::GroupByReason) => Some(AppEvent::ToggleDependentGrouping),
+                    Some(Action::ExpandAll) => Some(AppEvent::ExpandAllDependents),
+                    Some(Action::CollapseAll) => Some(AppEvent::CollapseAllDependents),
+
+                    Some(action @ (Action::NextPath | Action::PreviousPath)) => {
+                        let file = widget.files.get(self.selected.file_index()?)?;
+                        let forward = action == Action::NextPath;
+
+                        Some(AppEvent::CycleDependencyChain(file.id.clone(), forward))
+                    }
+
+                    Some(action) => action.toggled_reason().map(AppEvent::ToggleReason),
+
                     _ => None,
-                };
+                }
             }
-        }
 
-        None
+            crossterm::event::Event::Mouse(mouse) => self.produce_mouse_event(mouse, widget),
+            _ => None,
+        }
     }
 }
 
-impl StatefulWidget for FileDependentPanel {
-    type State = State;
+impl State {
+    fn produce_mouse_event(
+        &self,
+        mouse: &crossterm::event::MouseEvent,
+        widget: &FileDependentPanel,
+    ) -> Option<AppEvent> {
+        let target = self.row_target_at(mouse.column, mouse.row);
 
-    fn render(self, area: Rect, buf: &mut Buffer, state: &mut State) {
-        let rect = utils::padding(&area, 1, 1);
+        match mouse.kind {
+            MouseEventKind::ScrollDown if target.is_some() => Some(AppEvent::DownButtonPressed),
+            MouseEventKind::ScrollUp if target.is_some() => Some(AppEvent::UpButtonPressed),
 
-        let text: Vec<Line> = self
-            .files
-            .iter()
-            .enumerate()
-            .flat_map(|(index, file)| {
-                let max_width = rect.width as usize - 2;
-                let prefix = match state.expanded_file {
-                    Some(ref expanded) if expanded == &file.id => "▼",
-                    _ => "▶",
-                };
+            MouseEventKind::Down(MouseButton::Left) => match target? {
+                Cursor::File(index) if mouse.column < self.rows_area.x + MARKER_WIDTH => {
+                    let file = widget.files.get(index)?;
+                    Some(AppEvent::SelectDependentFile(file.clone()))
+                }
 
-                let mut content = utils::compact_file_path(&file.path, max_width - 2);
-                content = format!("{} {:width$}", prefix, content, width = max_width);
+                Cursor::File(index) => Some(AppEvent::FocusDependentFile(index, None)),
+                Cursor::Link(index, link) => Some(AppEvent::FocusDependentFile(index, Some(link))),
+                Cursor::Header(group) => Some(AppEvent::ToggleDependentGroup(
+                    RecomplileDependencyReason::ALL[group].clone(),
+                )),
+            },
 
-                let mut lines = vec![];
-                lines.push(Line::from(format!(" {} ", content)));
+            _ => None,
+        }
+    }
+}
 
-                match state.expanded_file {
-                    Some(ref expanded) if expanded == &file.id => {
-                        let mut dependencies_chain =
-                            dependency_chain_text(&file.dependency_chain, area);
-                        lines.append(&mut dependencies_chain);
-                    }
-                    _ => (),
+impl FileDependentPanel {
+    // The line of a dependent, followed by its dependency chain when expanded
+    fn file_lines(
+        &self,
+        index: usize,
+        rect: Rect,
+        area: Rect,
+        state: &State,
+        row_targets: &mut Vec<Cursor>,
+    ) -> Vec<Line<'_>> {
+        let file = &self.files[index];
+        let expanded = state.expanded.contains(&file.id);
+        let max_width = rect.width as usize - 2;
+        let prefix = if expanded { "▼" } else { "▶" };
+
+        // The path of the chain shown when the dependent recompiles through several of them
+        let paths_label = file
+            .paths_label()
+            .map(|label| format!(" {}", label))
+            .unwrap_or_default();
+        let label_width = paths_label.chars().count();
+
+        let file_path =
+            utils::compact_file_path(&file.path, max_width.saturating_sub(2 + label_width));
+        let indices = self
+            .query
+            .match_path(&file.path)
+            .map(|found| found.indices)
+            .unwrap_or_default();
+        let padding = max_width.saturating_sub(file_path.chars().count() + label_width);
+
+        // The marker tells whether the dependent is always recompiled
+        let certainty = if file.reason.is_definite() {
+            theme::current().definite
+        } else {
+            theme::current().indefinite
+        };
+
+        let mut spans = vec![Span::styled(format!(" {} ", prefix), certainty)];
+        spans.extend(utils::highlight_file_path(
+            &file.path,
+            &file_path,
+            &indices,
+            theme::current().search_match,
+        ));
+        spans.push(Span::from(" ".repeat(padding)));
+        spans.push(Span::styled(paths_label, theme::current().hint));
+        spans.push(Span::from(" "));
+
+        let mut lines = vec![];
+        lines.push(Line::from(spans));
+        row_targets.push(Cursor::File(index));
+
+        if expanded {
+            let mut dependencies_chain = dependency_chain_text(&file.dependency_chain, area);
+            lines.append(&mut dependencies_chain);
+
+            // Each expanded item spans 4 lines
+            for link in 0..file.dependency_chain.len() {
+                row_targets.extend([Cursor::Link(index, link); 4]);
+            }
+        }
+
+        if state.selected.file_index() == Some(index) {
+            let to_be_patched: Vec<&mut Line> = match state.selected {
+                Cursor::Link(_, expanded_index) => {
+                    // Each expanded item spans 4 lines
+                    lines
+                        .iter_mut()
+                        .skip(1 + expanded_index * 4)
+                        .take(4)
+                        .collect()
                 }
 
-                if state.selected_file_index.0 == index {
-                    let to_be_patched: Vec<&mut Line> = match state.selected_file_index.1 {
-                        Some(expanded_index) => {
-                            // Each expanded item spans 4 lines
-                            lines
-                                .iter_mut()
-                                .skip(1 + expanded_index * 4)
-                                .take(4)
-                                .collect()
-                        }
+                _ => lines.iter_mut().take(1).collect(),
+            };
 
-                        None => lines.iter_mut().take(1).collect(),
-                    };
+            for line in to_be_patched {
+                line.patch_style(theme::current().selection)
+            }
+        }
+
+        lines
+    }
 
-                    for line in to_be_patched {
-                        line.patch_style(
-                            Style::default()
-                                .bg(Color::Blue)
-                                .add_modifier(Modifier::BOLD),
-                        )
+    fn header_line(
+        &self,
+        reason: &RecomplileDependencyReason,
+        count: usize,
+        rect: Rect,
+        state: &State,
+    ) -> Line<'static> {
+        let prefix = if state.folded_groups.contains(reason) {
+            "▶"
+        } else {
+            "▼"
+        };
+
+        let text = format!(" {} {} ({})", prefix, reason, count);
+        let padding = (rect.width as usize).saturating_sub(text.chars().count());
+        let mut line = Line::from(vec![
+            Span::styled(text, theme::current().title),
+            Span::from(" ".repeat(padding)),
+        ]);
+
+        if state.selected == Cursor::Header(group_index(reason)) {
+            line.patch_style(theme::current().selection);
+        }
+
+        line
+    }
+}
+
+impl StatefulWidget for FileDependentPanel {
+    type State = State;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut State) {
+        state.clamp_selection(&self);
+
+        let rect = utils::padding(&area, 1, 1);
+        let mut row_targets = vec![];
+        let mut text: Vec<Line> = vec![];
+
+        if self.grouped {
+            for (group, indexes) in self.groups() {
+                let reason = &RecomplileDependencyReason::ALL[group];
+                text.push(self.header_line(reason, indexes.len(), rect, state));
+                row_targets.push(Cursor::Header(group));
+
+                if !state.folded_groups.contains(reason) {
+                    for index in indexes {
+                        text.append(&mut self.file_lines(
+                            index,
+                            rect,
+                            area,
+                            state,
+                            &mut row_targets,
+                        ));
                     }
                 }
+            }
+        } else {
+            for index in 0..self.files.len() {
+                text.append(&mut self.file_lines(index, rect, area, state, &mut row_targets));
+            }
+        }
 
-                lines
-            })
-            .collect();
+        state.scroll_offset = scroll_offset(state, &row_targets, rect.height as usize);
+        state.rows_area = rect;
+        state.row_targets = row_targets;
 
-        let paragraph = Paragraph::new(text).style(Style::default().fg(Color::White));
+        let paragraph = Paragraph::new(text)
+            .style(theme::current().text)
+            .scroll((state.scroll_offset as u16, 0));
 
         render_bounding_box(&self.dependency_source, &self.panel_title, area, buf);
         paragraph.render(rect, buf);
     }
 }
 
+// Returns the scroll offset which keeps the rows of the selection in view, moving as little as
+// possible from the previous offset
+fn scroll_offset(state: &State, row_targets: &[Cursor], height: usize) -> usize {
+    let selected = state.selected;
+    let max_offset = row_targets.len().saturating_sub(height);
+    let offset = state.scroll_offset.min(max_offset);
+
+    let first = row_targets.iter().position(|target| *target == selected);
+    let last = row_targets.iter().rposition(|target| *target == selected);
+
+    match (first, last) {
+        (Some(first), _) if first < offset => first,
+        (_, Some(last)) if last >= offset + height => last + 1 - height,
+        _ => offset,
+    }
+}
+
 fn render_bounding_box(
     source_file: &FilePath,
     title: &Option<String>,
@@ -359,14 +684,14 @@ fn render_bounding_box(
     let mut title_line = vec![Span::from(format!("Recompile files ({})", filename))];
 
     if let Some(text) = title {
-        title_line.push(Span::styled(text, Style::default().fg(Color::Cyan)));
+        title_line.push(Span::styled(text, theme::current().title));
     }
 
     Block::default()
         .borders(Borders::ALL)
         .title(Line::from(title_line))
         .border_type(BorderType::Rounded)
-        .border_style(Style::default().fg(Color::White))
+        .border_style(theme::current().border)
         .render(area, buf);
 }
 
@@ -380,17 +705,17 @@ fn dependency_chain_text(chain: &[DependencyLink], area: Rect) -> Vec<Line> {
             let padded_file_path = pad_left(&format!("└─➤ {}", link.sink), padding);
 
             let dependency_type = &link.dependency_type;
-            let dependency_type_color = match dependency_type {
-                DependencyType::Compile => Color::Red,
-                DependencyType::Exports => Color::White,
-                DependencyType::Runtime => Color::White,
+            let dependency_type_style = match dependency_type {
+                DependencyType::Compile => theme::current().compile_link,
+                DependencyType::Exports => Style::default(),
+                DependencyType::Runtime => Style::default(),
             };
 
             [
                 Line::from(pad_left("│", padding)),
                 Line::from(vec![
                     Span::from(pad_left("│", padding)),
-                    Span::from(format!(" ({})", dependency_type)).fg(dependency_type_color),
+                    Span::styled(format!(" ({})", dependency_type), dependency_type_style),
                 ]),
                 Line::from(pad_left("│", padding)),
                 Line::from(padded_file_path),
@@ -432,7 +757,9 @@ mod handle_event_tests {
         FileDependentPanel::new(
             String::from("source"),
             recompile_dependencies(&["one", "two", "three"]),
+            Query::default(),
             None,
+            false,
         )
     }
 
@@ -444,6 +771,7 @@ mod handle_event_tests {
                 path: f.to_string(),
                 reason: RecomplileDependencyReason::Compile,
                 dependency_chain: vec![],
+                paths: Default::default(),
             })
             .collect()
     }
@@ -472,13 +800,19 @@ mod handle_event_tests {
         rx.try_iter().collect()
     }
 
+    // Expanding a dependent also gets its causes in the cause panel
+    fn expand(state: &mut State, id: &str) {
+        state.expanded.insert(id.to_string());
+        state.causes_dependent = Some(id.to_string());
+    }
+
     mod up_button {
         use super::*;
 
         #[test]
         fn up_button_no_expand() {
             let mut state = State::new();
-            state.selected_file_index = (1, None);
+            state.selected = Cursor::File(1);
 
             let (tx, rx) = mpsc::channel::<AppEvent>();
 
@@ -488,7 +822,7 @@ mod handle_event_tests {
                 &mut noop_adapter(),
                 tx,
             );
-            assert_eq!(state.selected_file_index, (0, None));
+            assert_eq!(state.selected, Cursor::File(0));
             assert_eq!(collect_events(rx).len(), 0);
         }
 
@@ -496,15 +830,21 @@ mod handle_event_tests {
         fn up_button_with_expand() {
             let mut files = recompile_dependencies(&["one", "two", "three"]);
             files[1].dependency_chain = dependency_chain();
-            let widget = FileDependentPanel::new(String::from("source"), files, None);
+            let widget = FileDependentPanel::new(
+                String::from("source"),
+                files,
+                Query::default(),
+                None,
+                false,
+            );
 
             let mut state = State::new();
-            state.expanded_file = Some(String::from("two"));
-            state.selected_file_index = (1, Some(1));
+            expand(&mut state, "two");
+            state.selected = Cursor::Link(1, 1);
 
             let (tx, rx) = mpsc::channel::<AppEvent>();
             state.handle_event(&AppEvent::UpButtonPressed, &widget, &mut noop_adapter(), tx);
-            assert_eq!(state.selected_file_index, (1, Some(0)));
+            assert_eq!(state.selected, Cursor::Link(1, 0));
 
             let events = collect_events(rx);
             assert_eq!(events.len(), 2);
@@ -521,15 +861,21 @@ mod handle_event_tests {
         fn up_button_out_of_expand_list() {
             let mut files = recompile_dependencies(&["one", "two", "three"]);
             files[1].dependency_chain = dependency_chain();
-            let widget = FileDependentPanel::new(String::from("source"), files, None);
+            let widget = FileDependentPanel::new(
+                String::from("source"),
+                files,
+                Query::default(),
+                None,
+                false,
+            );
 
             let mut state = State::new();
-            state.expanded_file = Some(String::from("two"));
-            state.selected_file_index = (1, Some(0));
+            expand(&mut state, "two");
+            state.selected = Cursor::Link(1, 0);
 
             let (tx, rx) = mpsc::channel::<AppEvent>();
             state.handle_event(&AppEvent::UpButtonPressed, &widget, &mut noop_adapter(), tx);
-            assert_eq!(state.selected_file_index, (1, None));
+            assert_eq!(state.selected, Cursor::File(1));
             let events = collect_events(rx);
             assert_eq!(events.len(), 1);
             if let AppEvent::StopViewDependentFile(ref dependency_link) = events[0] {
@@ -541,15 +887,21 @@ mod handle_event_tests {
         fn up_button_into_expand_list() {
             let mut files = recompile_dependencies(&["one", "two", "three"]);
             files[1].dependency_chain = dependency_chain();
-            let widget = FileDependentPanel::new(String::from("source"), files, None);
+            let widget = FileDependentPanel::new(
+                String::from("source"),
+                files,
+                Query::default(),
+                None,
+                false,
+            );
 
             let mut state = State::new();
-            state.expanded_file = Some(String::from("two"));
-            state.selected_file_index = (2, None);
+            expand(&mut state, "two");
+            state.selected = Cursor::File(2);
 
             let (tx, rx) = mpsc::channel::<AppEvent>();
             state.handle_event(&AppEvent::UpButtonPressed, &widget, &mut noop_adapter(), tx);
-            assert_eq!(state.selected_file_index, (1, Some(2)));
+            assert_eq!(state.selected, Cursor::Link(1, 2));
             let events = collect_events(rx);
             assert_eq!(events.len(), 1);
             if let AppEvent::ViewDependentFile(ref dependency_link) = events[0] {
@@ -560,7 +912,7 @@ mod handle_event_tests {
         #[test]
         fn up_button_limit_no_expand() {
             let mut state = State::new();
-            state.selected_file_index = (0, None);
+            state.selected = Cursor::File(0);
 
             let (tx, rx) = mpsc::channel::<AppEvent>();
             state.handle_event(
@@ -569,7 +921,7 @@ mod handle_event_tests {
                 &mut noop_adapter(),
                 tx,
             );
-            assert_eq!(state.selected_file_index, (0, None));
+            assert_eq!(state.selected, Cursor::File(0));
             assert_eq!(collect_events(rx).len(), 0);
         }
     }
@@ -580,7 +932,7 @@ mod handle_event_tests {
         #[test]
         fn down_button_no_expand() {
             let mut state = State::new();
-            state.selected_file_index = (1, None);
+            state.selected = Cursor::File(1);
 
             let (tx, rx) = mpsc::channel::<AppEvent>();
             state.handle_event(
@@ -589,14 +941,14 @@ mod handle_event_tests {
                 &mut noop_adapter(),
                 tx,
             );
-            assert_eq!(state.selected_file_index, (2, None));
+            assert_eq!(state.selected, Cursor::File(2));
             assert_eq!(collect_events(rx).len(), 0);
         }
 
         #[test]
         fn down_button_limit_no_expand() {
             let mut state = State::new();
-            state.selected_file_index = (2, None);
+            state.selected = Cursor::File(2);
 
             let (tx, rx) = mpsc::channel::<AppEvent>();
             state.handle_event(
@@ -605,7 +957,7 @@ mod handle_event_tests {
                 &mut noop_adapter(),
                 tx,
             );
-            assert_eq!(state.selected_file_index, (2, None));
+            assert_eq!(state.selected, Cursor::File(2));
             assert_eq!(collect_events(rx).len(), 0);
         }
 
@@ -613,11 +965,17 @@ mod handle_event_tests {
         fn down_button_with_expand_list() {
             let mut files = recompile_dependencies(&["one", "two", "three"]);
             files[1].dependency_chain = dependency_chain();
-            let widget = FileDependentPanel::new(String::from("source"), files, None);
+            let widget = FileDependentPanel::new(
+                String::from("source"),
+                files,
+                Query::default(),
+                None,
+                false,
+            );
 
             let mut state = State::new();
-            state.expanded_file = Some(String::from("two"));
-            state.selected_file_index = (1, Some(1));
+            expand(&mut state, "two");
+            state.selected = Cursor::Link(1, 1);
 
             let (tx, rx) = mpsc::channel::<AppEvent>();
             state.handle_event(
@@ -626,7 +984,7 @@ mod handle_event_tests {
                 &mut noop_adapter(),
                 tx,
             );
-            assert_eq!(state.selected_file_index, (1, Some(2)));
+            assert_eq!(state.selected, Cursor::Link(1, 2));
 
             let events = collect_events(rx);
             assert_eq!(events.len(), 2);
@@ -644,11 +1002,17 @@ mod handle_event_tests {
         fn down_button_out_expand_list() {
             let mut files = recompile_dependencies(&["one", "two", "three"]);
             files[1].dependency_chain = dependency_chain();
-            let widget = FileDependentPanel::new(String::from("source"), files, None);
+            let widget = FileDependentPanel::new(
+                String::from("source"),
+                files,
+                Query::default(),
+                None,
+                false,
+            );
 
             let mut state = State::new();
-            state.expanded_file = Some(String::from("two"));
-            state.selected_file_index = (1, Some(2));
+            expand(&mut state, "two");
+            state.selected = Cursor::Link(1, 2);
 
             let (tx, rx) = mpsc::channel::<AppEvent>();
             state.handle_event(
@@ -657,7 +1021,7 @@ mod handle_event_tests {
                 &mut noop_adapter(),
                 tx,
             );
-            assert_eq!(state.selected_file_index, (2, None));
+            assert_eq!(state.selected, Cursor::File(2));
 
             let events = collect_events(rx);
             assert_eq!(events.len(), 1);
@@ -671,11 +1035,17 @@ mod handle_event_tests {
         fn down_button_into_expand_list() {
             let mut files = recompile_dependencies(&["one", "two", "three"]);
             files[1].dependency_chain = dependency_chain();
-            let widget = FileDependentPanel::new(String::from("source"), files, None);
+            let widget = FileDependentPanel::new(
+                String::from("source"),
+                files,
+                Query::default(),
+                None,
+                false,
+            );
 
             let mut state = State::new();
-            state.expanded_file = Some(String::from("two"));
-            state.selected_file_index = (1, None);
+            expand(&mut state, "two");
+            state.selected = Cursor::File(1);
 
             let (tx, rx) = mpsc::channel::<AppEvent>();
             state.handle_event(
@@ -684,7 +1054,7 @@ mod handle_event_tests {
                 &mut noop_adapter(),
                 tx,
             );
-            assert_eq!(state.selected_file_index, (1, Some(0)));
+            assert_eq!(state.selected, Cursor::Link(1, 0));
 
             let events = collect_events(rx);
             assert_eq!(events.len(), 1);
@@ -701,60 +1071,644 @@ mod handle_event_tests {
         #[test]
         fn expand_file_from_initial() {
             let recompile_dependencies = recompile_dependencies(&["one", "two", "three"]);
-            let widget =
-                FileDependentPanel::new(String::from("source"), recompile_dependencies.clone(), None);
+            let widget = FileDependentPanel::new(
+                String::from("source"),
+                recompile_dependencies.clone(),
+                Query::default(),
+                None,
+                false,
+            );
 
             let mut state = State::new();
             let event = AppEvent::SelectDependentFile(recompile_dependencies[0].clone());
             let (tx, _) = mpsc::channel::<AppEvent>();
             state.handle_event(&event, &widget, &mut noop_adapter(), tx);
-            assert_eq!(state.expanded_file, Some(String::from("one")));
+            assert_eq!(state.expanded, HashSet::from([String::from("one")]));
         }
 
         #[test]
         fn expand_file_when_already_expanded() {
             let recompile_dependencies = recompile_dependencies(&["one", "two", "three"]);
-            let widget =
-                FileDependentPanel::new(String::from("source"), recompile_dependencies.clone(), None);
+            let widget = FileDependentPanel::new(
+                String::from("source"),
+                recompile_dependencies.clone(),
+                Query::default(),
+                None,
+                false,
+            );
 
             let mut state = State::new();
-            state.expanded_file = Some(String::from("two"));
+            expand(&mut state, "two");
 
             let (tx, _) = mpsc::channel::<AppEvent>();
             let event = AppEvent::SelectDependentFile(recompile_dependencies[0].clone());
             state.handle_event(&event, &widget, &mut noop_adapter(), tx);
-            assert_eq!(state.expanded_file, Some(String::from("one")));
+            assert_eq!(
+                state.expanded,
+                HashSet::from([String::from("one"), String::from("two")])
+            );
         }
 
         #[test]
         fn collapse_file() {
             let recompile_dependencies = recompile_dependencies(&["one", "two", "three"]);
-            let widget =
-                FileDependentPanel::new(String::from("source"), recompile_dependencies.clone(), None);
+            let widget = FileDependentPanel::new(
+                String::from("source"),
+                recompile_dependencies.clone(),
+                Query::default(),
+                None,
+                false,
+            );
 
             let mut state = State::new();
-            state.expanded_file = Some(String::from("two"));
+            expand(&mut state, "two");
 
             let (tx, _) = mpsc::channel::<AppEvent>();
             let event = AppEvent::SelectDependentFile(recompile_dependencies[1].clone());
             state.handle_event(&event, &widget, &mut noop_adapter(), tx);
-            assert_eq!(state.expanded_file, None);
+            assert!(state.expanded.is_empty());
         }
 
         #[test]
         fn cancel_reset_state() {
             let recompile_dependencies = recompile_dependencies(&["one", "two", "three"]);
-            let widget = FileDependentPanel::new(String::from("source"), recompile_dependencies, None);
+            let widget = FileDependentPanel::new(
+                String::from("source"),
+                recompile_dependencies,
+                Query::default(),
+                None,
+                false,
+            );
 
             let mut state = State::new();
-            state.selected_file_index = (2, None);
+            state.selected = Cursor::File(2);
 
             let (tx, _) = mpsc::channel::<AppEvent>();
             state.handle_event(&AppEvent::Cancel, &widget, &mut noop_adapter(), tx);
-            assert_eq!(state.expanded_file, None);
-            assert_eq!(state.selected_file_index, (0, None));
-            assert_eq!(state.selected_file_index, (0, None));
-            assert_eq!(state.expanded_file, None);
+            assert!(state.expanded.is_empty());
+            assert_eq!(state.selected, Cursor::File(0));
+        }
+    }
+
+    mod focus_file {
+        use super::*;
+
+        fn expanded_widget() -> FileDependentPanel {
+            let mut recompile_dependencies = recompile_dependencies(&["one", "two", "three"]);
+            recompile_dependencies[1].dependency_chain = dependency_chain();
+
+            FileDependentPanel::new(
+                String::from("source"),
+                recompile_dependencies,
+                Query::default(),
+                None,
+                false,
+            )
+        }
+
+        #[test]
+        fn focus_file() {
+            let mut state = State::new();
+
+            let (tx, rx) = mpsc::channel::<AppEvent>();
+            let event = AppEvent::FocusDependentFile(2, None);
+            state.handle_event(&event, &widget(), &mut noop_adapter(), tx);
+            assert_eq!(state.selected, Cursor::File(2));
+            assert!(collect_events(rx).is_empty());
+        }
+
+        #[test]
+        fn focus_link() {
+            let mut state = State::new();
+            expand(&mut state, "two");
+            state.selected = Cursor::Link(1, 0);
+
+            let (tx, rx) = mpsc::channel::<AppEvent>();
+            let event = AppEvent::FocusDependentFile(1, Some(2));
+            state.handle_event(&event, &expanded_widget(), &mut noop_adapter(), tx);
+            assert_eq!(state.selected, Cursor::Link(1, 2));
+
+            let events = collect_events(rx);
+            assert!(matches!(
+                &events[..],
+                [AppEvent::StopViewDependentFile(stop), AppEvent::ViewDependentFile(view)]
+                    if stop.sink == "two.one" && view.sink == "two.three"
+            ));
+        }
+
+        #[test]
+        fn focus_link_of_collapsed_file() {
+            let mut state = State::new();
+
+            let (tx, _) = mpsc::channel::<AppEvent>();
+            let event = AppEvent::FocusDependentFile(1, Some(0));
+            state.handle_event(&event, &expanded_widget(), &mut noop_adapter(), tx);
+            assert_eq!(state.selected, Cursor::File(0));
+        }
+
+        #[test]
+        fn select_other_file_moves_focus() {
+            let widget = expanded_widget();
+            let mut state = State::new();
+            expand(&mut state, "two");
+            state.selected = Cursor::Link(1, 1);
+
+            let (tx, rx) = mpsc::channel::<AppEvent>();
+            let event = AppEvent::SelectDependentFile(widget.files[2].clone());
+            state.handle_event(&event, &widget, &mut noop_adapter(), tx);
+            assert_eq!(state.selected, Cursor::File(2));
+            assert!(state.expanded.contains("two") && state.expanded.contains("three"));
+
+            let events = collect_events(rx);
+            assert!(matches!(
+                &events[..],
+                [AppEvent::StopViewDependentFile(stop)] if stop.sink == "two.two"
+            ));
+        }
+    }
+
+    mod several_expanded {
+        use super::*;
+
+        // "one" and "two" have the same chain
+        fn widget() -> FileDependentPanel {
+            let mut files = recompile_dependencies(&["one", "two", "three"]);
+            files[0].dependency_chain = dependency_chain()[..2].to_vec();
+            files[1].dependency_chain = dependency_chain()[..2].to_vec();
+
+            FileDependentPanel::new(String::from("source"), files, Query::default(), None, false)
+        }
+
+        fn dispatch(
+            state: &mut State,
+            widget: &FileDependentPanel,
+            events: &[AppEvent],
+        ) -> Vec<AppEvent> {
+            let (tx, rx) = mpsc::channel::<AppEvent>();
+            for event in events {
+                state.handle_event(event, widget, &mut noop_adapter(), tx.clone());
+            }
+
+            collect_events(rx)
+        }
+
+        #[test]
+        fn move_through_every_chain() {
+            let widget = widget();
+            let mut state = State::new();
+            expand(&mut state, "one");
+            expand(&mut state, "two");
+
+            let mut cursors = vec![state.selected];
+            for _ in 0..6 {
+                dispatch(&mut state, &widget, &[AppEvent::DownButtonPressed]);
+                cursors.push(state.selected);
+            }
+
+            assert_eq!(
+                cursors,
+                [
+                    Cursor::File(0),
+                    Cursor::Link(0, 0),
+                    Cursor::Link(0, 1),
+                    Cursor::File(1),
+                    Cursor::Link(1, 0),
+                    Cursor::Link(1, 1),
+                    Cursor::File(2),
+                ]
+            );
+        }
+
+        #[test]
+        fn chain_of_other_dependent_resets_causes() {
+            let widget = widget();
+            let mut state = State::new();
+            expand(&mut state, "two");
+            expand(&mut state, "one");
+            state.selected = Cursor::File(1);
+
+            let events = dispatch(&mut state, &widget, &[AppEvent::DownButtonPressed]);
+            assert!(matches!(
+                &events[..],
+                [AppEvent::ResetDependencyCauses(Some((_, dependent)), Some(link))]
+                    if dependent.id == "two" && link.sink == "two.one"
+            ));
+
+            // The causes of "two" are shown now, moving within its chain only views the links
+            let events = dispatch(&mut state, &widget, &[AppEvent::DownButtonPressed]);
+            assert!(matches!(
+                &events[..],
+                [AppEvent::StopViewDependentFile(_), AppEvent::ViewDependentFile(link)]
+                    if link.sink == "two.two"
+            ));
+        }
+
+        #[test]
+        fn expand_and_collapse_all() {
+            let widget = widget();
+            let mut state = State::new();
+
+            dispatch(&mut state, &widget, &[AppEvent::ExpandAllDependents]);
+            assert_eq!(state.expanded.len(), 3);
+
+            state.selected = Cursor::Link(1, 1);
+            let events = dispatch(&mut state, &widget, &[AppEvent::CollapseAllDependents]);
+            assert!(state.expanded.is_empty());
+            assert_eq!(state.selected, Cursor::File(1));
+            assert!(matches!(&events[..], [AppEvent::StopViewDependentFile(_)]));
+        }
+    }
+
+    mod clamp_selection {
+        use super::*;
+
+        #[test]
+        fn selection_past_filtered_list() {
+            let mut state = State::new();
+            expand(&mut state, "three");
+            state.selected = Cursor::Link(2, 0);
+
+            let widget = FileDependentPanel::new(
+                String::from("source"),
+                recompile_dependencies(&["one", "two"]),
+                Query::default(),
+                None,
+                false,
+            );
+
+            let (tx, rx) = mpsc::channel::<AppEvent>();
+            state.handle_event(&AppEvent::UpButtonPressed, &widget, &mut noop_adapter(), tx);
+            assert_eq!(state.selected, Cursor::File(0));
+            assert!(collect_events(rx).is_empty());
+        }
+
+        #[test]
+        fn empty_filtered_list() {
+            let mut state = State::new();
+            expand(&mut state, "two");
+            state.selected = Cursor::File(1);
+
+            let widget = FileDependentPanel::new(
+                String::from("source"),
+                vec![],
+                Query::default(),
+                None,
+                false,
+            );
+
+            let (tx, _) = mpsc::channel::<AppEvent>();
+            state.handle_event(
+                &AppEvent::DownButtonPressed,
+                &widget,
+                &mut noop_adapter(),
+                tx,
+            );
+            assert_eq!(state.selected, Cursor::File(0));
+        }
+    }
+
+    mod mouse {
+        use super::*;
+        use crossterm::event::{Event, KeyModifiers, MouseEvent};
+
+        fn mouse_event(kind: MouseEventKind, column: u16, row: u16) -> Event {
+            Event::Mouse(MouseEvent {
+                kind,
+                column,
+                row,
+                modifiers: KeyModifiers::NONE,
+            })
+        }
+
+        fn click(column: u16, row: u16) -> Event {
+            mouse_event(MouseEventKind::Down(MouseButton::Left), column, row)
+        }
+
+        fn rendered_state(widget: &FileDependentPanel, expanded_file: Option<&str>) -> State {
+            let mut state = State::new();
+            state.expanded.extend(expanded_file.map(String::from));
+
+            let area = Rect::new(0, 0, 40, 8);
+            let mut buf = Buffer::empty(area);
+            widget.clone().render(area, &mut buf, &mut state);
+
+            state
+        }
+
+        #[test]
+        fn click_marker_toggles_file() {
+            let widget = widget();
+            let mut state = rendered_state(&widget, None);
+
+            // Rows start at y = 1 because of the border
+            match state.produce_event(&click(2, 2), &widget) {
+                Some(AppEvent::SelectDependentFile(file)) => assert_eq!(file.id, "two"),
+                _ => panic!("Expected SelectDependentFile event"),
+            }
+        }
+
+        #[test]
+        fn click_file_row_focuses_file() {
+            let widget = widget();
+            let mut state = rendered_state(&widget, None);
+
+            assert!(matches!(
+                state.produce_event(&click(10, 3), &widget),
+                Some(AppEvent::FocusDependentFile(2, None))
+            ));
+            assert!(state.produce_event(&click(10, 6), &widget).is_none());
+        }
+
+        #[test]
+        fn click_link_focuses_link() {
+            let mut recompile_dependencies = recompile_dependencies(&["one", "two", "three"]);
+            recompile_dependencies[0].dependency_chain = dependency_chain();
+            let widget = FileDependentPanel::new(
+                String::from("source"),
+                recompile_dependencies,
+                Query::default(),
+                None,
+                false,
+            );
+            let mut state = rendered_state(&widget, Some("one"));
+
+            // The file row is followed by 4 rows for each link
+            assert!(matches!(
+                state.produce_event(&click(10, 2), &widget),
+                Some(AppEvent::FocusDependentFile(0, Some(0)))
+            ));
+            assert!(matches!(
+                state.produce_event(&click(10, 6), &widget),
+                Some(AppEvent::FocusDependentFile(0, Some(1)))
+            ));
+        }
+
+        #[test]
+        fn click_with_scroll_offset() {
+            let mut recompile_dependencies = recompile_dependencies(&["one", "two", "three"]);
+            recompile_dependencies[0].dependency_chain = dependency_chain();
+            let widget = FileDependentPanel::new(
+                String::from("source"),
+                recompile_dependencies,
+                Query::default(),
+                None,
+                false,
+            );
+
+            let mut state = State::new();
+            expand(&mut state, "one");
+            state.selected = Cursor::File(2);
+
+            let area = Rect::new(0, 0, 40, 8);
+            let mut buf = Buffer::empty(area);
+            widget.clone().render(area, &mut buf, &mut state);
+
+            // 15 rows in a 6 rows tall list, scrolled to keep the last row in view
+            assert_eq!(state.scroll_offset, 9);
+            assert!(matches!(
+                state.produce_event(&click(10, 6), &widget),
+                Some(AppEvent::FocusDependentFile(2, None))
+            ));
+        }
+
+        #[test]
+        fn scroll_wheel() {
+            let widget = widget();
+            let mut state = rendered_state(&widget, None);
+
+            assert!(matches!(
+                state.produce_event(&mouse_event(MouseEventKind::ScrollDown, 5, 2), &widget),
+                Some(AppEvent::DownButtonPressed)
+            ));
+            assert!(matches!(
+                state.produce_event(&mouse_event(MouseEventKind::ScrollUp, 5, 2), &widget),
+                Some(AppEvent::UpButtonPressed)
+            ));
+            assert!(state
+                .produce_event(&mouse_event(MouseEventKind::ScrollUp, 50, 2), &widget)
+                .is_none());
+        }
+    }
+
+    mod pivot {
+        use super::*;
+        use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+        fn pivot_key() -> Event {
+            Event::Key(KeyEvent::new(KeyCode::Char('p'), KeyModifiers::NONE))
+        }
+
+        #[test]
+        fn pivot_to_dependent() {
+            let mut state = State::new();
+            state.selected = Cursor::File(1);
+
+            match state.produce_event(&pivot_key(), &widget()) {
+                Some(AppEvent::PivotDependencySource(path)) => assert_eq!(path, "two"),
+                _ => panic!("Expected PivotDependencySource event"),
+            }
+        }
+
+        #[test]
+        fn pivot_to_intermediate_file() {
+            let mut recompile_dependencies = recompile_dependencies(&["one", "two", "three"]);
+            recompile_dependencies[1].dependency_chain = dependency_chain();
+            let widget = FileDependentPanel::new(
+                String::from("source"),
+                recompile_dependencies,
+                Query::default(),
+                None,
+                false,
+            );
+
+            let mut state = State::new();
+            expand(&mut state, "two");
+            state.selected = Cursor::Link(1, 1);
+
+            match state.produce_event(&pivot_key(), &widget) {
+                Some(AppEvent::PivotDependencySource(path)) => assert_eq!(path, "two.two"),
+                _ => panic!("Expected PivotDependencySource event"),
+            }
+        }
+    }
+
+    mod cycle_chain {
+        use super::*;
+        use crate::DependencyPaths;
+        use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+        // "two" recompiles through the whole chain, or through its last link only
+        fn widget() -> FileDependentPanel {
+            let mut files = recompile_dependencies(&["one", "two", "three"]);
+            let chains = vec![dependency_chain(), dependency_chain()[2..].to_vec()];
+            files[1].dependency_chain = chains[0].clone();
+            files[1].paths = DependencyPaths {
+                dependency_chains: chains,
+                path_count: 2,
+                ..Default::default()
+            };
+
+            FileDependentPanel::new(String::from("source"), files, Query::default(), None, false)
+        }
+
+        #[test]
+        fn keys_cycle_the_focused_dependent() {
+            let mut state = State::new();
+            state.selected = Cursor::Link(1, 2);
+
+            let key = Event::Key(KeyEvent::new(KeyCode::Char('N'), KeyModifiers::NONE));
+            assert!(matches!(
+                state.produce_event(&key, &widget()),
+                Some(AppEvent::CycleDependencyChain(id, false)) if id == "two"
+            ));
+        }
+
+        #[test]
+        fn cursor_leaves_the_old_chain() {
+            let widget = widget();
+            let mut state = State::new();
+            expand(&mut state, "two");
+            state.selected = Cursor::Link(1, 2);
+
+            let (tx, rx) = mpsc::channel::<AppEvent>();
+            let event = AppEvent::CycleDependencyChain(String::from("two"), true);
+            state.handle_event(&event, &widget, &mut noop_adapter(), tx);
+
+            assert_eq!(state.selected, Cursor::File(1));
+            assert!(matches!(
+                &collect_events(rx)[..],
+                [
+                    AppEvent::StopViewDependentFile(_),
+                    AppEvent::ResetDependencyCauses(Some((_, dependent)), None),
+                ] if dependent.chain_index() == 1 && dependent.dependency_chain.len() == 1
+            ));
+        }
+
+        #[test]
+        fn single_path_is_left_alone() {
+            let mut state = State::new();
+            expand(&mut state, "one");
+
+            let (tx, rx) = mpsc::channel::<AppEvent>();
+            let event = AppEvent::CycleDependencyChain(String::from("one"), true);
+            state.handle_event(&event, &widget(), &mut noop_adapter(), tx);
+
+            assert!(collect_events(rx).is_empty());
+        }
+    }
+
+    mod grouped {
+        use super::*;
+        use crossterm::event::{Event, KeyCode, KeyEvent};
+
+        // Sorted into the groups, "one" and "three" are compile dependents, "two" is the
+        // exports one
+        fn grouped_widget() -> FileDependentPanel {
+            let mut files = recompile_dependencies(&["one", "two", "three"]);
+            files[1].reason = RecomplileDependencyReason::Exports;
+            files[1].dependency_chain = dependency_chain();
+
+            FileDependentPanel::new(String::from("source"), files, Query::default(), None, true)
+        }
+
+        fn press(state: &mut State, event: AppEvent, widget: &FileDependentPanel) -> Vec<AppEvent> {
+            let (tx, rx) = mpsc::channel::<AppEvent>();
+            state.handle_event(&event, widget, &mut noop_adapter(), tx);
+            collect_events(rx)
+        }
+
+        #[test]
+        fn files_are_sorted_into_groups() {
+            let widget = grouped_widget();
+            let ids: Vec<&str> = widget.files.iter().map(|file| file.id.as_str()).collect();
+
+            assert_eq!(ids, ["one", "three", "two"]);
+        }
+
+        #[test]
+        fn headers_are_selectable_rows() {
+            let widget = grouped_widget();
+            let mut state = State::new();
+
+            press(&mut state, AppEvent::UpButtonPressed, &widget);
+            assert_eq!(state.selected, Cursor::Header(0));
+
+            press(&mut state, AppEvent::DownButtonPressed, &widget);
+            press(&mut state, AppEvent::DownButtonPressed, &widget);
+            assert_eq!(state.selected, Cursor::File(1));
+
+            press(&mut state, AppEvent::DownButtonPressed, &widget);
+            assert_eq!(state.selected, Cursor::Header(1));
+
+            press(&mut state, AppEvent::DownButtonPressed, &widget);
+            assert_eq!(state.selected, Cursor::File(2));
+        }
+
+        #[test]
+        fn enter_on_header_folds_group() {
+            let widget = grouped_widget();
+            let mut state = State::new();
+            press(&mut state, AppEvent::UpButtonPressed, &widget);
+
+            let enter = Event::Key(KeyEvent::from(KeyCode::Enter));
+            let event = state.produce_event(&enter, &widget).unwrap();
+            assert!(matches!(
+                event,
+                AppEvent::ToggleDependentGroup(RecomplileDependencyReason::Compile)
+            ));
+
+            press(&mut state, event, &widget);
+            assert_eq!(
+                state.folded_groups,
+                vec![RecomplileDependencyReason::Compile]
+            );
+
+            // The files of the folded group are skipped
+            press(&mut state, AppEvent::DownButtonPressed, &widget);
+            assert_eq!(state.selected, Cursor::Header(1));
+
+            press(&mut state, AppEvent::UpButtonPressed, &widget);
+            let event = AppEvent::ToggleDependentGroup(RecomplileDependencyReason::Compile);
+            press(&mut state, event, &widget);
+            assert!(state.folded_groups.is_empty());
+        }
+
+        #[test]
+        fn folding_stops_viewing_link() {
+            let widget = grouped_widget();
+            let mut state = State::new();
+            expand(&mut state, "two");
+            state.selected = Cursor::Link(2, 0);
+
+            let event = AppEvent::ToggleDependentGroup(RecomplileDependencyReason::Exports);
+            let events = press(&mut state, event, &widget);
+
+            assert!(matches!(events[..], [AppEvent::StopViewDependentFile(_)]));
+            assert_eq!(state.selected, Cursor::Header(1));
+        }
+
+        #[test]
+        fn click_header_toggles_group() {
+            let widget = grouped_widget();
+            let mut state = State::new();
+
+            let area = Rect::new(0, 0, 40, 8);
+            let mut buf = Buffer::empty(area);
+            widget.clone().render(area, &mut buf, &mut state);
+
+            let click = Event::Mouse(crossterm::event::MouseEvent {
+                kind: MouseEventKind::Down(MouseButton::Left),
+                column: 10,
+                row: 4,
+                modifiers: crossterm::event::KeyModifiers::NONE,
+            });
+
+            assert!(matches!(
+                state.produce_event(&click, &widget),
+                Some(AppEvent::ToggleDependentGroup(
+                    RecomplileDependencyReason::Exports
+                ))
+            ));
         }
     }
 }
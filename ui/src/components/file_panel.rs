@@ -1,39 +1,92 @@
+use crossterm::event::{MouseButton, MouseEventKind};
 use ratatui::buffer::Buffer;
 use ratatui::layout::{Alignment, Rect};
-use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::style::{Modifier, Stylize};
 use ratatui::text::{Line, Span};
 use ratatui::widgets::{
     Block, BorderType, Borders, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState,
     StatefulWidget, Widget,
 };
+use std::rc::Rc;
 use std::sync::mpsc;
+use std::time::{Duration, Instant};
 
 use crate::adapter::ServerAdapter;
 use crate::app_event::AppEvent;
 use crate::components::loading_icon::LoadingIcon;
+use crate::keymap::{self, Action, Scope};
+use crate::query::Query;
+use crate::reason_filter::ReasonFilter;
+use crate::theme;
 use crate::utils;
-use crate::{FileEntry, HandleEvent, ProduceEvent};
+use crate::{FileEntry, FilePath, HandleEvent, ProduceEvent, RecomplileDependency};
 
 #[derive(Clone)]
 pub struct FilePanel {
-    files: Option<Vec<FileEntry>>,
+    // Shared with the list cached between frames
+    files: Option<Rc<[FileEntry]>>,
+    pinned_files: Vec<FilePath>,
+    // Marked as changed for the what if report
+    marked_files: Vec<FilePath>,
+    query: Query,
+    // The counts only include the dependents shown by the filter
+    counts_filter: Option<ReasonFilter>,
     panel_title: Option<String>,
 }
 
 impl FilePanel {
-    pub fn new(files: Option<Vec<FileEntry>>, panel_title: Option<String>) -> Self {
-        Self { files, panel_title }
+    pub fn new(
+        files: Option<Rc<[FileEntry]>>,
+        pinned_files: Vec<FilePath>,
+        marked_files: Vec<FilePath>,
+        query: Query,
+        counts_filter: Option<ReasonFilter>,
+        panel_title: Option<String>,
+    ) -> Self {
+        Self {
+            files,
+            pinned_files,
+            marked_files,
+            query,
+            counts_filter,
+            panel_title,
+        }
     }
 }
 
+// Two clicks on the same row within this interval make a double click
+const DOUBLE_CLICK_INTERVAL: Duration = Duration::from_millis(500);
+
 pub struct State {
     pub selected_file_index: usize,
+    // The area of the files list and its scroll offset in the last render, used to map mouse
+    // positions back to rows
+    files_area: Rect,
+    scroll_offset: usize,
+    last_click: Option<(Instant, usize)>,
 }
 
 impl State {
     pub fn new() -> Self {
         Self {
             selected_file_index: 0,
+            files_area: Rect::default(),
+            scroll_offset: 0,
+            last_click: None,
+        }
+    }
+
+    // Keep the selection within the list, which shrinks as a search is typed
+    fn clamp_selection(&mut self, files_count: usize) {
+        self.selected_file_index = self.selected_file_index.min(files_count.saturating_sub(1));
+    }
+
+    // Returns the index of the file rendered at the given terminal position
+    fn file_index_at(&self, column: u16, row: u16) -> Option<usize> {
+        if utils::contains(&self.files_area, column, row) {
+            Some((row - self.files_area.y) as usize + self.scroll_offset)
+        } else {
+            None
         }
     }
 }
@@ -49,10 +102,25 @@ impl HandleEvent for State {
         _dispatcher: mpsc::Sender<AppEvent>,
     ) {
         if let Some(ref files) = widget.files {
+            // The list is filtered while the search is typed, the best matches come first. The top
+            // of the list is also selected when it gets sorted differently
+            let order_changed = match event {
+                AppEvent::SearchInput(_) | AppEvent::SearchInputDelete => true,
+                AppEvent::SearchInputEdit(edit) => edit.changes_text(),
+                AppEvent::CycleFileSort | AppEvent::ReverseFileSort => true,
+                _ => false,
+            };
+
+            if order_changed {
+                self.selected_file_index = 0;
+            }
+
             if files.is_empty() {
                 return;
             }
 
+            self.clamp_selection(files.len());
+
             match event {
                 AppEvent::DownButtonPressed => {
                     if self.selected_file_index < files.len() - 1 {
@@ -66,6 +134,10 @@ impl HandleEvent for State {
                     }
                 }
 
+                AppEvent::FocusFile(index) if *index < files.len() => {
+                    self.selected_file_index = *index;
+                }
+
                 AppEvent::SubmitSearch => self.selected_file_index = 0,
                 _ => (),
             }
@@ -81,25 +153,82 @@ impl ProduceEvent for State {
         terminal_event: &crossterm::event::Event,
         widget: &Self::Widget,
     ) -> Option<AppEvent> {
-        if let crossterm::event::Event::Key(key) = terminal_event {
-            if key.kind == crossterm::event::KeyEventKind::Press {
-                return match key.code {
-                    crossterm::event::KeyCode::Enter => {
-                        if let Some(ref files) = widget.files {
-                            let index = self.selected_file_index;
-
-                            Some(AppEvent::SelectFile(files[index].clone()))
-                        } else {
-                            None
-                        }
+        match terminal_event {
+            crossterm::event::Event::Key(key)
+                if key.kind == crossterm::event::KeyEventKind::Press =>
+            {
+                match keymap::current().action(Scope::FilePanel, key) {
+                    Some(Action::Select) => {
+                        let files = widget.files.as_ref()?;
+                        let file = files.get(self.selected_file_index)?;
+
+                        Some(AppEvent::SelectFile(file.clone()))
                     }
 
+                    Some(Action::Pin) => {
+                        let files = widget.files.as_ref()?;
+                        let file = files.get(self.selected_file_index)?;
+
+                        Some(AppEvent::TogglePin(file.path.clone()))
+                    }
+
+                    Some(Action::Mark) => {
+                        let files = widget.files.as_ref()?;
+                        let file = files.get(self.selected_file_index)?;
+
+                        Some(AppEvent::ToggleMark(file.path.clone()))
+                    }
+
+                    Some(Action::ClearMarks) => Some(AppEvent::ClearMarks),
+
+                    Some(Action::CycleSort) => Some(AppEvent::CycleFileSort),
+                    Some(Action::FilterCounts) => Some(AppEvent::ToggleFilteredCounts),
+                    Some(Action::ReverseSort) => Some(AppEvent::ReverseFileSort),
+
                     _ => None,
-                };
+                }
             }
+
+            crossterm::event::Event::Mouse(mouse) => self.produce_mouse_event(mouse, widget),
+            _ => None,
         }
+    }
+}
 
-        None
+impl State {
+    fn produce_mouse_event(
+        &mut self,
+        mouse: &crossterm::event::MouseEvent,
+        widget: &FilePanel,
+    ) -> Option<AppEvent> {
+        let files = widget.files.as_ref()?;
+        let index = self.file_index_at(mouse.column, mouse.row);
+
+        match mouse.kind {
+            MouseEventKind::ScrollDown if index.is_some() => Some(AppEvent::DownButtonPressed),
+            MouseEventKind::ScrollUp if index.is_some() => Some(AppEvent::UpButtonPressed),
+
+            MouseEventKind::Down(MouseButton::Left) => {
+                let index = index.filter(|index| *index < files.len())?;
+                let now = Instant::now();
+
+                let double_click = matches!(
+                    self.last_click,
+                    Some((time, last_index))
+                        if last_index == index && now - time < DOUBLE_CLICK_INTERVAL
+                );
+
+                if double_click {
+                    self.last_click = None;
+                    Some(AppEvent::SelectFile(files[index].clone()))
+                } else {
+                    self.last_click = Some((now, index));
+                    Some(AppEvent::FocusFile(index))
+                }
+            }
+
+            _ => None,
+        }
     }
 }
 
@@ -111,8 +240,10 @@ impl StatefulWidget for FilePanel {
 
         match self.files {
             Some(ref files) => {
+                state.clamp_selection(files.len());
+
                 let files_rect = utils::padding(&area, 1, 1);
-                render_files_list(files, state, files_rect, buf);
+                render_files_list(files, &self, state, files_rect, buf);
 
                 // We have padding y of 1, hence the -2
                 let overflow = files.len() as u16 > (area.height - 2);
@@ -131,7 +262,7 @@ impl StatefulWidget for FilePanel {
                     LoadingIcon::new().into(),
                     Span::from(" Collecting data"),
                 ]))
-                .style(Style::default().fg(Color::White))
+                .style(theme::current().text)
                 .add_modifier(Modifier::BOLD)
                 .alignment(Alignment::Center);
 
@@ -144,56 +275,116 @@ impl StatefulWidget for FilePanel {
     }
 }
 
-fn render_files_list(files: &[FileEntry], state: &State, area: Rect, buf: &mut Buffer) {
+fn render_files_list(
+    files: &[FileEntry],
+    widget: &FilePanel,
+    state: &mut State,
+    area: Rect,
+    buf: &mut Buffer,
+) {
+    let scroll_offset = if (state.selected_file_index as u16) < area.height {
+        0
+    } else {
+        state.selected_file_index as u16 - area.height + 1
+    };
+
+    state.files_area = area;
+    state.scroll_offset = scroll_offset as usize;
+
+    // Only the visible rows are built, the list can hold tens of thousands of files
     let text: Vec<Line> = files
         .iter()
         .enumerate()
+        .skip(scroll_offset as usize)
+        .take(area.height as usize)
         .map(|(index, file)| {
-            let max_width = area.width as usize - 5;
-            let mut file_path = utils::compact_file_path(&file.path, max_width);
-            file_path = format!("{:width$}", file_path, width = max_width);
-
-            let dependents_count = format!("{: >3}", file.recompile_dependencies.len().to_string());
-
-            let mut line = Line::from(vec![
-                Span::from(" "),
-                Span::from(file_path),
-                Span::styled(dependents_count, Style::default().fg(Color::Yellow)),
-                Span::from(" "),
-            ]);
+            let max_width = (area.width as usize).saturating_sub(10);
+            let file_path = utils::compact_file_path(&file.path, max_width);
+            let indices = widget
+                .query
+                .match_path(&file.path)
+                .map(|found| found.indices)
+                .unwrap_or_default();
+
+            let (definite, indefinite) =
+                certainty_counts(&file.recompile_dependencies, &widget.counts_filter);
+
+            let pin_marker = if widget.pinned_files.contains(&file.path) {
+                Span::styled("*", theme::current().title)
+            } else {
+                Span::from(" ")
+            };
+
+            let mark_marker = if widget.marked_files.contains(&file.path) {
+                Span::styled("+", theme::current().definite)
+            } else {
+                Span::from(" ")
+            };
+
+            let padding = max_width.saturating_sub(file_path.chars().count());
+
+            let mut spans = vec![mark_marker, pin_marker];
+            spans.extend(utils::highlight_file_path(
+                &file.path,
+                &file_path,
+                &indices,
+                theme::current().search_match,
+            ));
+            spans.push(Span::from(" ".repeat(padding)));
+            spans.push(Span::styled(
+                format!("{: >3}", definite),
+                theme::current().definite,
+            ));
+            spans.push(Span::styled(
+                format!(" {: >3}", indefinite),
+                theme::current().indefinite,
+            ));
+            spans.push(Span::from(" "));
+
+            let mut line = Line::from(spans);
 
             if state.selected_file_index == index {
-                line.patch_style(
-                    Style::default()
-                        .bg(Color::Blue)
-                        .add_modifier(Modifier::BOLD),
-                )
+                line.patch_style(theme::current().selection)
             }
 
             line
         })
         .collect();
 
-    let scroll_offset = if (state.selected_file_index as u16) < area.height {
-        0
-    } else {
-        state.selected_file_index as u16 - area.height + 1
-    };
-
-    let paragraph = Paragraph::new(text[scroll_offset as usize..].to_vec())
-        .style(Style::default().fg(Color::White));
+    let paragraph = Paragraph::new(text).style(theme::current().text);
 
     paragraph.render(area, buf);
 }
 
+// The number of dependents which are always recompiled, and of those which may be
+fn certainty_counts(
+    dependents: &[RecomplileDependency],
+    filter: &Option<ReasonFilter>,
+) -> (usize, usize) {
+    dependents
+        .iter()
+        .filter(|dependent| {
+            filter
+                .as_ref()
+                .is_none_or(|filter| filter.is_shown(&dependent.reason))
+        })
+        .fold((0, 0), |(definite, indefinite), dependent| {
+            if dependent.reason.is_definite() {
+                (definite + 1, indefinite)
+            } else {
+                (definite, indefinite + 1)
+            }
+        })
+}
+
 fn render_scroll_bar(content_length: u16, scroll_position: u16, area: Rect, buf: &mut Buffer) {
     let scrollbar = Scrollbar::default()
         .orientation(ScrollbarOrientation::VerticalRight)
         .begin_symbol(Some("▲"))
         .end_symbol(Some("▼"))
         .track_symbol(None)
-        .track_style(Style::default().fg(Color::Gray))
-        .thumb_style(Style::default().fg(Color::Gray));
+        .track_style(theme::current().scrollbar)
+        .thumb_style(theme::current().scrollbar);
 
     let mut scrollbar_state = ScrollbarState::default()
         .content_length(content_length)
@@ -203,16 +394,22 @@ fn render_scroll_bar(content_length: u16, scroll_position: u16, area: Rect, buf:
 }
 
 fn render_bounding_box(title: &Option<String>, area: Rect, buf: &mut Buffer) {
-    let mut title_line = vec![Span::from("Files (with recompile dependencies count)")];
+    let mut title_line = vec![
+        Span::from("Files (recompile dependencies: "),
+        Span::styled("definite", theme::current().definite),
+        Span::from(" "),
+        Span::styled("indefinite", theme::current().indefinite),
+        Span::from(")"),
+    ];
     if let Some(text) = title {
-        title_line.push(Span::styled(text, Style::default().fg(Color::Cyan)));
+        title_line.push(Span::styled(text, theme::current().title));
     }
 
     Block::default()
         .borders(Borders::ALL)
         .title(Line::from(title_line))
         .border_type(BorderType::Rounded)
-        .border_style(Style::default().fg(Color::White))
+        .border_style(theme::current().border)
         .render(area, buf);
 }
 
@@ -220,6 +417,7 @@ fn render_bounding_box(title: &Option<String>, area: Rect, buf: &mut Buffer) {
 mod handle_event_tests {
     use super::*;
     use crate::adapter::NoopAdapter;
+    use crate::components::search_input;
 
     fn noop_adapter() -> NoopAdapter {
         NoopAdapter::new()
@@ -243,7 +441,14 @@ mod handle_event_tests {
         let (tx, _) = mpsc::channel::<AppEvent>();
         state.handle_event(
             &AppEvent::UpButtonPressed,
-            &FilePanel::new(Some(file_entries(&["one", "two", "three"])), None),
+            &FilePanel::new(
+                Some(file_entries(&["one", "two", "three"]).into()),
+                vec![],
+                vec![],
+                Query::default(),
+                None,
+                None,
+            ),
             &mut noop_adapter(),
             tx,
         );
@@ -258,7 +463,14 @@ mod handle_event_tests {
         let (tx, _) = mpsc::channel::<AppEvent>();
         state.handle_event(
             &AppEvent::UpButtonPressed,
-            &FilePanel::new(Some(file_entries(&["one", "two", "three"])), None),
+            &FilePanel::new(
+                Some(file_entries(&["one", "two", "three"]).into()),
+                vec![],
+                vec![],
+                Query::default(),
+                None,
+                None,
+            ),
             &mut noop_adapter(),
             tx,
         );
@@ -273,7 +485,14 @@ mod handle_event_tests {
         let (tx, _) = mpsc::channel::<AppEvent>();
         state.handle_event(
             &AppEvent::DownButtonPressed,
-            &FilePanel::new(Some(file_entries(&["one", "two", "three"])), None),
+            &FilePanel::new(
+                Some(file_entries(&["one", "two", "three"]).into()),
+                vec![],
+                vec![],
+                Query::default(),
+                None,
+                None,
+            ),
             &mut noop_adapter(),
             tx,
         );
@@ -288,10 +507,200 @@ mod handle_event_tests {
         let (tx, _) = mpsc::channel::<AppEvent>();
         state.handle_event(
             &AppEvent::DownButtonPressed,
-            &FilePanel::new(Some(file_entries(&["one", "two", "three"])), None),
+            &FilePanel::new(
+                Some(file_entries(&["one", "two", "three"]).into()),
+                vec![],
+                vec![],
+                Query::default(),
+                None,
+                None,
+            ),
             &mut noop_adapter(),
             tx,
         );
         assert_eq!(state.selected_file_index, 2);
     }
+
+    #[test]
+    fn focus_file() {
+        let mut state = State::new();
+
+        let (tx, _) = mpsc::channel::<AppEvent>();
+        let widget = FilePanel::new(
+            Some(file_entries(&["one", "two", "three"]).into()),
+            vec![],
+            vec![],
+            Query::default(),
+            None,
+            None,
+        );
+        state.handle_event(
+            &AppEvent::FocusFile(2),
+            &widget,
+            &mut noop_adapter(),
+            tx.clone(),
+        );
+        assert_eq!(state.selected_file_index, 2);
+
+        state.handle_event(&AppEvent::FocusFile(3), &widget, &mut noop_adapter(), tx);
+        assert_eq!(state.selected_file_index, 2);
+    }
+
+    #[test]
+    fn typing_search_selects_best_match() {
+        let mut state = State::new();
+        state.selected_file_index = 2;
+
+        let (tx, _) = mpsc::channel::<AppEvent>();
+        let widget = FilePanel::new(
+            Some(file_entries(&["one", "two", "three"]).into()),
+            vec![],
+            vec![],
+            Query::default(),
+            None,
+            None,
+        );
+
+        let cursor_left = AppEvent::SearchInputEdit(search_input::Edit::CursorLeft);
+        state.handle_event(&cursor_left, &widget, &mut noop_adapter(), tx.clone());
+        assert_eq!(state.selected_file_index, 2);
+
+        state.handle_event(
+            &AppEvent::SearchInput('o'),
+            &widget,
+            &mut noop_adapter(),
+            tx,
+        );
+        assert_eq!(state.selected_file_index, 0);
+    }
+
+    #[test]
+    fn selection_is_clamped_to_filtered_list() {
+        let mut state = State::new();
+        state.selected_file_index = 5;
+
+        let widget = FilePanel::new(
+            Some(file_entries(&["one", "two"]).into()),
+            vec![],
+            vec![],
+            Query::default(),
+            None,
+            None,
+        );
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 30, 10));
+        widget.render(buffer.area, &mut buffer, &mut state);
+        assert_eq!(state.selected_file_index, 1);
+    }
+}
+
+#[cfg(test)]
+mod produce_event_tests {
+    use super::*;
+    use crossterm::event::{Event, KeyModifiers, MouseEvent};
+
+    fn widget() -> FilePanel {
+        let files = (0..10)
+            .map(|index| FileEntry {
+                path: format!("lib/file_{}.ex", index),
+                recompile_dependencies: vec![],
+            })
+            .collect();
+
+        FilePanel::new(Some(files), vec![], vec![], Query::default(), None, None)
+    }
+
+    fn click(column: u16, row: u16) -> Event {
+        Event::Mouse(MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        })
+    }
+
+    fn rendered_state(widget: &FilePanel, selected_file_index: usize) -> State {
+        let mut state = State::new();
+        state.selected_file_index = selected_file_index;
+
+        let area = Rect::new(0, 0, 30, 6);
+        let mut buf = Buffer::empty(area);
+        widget.clone().render(area, &mut buf, &mut state);
+
+        state
+    }
+
+    #[test]
+    fn click_focuses_row() {
+        let widget = widget();
+        let mut state = rendered_state(&widget, 0);
+
+        // Rows start at y = 1 because of the border
+        assert!(matches!(
+            state.produce_event(&click(5, 2), &widget),
+            Some(AppEvent::FocusFile(1))
+        ));
+        assert!(state.produce_event(&click(5, 5), &widget).is_none());
+    }
+
+    #[test]
+    fn click_with_scroll_offset() {
+        let widget = widget();
+        let mut state = rendered_state(&widget, 7);
+
+        // 4 rows are visible, the selected row is the last one
+        assert!(matches!(
+            state.produce_event(&click(5, 4), &widget),
+            Some(AppEvent::FocusFile(7))
+        ));
+    }
+
+    #[test]
+    fn double_click_selects_file() {
+        let widget = widget();
+        let mut state = rendered_state(&widget, 0);
+
+        state.produce_event(&click(5, 3), &widget);
+        match state.produce_event(&click(5, 3), &widget) {
+            Some(AppEvent::SelectFile(file)) => assert_eq!(file.path, "lib/file_2.ex"),
+            _ => panic!("Expected SelectFile event"),
+        }
+    }
+
+    #[test]
+    fn clicks_on_different_rows() {
+        let widget = widget();
+        let mut state = rendered_state(&widget, 0);
+
+        state.produce_event(&click(5, 2), &widget);
+        assert!(matches!(
+            state.produce_event(&click(5, 3), &widget),
+            Some(AppEvent::FocusFile(2))
+        ));
+    }
+}
+
+#[cfg(test)]
+mod certainty_counts_tests {
+    use super::*;
+    use crate::RecomplileDependencyReason::{self, *};
+
+    fn dependents(reasons: &[RecomplileDependencyReason]) -> Vec<RecomplileDependency> {
+        reasons
+            .iter()
+            .enumerate()
+            .map(|(index, reason)| {
+                RecomplileDependency::fixture(&format!("lib/{}.ex", index))
+                    .with_reason(reason.clone())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn split_by_certainty() {
+        let dependents = dependents(&[Compile, Exports, CompileThenRuntime, ExportsThenCompile]);
+        assert_eq!(certainty_counts(&dependents, &None), (2, 2));
+
+        let filter = ReasonFilter::only(&Compile);
+        assert_eq!(certainty_counts(&dependents, &Some(filter)), (1, 0));
+    }
 }
@@ -0,0 +1,351 @@
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, BorderType, Borders, StatefulWidget, Widget};
+use std::collections::HashSet;
+use std::rc::Rc;
+use std::sync::mpsc;
+
+use crate::adapter::ServerAdapter;
+use crate::app_event::AppEvent;
+use crate::components::tree_list::TreeList;
+use crate::directory_tree::{DirectoryTree, Folder, Row, RowKind};
+use crate::keymap::{self, Action, Scope};
+use crate::theme;
+use crate::utils;
+use crate::{FilePath, HandleEvent, ProduceEvent};
+
+/// The files grouped by directory, the alternative to the flat list of `FilePanel`
+#[derive(Clone)]
+pub struct FileTree {
+    tree: Rc<DirectoryTree>,
+    pinned_files: Vec<FilePath>,
+    // Marked as changed for the what if report
+    marked_files: Vec<FilePath>,
+    panel_title: Option<String>,
+}
+
+impl FileTree {
+    pub fn new(
+        tree: Rc<DirectoryTree>,
+        pinned_files: Vec<FilePath>,
+        marked_files: Vec<FilePath>,
+        panel_title: Option<String>,
+    ) -> Self {
+        Self {
+            tree,
+            pinned_files,
+            marked_files,
+            panel_title,
+        }
+    }
+}
+
+pub struct State {
+    // The folders stay expanded while the list is searched or sorted
+    expanded: HashSet<FilePath>,
+    list: TreeList,
+}
+
+impl State {
+    pub fn new() -> Self {
+        Self {
+            expanded: HashSet::new(),
+            list: TreeList::new(),
+        }
+    }
+
+    fn rows(&self, tree: &DirectoryTree) -> Vec<Row> {
+        tree.rows(&self.expanded)
+    }
+
+    fn selected<'a>(&self, rows: &'a [Row]) -> Option<&'a Row> {
+        rows.get(self.list.selected_row())
+    }
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HandleEvent for State {
+    type Widget = FileTree;
+
+    fn handle_event(
+        &mut self,
+        event: &AppEvent,
+        widget: &Self::Widget,
+        _adapter: &mut impl ServerAdapter,
+        _dispatcher: mpsc::Sender<AppEvent>,
+    ) {
+        let rows_count = self.rows(&widget.tree).len();
+
+        match event {
+            // The rows change as the search is typed, the top one is selected like in the list
+            AppEvent::SearchInput(_) | AppEvent::SearchInputDelete | AppEvent::SubmitSearch => {
+                self.list.select(0)
+            }
+            AppEvent::SearchInputEdit(edit) if edit.changes_text() => self.list.select(0),
+
+            AppEvent::ToggleTreeNode(path) => {
+                if !self.expanded.remove(path) {
+                    self.expanded.insert(path.clone());
+                }
+
+                // Collapsing can hide the selected row, the toggled folder takes the selection
+                let rows = self.rows(&widget.tree);
+                if let Some(index) = rows.iter().position(|row| folder_path(row) == Some(path)) {
+                    self.list.select(index);
+                }
+            }
+
+            event => self.list.navigate(event, rows_count),
+        }
+    }
+}
+
+impl ProduceEvent for State {
+    type Widget = FileTree;
+
+    fn produce_event(
+        &mut self,
+        terminal_event: &crossterm::event::Event,
+        widget: &Self::Widget,
+    ) -> Option<AppEvent> {
+        match terminal_event {
+            crossterm::event::Event::Key(key)
+                if key.kind == crossterm::event::KeyEventKind::Press =>
+            {
+                let rows = self.rows(&widget.tree);
+                let row = self.selected(&rows);
+
+                match keymap::current().action(Scope::FilePanel, key) {
+                    Some(Action::Select) => match row?.kind {
+                        RowKind::Folder { ref folder, .. } => {
+                            Some(AppEvent::ToggleTreeNode(folder.path.clone()))
+                        }
+                        RowKind::File(index) => {
+                            Some(AppEvent::SelectFile(widget.tree.file(index).clone()))
+                        }
+                    },
+
+                    Some(Action::Pin) => match row?.kind {
+                        RowKind::File(index) => {
+                            Some(AppEvent::TogglePin(widget.tree.file(index).path.clone()))
+                        }
+                        RowKind::Folder { .. } => None,
+                    },
+
+                    Some(Action::Mark) => match row?.kind {
+                        RowKind::File(index) => {
+                            Some(AppEvent::ToggleMark(widget.tree.file(index).path.clone()))
+                        }
+                        RowKind::Folder { .. } => None,
+                    },
+
+                    Some(Action::ClearMarks) => Some(AppEvent::ClearMarks),
+
+                    Some(Action::ToggleTree) => Some(AppEvent::ToggleDirectoryTree),
+                    Some(Action::CycleSort) => Some(AppEvent::CycleFileSort),
+                    Some(Action::FilterCounts) => Some(AppEvent::ToggleFilteredCounts),
+                    Some(Action::ReverseSort) => Some(AppEvent::ReverseFileSort),
+                    _ => None,
+                }
+            }
+
+            crossterm::event::Event::Mouse(mouse) => self.list.mouse_event(mouse),
+
+            _ => None,
+        }
+    }
+}
+
+fn folder_path(row: &Row) -> Option<&FilePath> {
+    match row.kind {
+        RowKind::Folder { ref folder, .. } => Some(&folder.path),
+        RowKind::File(_) => None,
+    }
+}
+
+impl StatefulWidget for FileTree {
+    type State = State;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut State) {
+        let rect = utils::padding(&area, 1, 1);
+        let rows = state.rows(&self.tree);
+        let width = rect.width as usize;
+
+        render_bounding_box(&self.panel_title, area, buf);
+        state.list.render(&rows, rect, buf, |row| match row.kind {
+            RowKind::Folder {
+                ref folder,
+                ref name,
+                expanded,
+            } => self.folder_line(folder, name, expanded, row.depth, width),
+            RowKind::File(file) => self.file_line(file, row.depth, width),
+        });
+    }
+}
+
+impl FileTree {
+    // The number of unique dependents across the folder, then the file with the most of them
+    // when there is room left
+    fn folder_line(
+        &self,
+        folder: &Folder,
+        name: &str,
+        expanded: bool,
+        depth: usize,
+        width: usize,
+    ) -> Line<'static> {
+        let marker = if expanded { "▼" } else { "▶" };
+        let indent = "  ".repeat(depth);
+        let count = format!("{: >4}", folder.unique_dependents);
+        let max_width = width.saturating_sub(indent.len() + count.len() + 3);
+        let name = utils::compact_file_path(name, max_width);
+
+        let hotspot = folder
+            .hotspot
+            .map(|(index, count)| {
+                let path = &self.tree.file(index).path;
+                let filename = path.rsplit('/').next().unwrap_or(path);
+                format!(" (max {} {})", filename, count)
+            })
+            .filter(|hotspot| name.chars().count() + hotspot.chars().count() <= max_width)
+            .unwrap_or_default();
+
+        let padding = max_width.saturating_sub(name.chars().count() + hotspot.chars().count());
+
+        Line::from(vec![
+            Span::from(format!("{}{} ", indent, marker)),
+            Span::styled(name, theme::current().title),
+            Span::styled(hotspot, theme::current().hint),
+            Span::from(" ".repeat(padding)),
+            Span::styled(count, theme::current().count),
+            Span::from(" "),
+        ])
+    }
+
+    fn file_line(&self, index: usize, depth: usize, width: usize) -> Line<'static> {
+        let path = &self.tree.file(index).path;
+        let filename = path.rsplit('/').next().unwrap_or(path);
+
+        let pin_marker = if self.pinned_files.contains(path) {
+            Span::styled("*", theme::current().title)
+        } else {
+            Span::from(" ")
+        };
+
+        let mark_marker = if self.marked_files.contains(path) {
+            Span::styled("+", theme::current().definite)
+        } else {
+            Span::from(" ")
+        };
+
+        let indent = "  ".repeat(depth);
+        let count = format!("{: >4}", self.tree.count(index));
+        let max_width = width.saturating_sub(indent.len() + count.len() + 3);
+        let filename = utils::compact_file_path(filename, max_width);
+        let padding = max_width.saturating_sub(filename.chars().count());
+
+        Line::from(vec![
+            Span::from(indent),
+            mark_marker,
+            pin_marker,
+            Span::from(filename),
+            Span::from(" ".repeat(padding)),
+            Span::styled(count, theme::current().count),
+            Span::from(" "),
+        ])
+    }
+}
+
+fn render_bounding_box(title: &Option<String>, area: Rect, buf: &mut Buffer) {
+    let mut title_line = vec![Span::from("Files by directory (unique dependents)")];
+    if let Some(text) = title {
+        title_line.push(Span::styled(text, theme::current().title));
+    }
+
+    Block::default()
+        .borders(Borders::ALL)
+        .title(Line::from(title_line))
+        .border_type(BorderType::Rounded)
+        .border_style(theme::current().border)
+        .render(area, buf);
+}
+
+#[cfg(test)]
+mod handle_event_tests {
+    use super::*;
+    use crate::adapter::NoopAdapter;
+    use crate::FileEntry;
+    use crossterm::event::{Event, KeyCode, KeyEvent};
+
+    // lib/ holds web/ and app.ex, web/ holds router.ex
+    fn widget() -> FileTree {
+        let files = ["lib/web/router.ex", "lib/app.ex", "mix.exs"]
+            .iter()
+            .map(|path| FileEntry::fixture(path, vec![]))
+            .collect();
+
+        FileTree::new(
+            Rc::new(DirectoryTree::build(files, None)),
+            vec![],
+            vec![],
+            None,
+        )
+    }
+
+    fn dispatch_events(state: &mut State, widget: &FileTree, events: &[AppEvent]) {
+        let (tx, _rx) = mpsc::channel::<AppEvent>();
+
+        for event in events {
+            state.handle_event(event, widget, &mut NoopAdapter::new(), tx.clone());
+        }
+    }
+
+    fn enter() -> Event {
+        Event::Key(KeyEvent::from(KeyCode::Enter))
+    }
+
+    #[test]
+    fn enter_toggles_folder_and_selects_file() {
+        let widget = widget();
+        let mut state = State::new();
+
+        let event = state.produce_event(&enter(), &widget);
+        assert!(matches!(event, Some(AppEvent::ToggleTreeNode(ref path)) if path == "lib"));
+
+        dispatch_events(
+            &mut state,
+            &widget,
+            &[event.unwrap(), AppEvent::DownButtonPressed],
+        );
+        assert_eq!(state.rows(&widget.tree).len(), 4);
+
+        dispatch_events(&mut state, &widget, &[AppEvent::DownButtonPressed]);
+        let event = state.produce_event(&enter(), &widget);
+        assert!(matches!(event, Some(AppEvent::SelectFile(ref file)) if file.path == "lib/app.ex"));
+    }
+
+    #[test]
+    fn collapse_selects_toggled_folder() {
+        let widget = widget();
+        let mut state = State::new();
+
+        dispatch_events(
+            &mut state,
+            &widget,
+            &[
+                AppEvent::ToggleTreeNode(String::from("lib")),
+                AppEvent::FocusTreeRow(2),
+                AppEvent::ToggleTreeNode(String::from("lib")),
+            ],
+        );
+
+        assert_eq!(state.list.selected_row(), 0);
+        assert_eq!(state.rows(&widget.tree).len(), 2);
+    }
+}
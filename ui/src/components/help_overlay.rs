@@ -0,0 +1,104 @@
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::Modifier;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, BorderType, Borders, Clear, Paragraph, Widget};
+
+use crate::keymap::{self, Scope};
+use crate::theme;
+use crate::utils;
+
+#[derive(Clone)]
+pub struct HelpOverlay {
+    scopes: Vec<Scope>,
+}
+
+impl HelpOverlay {
+    pub fn new(scopes: Vec<Scope>) -> Self {
+        Self { scopes }
+    }
+}
+
+impl Widget for HelpOverlay {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let lines = help_text(&self.scopes);
+
+        let content_width = lines.iter().map(|line| line.width()).max().unwrap_or(0) as u16;
+        let mut rect = Rect {
+            x: area.x,
+            y: area.y,
+            // Borders and horizontal padding
+            width: (content_width + 4).min(area.width),
+            height: (lines.len() as u16 + 2).min(area.height),
+        };
+        utils::center_rect_in_container(&mut rect, &area);
+
+        Clear.render(rect, buf);
+
+        Block::default()
+            .borders(Borders::ALL)
+            .title("Key bindings")
+            .border_type(BorderType::Rounded)
+            .border_style(theme::current().border)
+            .render(rect, buf);
+
+        Paragraph::new(lines)
+            .style(theme::current().text)
+            .render(utils::padding(&rect, 2, 1), buf);
+    }
+}
+
+fn help_text(scopes: &[Scope]) -> Vec<Line<'static>> {
+    let keymap = keymap::current();
+
+    let sections: Vec<(Scope, Vec<(String, &str)>)> = scopes
+        .iter()
+        .map(|scope| {
+            let mut entries: Vec<(String, &str)> = keymap
+                .describe(*scope)
+                .into_iter()
+                .map(|(keys, action)| {
+                    let keys: Vec<String> = keys.iter().map(|key| key.to_string()).collect();
+                    (keys.join("/"), action.description())
+                })
+                .collect();
+
+            if *scope == Scope::Prompt {
+                entries.push((String::from("<any>"), "Type into the prompt"));
+            }
+
+            (*scope, entries)
+        })
+        .collect();
+
+    let keys_width = sections
+        .iter()
+        .flat_map(|(_, entries)| entries.iter().map(|(keys, _)| keys.chars().count()))
+        .max()
+        .unwrap_or(0);
+
+    let mut lines = vec![];
+
+    for (index, (scope, entries)) in sections.into_iter().enumerate() {
+        if index > 0 {
+            lines.push(Line::from(""));
+        }
+
+        lines.push(Line::styled(
+            scope.title(),
+            theme::current().title.add_modifier(Modifier::BOLD),
+        ));
+
+        for (keys, description) in entries {
+            lines.push(Line::from(vec![
+                Span::styled(
+                    format!("{: >width$}", keys, width = keys_width),
+                    theme::current().hint,
+                ),
+                Span::from(format!("  {}", description)),
+            ]));
+        }
+    }
+
+    lines
+}
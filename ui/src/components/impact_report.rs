@@ -0,0 +1,260 @@
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, BorderType, Borders, Clear, Paragraph, StatefulWidget, Widget};
+use std::rc::Rc;
+use std::sync::mpsc;
+
+use crate::adapter::ServerAdapter;
+use crate::app_event::AppEvent;
+use crate::impact::{AffectedFile, Impact};
+use crate::keymap::{self, Action, Scope};
+use crate::theme;
+use crate::utils;
+use crate::HandleEvent;
+
+/// Every file recompiled when the marked files change, and which of them trigger it. Drawn over
+/// the panels
+#[derive(Clone)]
+pub struct ImpactReport {
+    impact: Rc<Impact>,
+}
+
+impl ImpactReport {
+    pub fn new(impact: Rc<Impact>) -> Self {
+        Self { impact }
+    }
+}
+
+pub struct State {
+    scroll_offset: usize,
+    // The number of lines in the last render, the report can't be scrolled past them
+    lines_count: usize,
+}
+
+impl State {
+    pub fn new() -> Self {
+        Self {
+            scroll_offset: 0,
+            lines_count: 0,
+        }
+    }
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HandleEvent for State {
+    type Widget = ImpactReport;
+
+    fn handle_event(
+        &mut self,
+        event: &AppEvent,
+        _widget: &Self::Widget,
+        _adapter: &mut impl ServerAdapter,
+        _dispatcher: mpsc::Sender<AppEvent>,
+    ) {
+        match event {
+            AppEvent::ScrollImpactReportDown if self.scroll_offset + 1 < self.lines_count => {
+                self.scroll_offset += 1;
+            }
+
+            AppEvent::ScrollImpactReportUp => {
+                self.scroll_offset = self.scroll_offset.saturating_sub(1);
+            }
+
+            // Each report starts from the top
+            AppEvent::ToggleImpactReport => self.scroll_offset = 0,
+            _ => (),
+        }
+    }
+}
+
+impl StatefulWidget for ImpactReport {
+    type State = State;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut State) {
+        Clear.render(area, buf);
+
+        Block::default()
+            .borders(Borders::ALL)
+            .title("What if")
+            .border_type(BorderType::Rounded)
+            .border_style(theme::current().border)
+            .render(area, buf);
+
+        let lines = self.lines();
+        state.lines_count = lines.len();
+        state.scroll_offset = state.scroll_offset.min(lines.len().saturating_sub(1));
+
+        Paragraph::new(lines)
+            .style(theme::current().text)
+            .scroll((state.scroll_offset as u16, 0))
+            .render(utils::padding(&area, 2, 1), buf);
+    }
+}
+
+impl ImpactReport {
+    fn lines(&self) -> Vec<Line<'static>> {
+        let impact = &self.impact;
+
+        if impact.changed.is_empty() {
+            let key = keymap::current()
+                .keys(Scope::FilePanel, Action::Mark)
+                .first()
+                .map(|key| key.to_string())
+                .unwrap_or_default();
+
+            return vec![Line::styled(
+                format!(
+                    "No file is marked, mark files with {} in the files panel",
+                    key
+                ),
+                theme::current().hint,
+            )];
+        }
+
+        let mut lines = vec![Line::styled("If these files change:", bold())];
+        lines.extend(
+            impact
+                .changed
+                .iter()
+                .map(|path| Line::styled(format!("  {}", path), theme::current().title)),
+        );
+        lines.push(Line::from(""));
+        lines.extend(recompiled_lines(impact));
+
+        lines
+    }
+}
+
+/// The count of the files recompiled, then each of them with the changed files triggering it
+pub fn recompiled_lines(impact: &Impact) -> Vec<Line<'static>> {
+    let (definite, indefinite) = impact.counts();
+    let mut lines = vec![Line::from(vec![
+        Span::styled(
+            format!("Files recompiled: {} – ", impact.affected.len()),
+            bold(),
+        ),
+        Span::styled(format!("{} definite", definite), theme::current().definite),
+        Span::from(", "),
+        Span::styled(
+            format!("{} indefinite", indefinite),
+            theme::current().indefinite,
+        ),
+    ])];
+
+    for file in &impact.affected {
+        lines.push(Line::from(""));
+        lines.extend(affected_lines(file));
+    }
+
+    lines
+}
+
+// The file, then each changed file triggering it with the reason
+fn affected_lines(file: &AffectedFile) -> Vec<Line<'static>> {
+    let style = if file.is_definite() {
+        theme::current().definite
+    } else {
+        theme::current().indefinite
+    };
+
+    let mut lines = vec![Line::styled(file.path.clone(), style)];
+    lines.extend(file.triggers.iter().map(|(source, reason)| {
+        Line::from(vec![
+            Span::from(format!("  ← {} ", source)),
+            Span::styled(format!("({})", reason), theme::current().hint),
+        ])
+    }));
+
+    lines
+}
+
+fn bold() -> Style {
+    Style::default().add_modifier(Modifier::BOLD)
+}
+
+#[cfg(test)]
+mod impact_report_tests {
+    use super::*;
+    use crate::adapter::NoopAdapter;
+    use crate::{FileEntry, RecomplileDependency, RecomplileDependencyReason};
+
+    fn report(changed: &[&str]) -> ImpactReport {
+        let dependent = |reason| RecomplileDependency::fixture("c").with_reason(reason);
+        let files = vec![
+            FileEntry::fixture("a", vec![dependent(RecomplileDependencyReason::Exports)]),
+            FileEntry::fixture("b", vec![dependent(RecomplileDependencyReason::Compile)]),
+        ];
+
+        let changed: Vec<String> = changed.iter().map(|path| path.to_string()).collect();
+        ImpactReport::new(Rc::new(Impact::compute(&changed, &files)))
+    }
+
+    fn text(lines: &[Line]) -> Vec<String> {
+        lines
+            .iter()
+            .map(|line| {
+                line.spans
+                    .iter()
+                    .map(|span| span.content.as_ref())
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn triggers_of_each_file() {
+        let lines = text(&report(&["a", "b"]).lines());
+
+        assert_eq!(
+            lines,
+            [
+                "If these files change:",
+                "  a",
+                "  b",
+                "",
+                "Files recompiled: 1 – 1 definite, 0 indefinite",
+                "",
+                "c",
+                "  ← a (exports)",
+                "  ← b (compile)",
+            ]
+        );
+    }
+
+    #[test]
+    fn nothing_marked() {
+        let lines = text(&report(&[]).lines());
+        assert_eq!(
+            lines,
+            ["No file is marked, mark files with space in the files panel"]
+        );
+    }
+
+    #[test]
+    fn scroll_is_bounded() {
+        let report = report(&["a", "b"]);
+        let mut state = State::new();
+
+        let area = Rect::new(0, 0, 60, 20);
+        let mut buffer = Buffer::empty(area);
+        report.clone().render(area, &mut buffer, &mut state);
+
+        let (tx, _rx) = mpsc::channel::<AppEvent>();
+        for _ in 0..20 {
+            let event = AppEvent::ScrollImpactReportDown;
+            state.handle_event(&event, &report, &mut NoopAdapter::new(), tx.clone());
+        }
+        assert_eq!(state.scroll_offset, 8);
+
+        let event = AppEvent::ToggleImpactReport;
+        state.handle_event(&event, &report, &mut NoopAdapter::new(), tx);
+        assert_eq!(state.scroll_offset, 0);
+    }
+}
@@ -1,9 +1,10 @@
 use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
-use ratatui::style::{Color, Style};
 use ratatui::text::{Line, Span};
 use ratatui::widgets::{Paragraph, Widget};
 
+use crate::keymap::{self, Action, Scope};
+use crate::theme;
 use crate::utils;
 
 #[derive(Clone)]
@@ -20,11 +21,31 @@ pub struct State {}
 impl Widget for Instructions {
     fn render(self, area: Rect, buf: &mut Buffer) {
         let rect = utils::padding(&area, 1, 0);
+        let keymap = keymap::current();
+        let first_key = |scope: Scope, action: Action| {
+            keymap
+                .keys(scope, action)
+                .first()
+                .map(|key| key.to_string())
+                .unwrap_or_default()
+        };
+
         let paragraph = Paragraph::new(Line::from(vec![
-            Span::from("j/k: Move; "),
-            Span::from("<enter>: Select"),
+            Span::from(format!(
+                "{}/{}: Move; ",
+                first_key(Scope::Global, Action::MoveDown),
+                first_key(Scope::Global, Action::MoveUp)
+            )),
+            Span::from(format!(
+                "{}: Select; ",
+                first_key(Scope::FilePanel, Action::Select)
+            )),
+            Span::from(format!(
+                "{}: Help",
+                first_key(Scope::Global, Action::ToggleHelp)
+            )),
         ]))
-        .style(Style::default().fg(Color::Yellow));
+        .style(theme::current().hint);
 
         paragraph.render(rect, buf);
     }
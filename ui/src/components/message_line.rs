@@ -0,0 +1,37 @@
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::widgets::{Paragraph, Widget};
+
+use crate::theme;
+use crate::utils;
+
+/// A message shown in the footer until the next key press, e.g. the outcome of a command
+#[derive(Debug, Clone, PartialEq)]
+pub enum Message {
+    Info(String),
+    Error(String),
+}
+
+#[derive(Clone)]
+pub struct MessageLine {
+    message: Message,
+}
+
+impl MessageLine {
+    pub fn new(message: Message) -> Self {
+        Self { message }
+    }
+}
+
+impl Widget for MessageLine {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let rect = utils::padding(&area, 1, 0);
+
+        let paragraph = match self.message {
+            Message::Info(text) => Paragraph::new(text).style(theme::current().hint),
+            Message::Error(text) => Paragraph::new(text).style(theme::current().error),
+        };
+
+        paragraph.render(rect, buf);
+    }
+}
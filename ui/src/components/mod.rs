@@ -1,6 +1,16 @@
+pub mod causes;
+pub mod changed_files_panel;
+pub mod dashboard;
 pub mod dependency_cause_panel;
+pub mod dependents_tree;
 pub mod file_dependent_panel;
 pub mod file_panel;
+pub mod file_tree;
+pub mod help_overlay;
+pub mod impact_report;
 pub mod instructions;
 pub mod loading_icon;
+pub mod message_line;
 pub mod search_input;
+pub mod tree_list;
+pub mod why_report;
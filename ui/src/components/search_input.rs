@@ -1,19 +1,189 @@
 use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
-use ratatui::style::{Color, Style};
+use ratatui::style::{Modifier, Style};
 use ratatui::text::{Line, Span};
 use ratatui::widgets::{Paragraph, Widget};
 
+use crate::theme;
 use crate::utils;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum State {
     None,
-    Prompt(String),
+    Prompt(Input),
     Search(String),
 }
 
+/// The text of a prompt being edited. The cursor is a character index, not a byte index, so
+/// it stays on character boundaries with non-ASCII input
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Input {
+    text: String,
+    cursor: usize,
+}
+
+/// Editing operations on a prompt, besides typing and deleting a character
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Edit {
+    CursorLeft,
+    CursorRight,
+    CursorHome,
+    CursorEnd,
+    DeleteWord,
+    ClearLine,
+    PreviousQuery,
+    NextQuery,
+}
+
+impl Edit {
+    /// Whether the edit can change the text, rather than only move the cursor
+    pub fn changes_text(&self) -> bool {
+        !matches!(
+            self,
+            Edit::CursorLeft | Edit::CursorRight | Edit::CursorHome | Edit::CursorEnd
+        )
+    }
+}
+
+impl Input {
+    /// An input with the cursor at the end of the text
+    pub fn new(text: &str) -> Self {
+        Self {
+            text: text.to_string(),
+            cursor: text.chars().count(),
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    fn byte_index(&self, cursor: usize) -> usize {
+        self.text
+            .char_indices()
+            .nth(cursor)
+            .map(|(index, _)| index)
+            .unwrap_or(self.text.len())
+    }
+
+    fn insert(&mut self, char: char) {
+        let index = self.byte_index(self.cursor);
+        self.text.insert(index, char);
+        self.cursor += 1;
+    }
+
+    // Delete the character before the cursor
+    fn delete(&mut self) {
+        if self.cursor > 0 {
+            self.cursor -= 1;
+            let index = self.byte_index(self.cursor);
+            self.text.remove(index);
+        }
+    }
+
+    // Delete the word before the cursor, along with the whitespaces following it
+    fn delete_word(&mut self) {
+        let chars: Vec<char> = self.text.chars().collect();
+        let mut start = self.cursor;
+
+        while start > 0 && chars[start - 1].is_whitespace() {
+            start -= 1;
+        }
+
+        while start > 0 && !chars[start - 1].is_whitespace() {
+            start -= 1;
+        }
+
+        let range = self.byte_index(start)..self.byte_index(self.cursor);
+        self.text.replace_range(range, "");
+        self.cursor = start;
+    }
+
+    fn edit(&mut self, edit: Edit) {
+        match edit {
+            Edit::CursorLeft => self.cursor = self.cursor.saturating_sub(1),
+            Edit::CursorRight => self.cursor = (self.cursor + 1).min(self.text.chars().count()),
+            Edit::CursorHome => self.cursor = 0,
+            Edit::CursorEnd => self.cursor = self.text.chars().count(),
+            Edit::DeleteWord => self.delete_word(),
+            Edit::ClearLine => *self = Self::default(),
+            // Recalling queries needs the history, see `State::edit`
+            Edit::PreviousQuery | Edit::NextQuery => (),
+        }
+    }
+}
+
+/// Earlier queries of a search, recalled with up and down in the prompt
+#[derive(Debug, Clone, Default)]
+pub struct SearchHistory {
+    queries: Vec<String>,
+    // The recalled query, and the input that was being typed before recalling
+    position: Option<(usize, Input)>,
+}
+
+const MAX_SEARCH_HISTORY: usize = 100;
+
+impl SearchHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a submitted query. Submitting an earlier query again moves it to the end
+    pub fn push(&mut self, query: &str) {
+        self.position = None;
+
+        if query.trim().is_empty() {
+            return;
+        }
+
+        self.queries.retain(|earlier| earlier != query);
+        self.queries.push(query.to_string());
+
+        if self.queries.len() > MAX_SEARCH_HISTORY {
+            self.queries.remove(0);
+        }
+    }
+
+    fn previous(&mut self, input: &Input) -> Option<Input> {
+        let (index, draft) = match self.position.take() {
+            Some((0, draft)) => (0, draft),
+            Some((index, draft)) => (index - 1, draft),
+            None if self.queries.is_empty() => return None,
+            None => (self.queries.len() - 1, input.clone()),
+        };
+
+        let recalled = Input::new(&self.queries[index]);
+        self.position = Some((index, draft));
+
+        Some(recalled)
+    }
+
+    fn next(&mut self) -> Option<Input> {
+        match self.position.take()? {
+            (index, draft) if index + 1 == self.queries.len() => Some(draft),
+            (index, draft) => {
+                self.position = Some((index + 1, draft));
+                Some(Input::new(&self.queries[index + 1]))
+            }
+        }
+    }
+
+    /// Stop recalling, e.g. when the prompt is edited or closed
+    pub fn reset(&mut self) {
+        self.position = None;
+    }
+}
+
 impl State {
+    /// A prompt with the given input, with the cursor at its end
+    pub fn prompt(text: &str) -> Self {
+        Self::Prompt(Input::new(text))
+    }
+
     // Input is either in prompting or searching state
     pub fn is_active(&self) -> bool {
         match self {
@@ -39,38 +209,70 @@ impl State {
         }
     }
 
+    /// The text searched for, while it is typed too. The cursor doesn't change it
+    pub fn query_text(&self) -> &str {
+        match self {
+            Self::Search(text) => text,
+            Self::Prompt(input) => input.text(),
+            Self::None => "",
+        }
+    }
+
     pub fn prompt_input(&self) -> Option<String> {
         match self {
-            Self::Prompt(input) => Some(input.clone()),
+            Self::Prompt(input) => Some(input.text.clone()),
             _ => None,
         }
     }
 
     pub fn prompt_begin(&mut self) {
         if let Self::None = self {
-            *self = Self::Prompt(String::new());
+            *self = Self::Prompt(Input::default());
         }
     }
 
-    pub fn prompt_add(&mut self, char: char) {
+    pub fn prompt_add(&mut self, char: char, history: &mut SearchHistory) {
         if let Self::Prompt(input) = self {
-            input.push(char);
+            history.reset();
+            input.insert(char);
         }
     }
 
-    pub fn prompt_remove(&mut self) {
+    pub fn prompt_remove(&mut self, history: &mut SearchHistory) {
         if let Self::Prompt(input) = self {
-            input.pop();
+            history.reset();
+            input.delete();
         }
     }
 
-    pub fn search(&mut self) {
+    pub fn edit(&mut self, edit: Edit, history: &mut SearchHistory) {
         if let Self::Prompt(input) = self {
-            *self = Self::Search(input.clone());
+            let recalled = match edit {
+                Edit::PreviousQuery => history.previous(input),
+                Edit::NextQuery => history.next(),
+                edit => {
+                    history.reset();
+                    input.edit(edit);
+                    None
+                }
+            };
+
+            if let Some(recalled) = recalled {
+                *input = recalled;
+            }
         }
     }
 
-    pub fn cancel(&mut self) {
+    pub fn search(&mut self, history: &mut SearchHistory) {
+        if let Self::Prompt(input) = self {
+            history.push(&input.text);
+            *self = Self::Search(input.text.clone());
+        }
+    }
+
+    pub fn cancel(&mut self, history: &mut SearchHistory) {
+        history.reset();
+
         match self {
             Self::Prompt(_) => *self = Self::None,
             Self::Search(_) => *self = Self::None,
@@ -88,11 +290,20 @@ impl Default for State {
 #[derive(Clone)]
 pub struct SearchInput {
     state: State,
+    label: &'static str,
 }
 
 impl SearchInput {
     pub fn new(state: State) -> Self {
-        Self { state }
+        Self {
+            state,
+            label: "Search: ",
+        }
+    }
+
+    /// The prompt of the command palette
+    pub fn command(state: State) -> Self {
+        Self { state, label: ":" }
     }
 }
 
@@ -103,20 +314,192 @@ impl Widget for SearchInput {
         let paragraph = match self.state {
             State::None => Paragraph::new(""),
             State::Prompt(input) => {
-                Paragraph::new(Line::from(vec![Span::from("Search: "), Span::from(input)]))
-                    .style(Style::default().fg(Color::Cyan))
+                let mut spans = vec![Span::from(self.label)];
+                spans.extend(input_with_cursor(&input));
+
+                Paragraph::new(Line::from(spans)).style(theme::current().prompt)
             }
 
             State::Search(query) => Paragraph::new(Line::from(vec![
-                Span::from(format!("Search: {}", query)),
-                Span::styled(
-                    " | <esc> to exit search",
-                    Style::default().fg(Color::Yellow),
-                ),
+                Span::from(format!("{}{}", self.label, query)),
+                Span::styled(" | <esc> to exit search", theme::current().hint),
             ]))
-            .style(Style::default().fg(Color::Cyan)),
+            .style(theme::current().prompt),
         };
 
         paragraph.render(rect, buf);
     }
 }
+
+// Split the input around the cursor, which is drawn as a reversed character
+fn input_with_cursor(input: &Input) -> Vec<Span<'static>> {
+    let before: String = input.text.chars().take(input.cursor).collect();
+    let mut after = input.text.chars().skip(input.cursor);
+
+    let cursor = after.next().map(String::from).unwrap_or(String::from(" "));
+    let after: String = after.collect();
+
+    vec![
+        Span::from(before),
+        Span::styled(cursor, Style::default().add_modifier(Modifier::REVERSED)),
+        Span::from(after),
+    ]
+}
+
+#[cfg(test)]
+mod input_tests {
+    use super::*;
+
+    fn input(text: &str, cursor: usize) -> Input {
+        Input {
+            text: text.to_string(),
+            cursor,
+        }
+    }
+
+    #[test]
+    fn insert_at_cursor() {
+        let mut state = State::Prompt(input("ac", 1));
+        state.prompt_add('b', &mut SearchHistory::new());
+        assert_eq!(state, State::Prompt(input("abc", 2)));
+    }
+
+    #[test]
+    fn non_ascii() {
+        let mut state = State::prompt("üñï");
+        let mut history = SearchHistory::new();
+
+        state.edit(Edit::CursorLeft, &mut history);
+        state.prompt_add('ç', &mut history);
+        assert_eq!(state, State::Prompt(input("üñçï", 3)));
+
+        state.prompt_remove(&mut history);
+        state.prompt_remove(&mut history);
+        assert_eq!(state, State::Prompt(input("üï", 1)));
+    }
+
+    #[test]
+    fn cursor_movements() {
+        let mut state = State::prompt("abc");
+        let mut history = SearchHistory::new();
+
+        state.edit(Edit::CursorRight, &mut history);
+        assert_eq!(state, State::Prompt(input("abc", 3)));
+
+        state.edit(Edit::CursorHome, &mut history);
+        state.edit(Edit::CursorLeft, &mut history);
+        assert_eq!(state, State::Prompt(input("abc", 0)));
+
+        state.edit(Edit::CursorEnd, &mut history);
+        assert_eq!(state, State::Prompt(input("abc", 3)));
+    }
+
+    #[test]
+    fn query_text_ignores_the_cursor() {
+        let mut state = State::prompt("abc");
+        state.edit(Edit::CursorHome, &mut SearchHistory::new());
+        assert_eq!(state.query_text(), "abc");
+
+        assert_eq!(State::Search(String::from("abc")).query_text(), "abc");
+        assert_eq!(State::None.query_text(), "");
+    }
+
+    #[test]
+    fn delete_word() {
+        let mut state = State::Prompt(input("lib/foo  'bar baz", 14));
+        let mut history = SearchHistory::new();
+
+        state.edit(Edit::DeleteWord, &mut history);
+        assert_eq!(state, State::Prompt(input("lib/foo  baz", 9)));
+
+        state.edit(Edit::DeleteWord, &mut history);
+        assert_eq!(state, State::Prompt(input("baz", 0)));
+
+        state.edit(Edit::DeleteWord, &mut history);
+        assert_eq!(state, State::Prompt(input("baz", 0)));
+    }
+
+    #[test]
+    fn clear_line() {
+        let mut state = State::prompt("foo bar");
+        state.edit(Edit::ClearLine, &mut SearchHistory::new());
+        assert_eq!(state, State::prompt(""));
+    }
+
+    #[test]
+    fn recall_queries() {
+        let mut history = SearchHistory::new();
+        for query in ["one", "two", "one", " "] {
+            let mut state = State::prompt(query);
+            state.search(&mut history);
+        }
+
+        let mut state = State::prompt("dra");
+        state.edit(Edit::PreviousQuery, &mut history);
+        assert_eq!(state, State::prompt("one"));
+
+        state.edit(Edit::PreviousQuery, &mut history);
+        assert_eq!(state, State::prompt("two"));
+
+        // There is nothing older
+        state.edit(Edit::PreviousQuery, &mut history);
+        assert_eq!(state, State::prompt("two"));
+
+        state.edit(Edit::NextQuery, &mut history);
+        state.edit(Edit::NextQuery, &mut history);
+        assert_eq!(state, State::prompt("dra"));
+
+        state.edit(Edit::NextQuery, &mut history);
+        assert_eq!(state, State::prompt("dra"));
+    }
+
+    fn history_of(queries: &[&str]) -> SearchHistory {
+        let mut history = SearchHistory::new();
+        for query in queries {
+            State::prompt(query).search(&mut history);
+        }
+
+        history
+    }
+
+    #[test]
+    fn typing_stops_recalling() {
+        let mut history = history_of(&["one", "two"]);
+
+        let mut state = State::prompt("dra");
+        state.edit(Edit::PreviousQuery, &mut history);
+        state.prompt_add('s', &mut history);
+        assert_eq!(state, State::prompt("twos"));
+
+        // The typed query is the draft now, the newest query is recalled again
+        state.edit(Edit::PreviousQuery, &mut history);
+        assert_eq!(state, State::prompt("two"));
+
+        state.edit(Edit::NextQuery, &mut history);
+        assert_eq!(state, State::prompt("twos"));
+    }
+
+    #[test]
+    fn cancel_stops_recalling() {
+        let mut history = history_of(&["one", "two"]);
+
+        let mut state = State::prompt("dra");
+        state.edit(Edit::PreviousQuery, &mut history);
+        state.cancel(&mut history);
+
+        state.prompt_begin();
+        state.edit(Edit::NextQuery, &mut history);
+        assert_eq!(state, State::prompt(""));
+    }
+
+    #[test]
+    fn render_cursor() {
+        let spans = input_with_cursor(&input("añb", 1));
+        let contents: Vec<&str> = spans.iter().map(|span| span.content.as_ref()).collect();
+        assert_eq!(contents, vec!["a", "ñ", "b"]);
+
+        let spans = input_with_cursor(&input("ab", 2));
+        let contents: Vec<&str> = spans.iter().map(|span| span.content.as_ref()).collect();
+        assert_eq!(contents, vec!["ab", " ", ""]);
+    }
+}
@@ -0,0 +1,166 @@
+use crossterm::event::{MouseButton, MouseEvent, MouseEventKind};
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::text::Line;
+use ratatui::widgets::{Paragraph, Widget};
+
+use crate::app_event::AppEvent;
+use crate::theme;
+use crate::utils;
+
+/// The selected row of a tree and its scrolling, shared by the trees of the panels. The trees
+/// build their rows, the list keeps the selected one in view
+pub struct TreeList {
+    selected_row: usize,
+    // The area of the rows and their scroll offset in the last render, used to map mouse
+    // positions back to rows
+    rows_area: Rect,
+    scroll_offset: usize,
+}
+
+impl TreeList {
+    pub fn new() -> Self {
+        Self {
+            selected_row: 0,
+            rows_area: Rect::default(),
+            scroll_offset: 0,
+        }
+    }
+
+    pub fn selected_row(&self) -> usize {
+        self.selected_row
+    }
+
+    pub fn select(&mut self, row: usize) {
+        self.selected_row = row;
+    }
+
+    /// Move the selection on the events moving through the rows
+    pub fn navigate(&mut self, event: &AppEvent, rows_count: usize) {
+        match event {
+            AppEvent::DownButtonPressed if self.selected_row + 1 < rows_count => {
+                self.selected_row += 1;
+            }
+
+            AppEvent::UpButtonPressed => self.selected_row = self.selected_row.saturating_sub(1),
+
+            AppEvent::FocusTreeRow(index) if *index < rows_count => self.selected_row = *index,
+            _ => (),
+        }
+    }
+
+    /// Clicking focuses the row under the mouse, scrolling moves the selection
+    pub fn mouse_event(&self, mouse: &MouseEvent) -> Option<AppEvent> {
+        let index = self.row_at(mouse.column, mouse.row)?;
+
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) => Some(AppEvent::FocusTreeRow(index)),
+            MouseEventKind::ScrollDown => Some(AppEvent::DownButtonPressed),
+            MouseEventKind::ScrollUp => Some(AppEvent::UpButtonPressed),
+            _ => None,
+        }
+    }
+
+    fn row_at(&self, column: u16, row: u16) -> Option<usize> {
+        if utils::contains(&self.rows_area, column, row) {
+            Some((row - self.rows_area.y) as usize + self.scroll_offset)
+        } else {
+            None
+        }
+    }
+
+    /// Render the rows in view, the selected one highlighted. Only the lines of these rows are
+    /// built
+    pub fn render<R>(
+        &mut self,
+        rows: &[R],
+        area: Rect,
+        buf: &mut Buffer,
+        line: impl Fn(&R) -> Line<'static>,
+    ) {
+        self.selected_row = self.selected_row.min(rows.len().saturating_sub(1));
+
+        // Keep the selected row in view, moving as little as possible
+        let height = area.height as usize;
+        self.scroll_offset = match self.selected_row {
+            selected if selected < self.scroll_offset => selected,
+            selected if selected >= self.scroll_offset + height => selected + 1 - height,
+            _ => self.scroll_offset.min(rows.len().saturating_sub(height)),
+        };
+        self.rows_area = area;
+
+        let lines: Vec<Line> = rows
+            .iter()
+            .enumerate()
+            .skip(self.scroll_offset)
+            .take(height)
+            .map(|(index, row)| {
+                let mut line = line(row);
+                if index == self.selected_row {
+                    line.patch_style(theme::current().selection);
+                }
+
+                line
+            })
+            .collect();
+
+        Paragraph::new(lines)
+            .style(theme::current().text)
+            .render(area, buf);
+    }
+}
+
+impl Default for TreeList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tree_list_tests {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+
+    fn render(list: &mut TreeList, rows_count: usize) {
+        let rows: Vec<usize> = (0..rows_count).collect();
+        let area = Rect::new(0, 2, 20, 3);
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 20, 10));
+        list.render(&rows, area, &mut buffer, |row| Line::from(row.to_string()));
+    }
+
+    fn click(row: u16) -> MouseEvent {
+        MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column: 1,
+            row,
+            modifiers: KeyModifiers::NONE,
+        }
+    }
+
+    #[test]
+    fn selection_stays_in_view() {
+        let mut list = TreeList::new();
+        for _ in 0..5 {
+            list.navigate(&AppEvent::DownButtonPressed, 10);
+        }
+        render(&mut list, 10);
+
+        // Rows 3 to 5 are shown, the selected one at the bottom
+        assert!(matches!(
+            list.mouse_event(&click(4)),
+            Some(AppEvent::FocusTreeRow(5))
+        ));
+        assert!(list.mouse_event(&click(5)).is_none());
+    }
+
+    #[test]
+    fn selection_is_bounded() {
+        let mut list = TreeList::new();
+        list.navigate(&AppEvent::FocusTreeRow(2), 2);
+        assert_eq!(list.selected_row(), 0);
+
+        list.select(8);
+        render(&mut list, 4);
+        assert_eq!(list.selected_row(), 3);
+    }
+}
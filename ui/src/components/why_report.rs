@@ -0,0 +1,459 @@
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, BorderType, Borders, Clear, Paragraph, StatefulWidget, Widget};
+use std::rc::Rc;
+use std::sync::mpsc;
+
+use crate::adapter::ServerAdapter;
+use crate::app_event::AppEvent;
+use crate::components::causes::{self, Causes};
+use crate::components::loading_icon::LoadingIcon;
+use crate::theme;
+use crate::utils;
+use crate::{DependencyLink, FileEntry, FilePath, HandleEvent, RecomplileDependency};
+
+/// Answers why a file recompiles when another one changes: every recompile relation between
+/// them, the chains of each and the causes of their links. Drawn over the panels
+#[derive(Clone)]
+pub struct WhyReport {
+    dependent: FilePath,
+    changed: FilePath,
+    // The dependents of the changed file which are the dependent file, one for each reason
+    relations: Rc<[RecomplileDependency]>,
+    // The changed file recompiles when the dependent changes, the other way around
+    reverse: bool,
+}
+
+impl WhyReport {
+    pub fn new(dependent: FilePath, changed: FilePath, files: &[FileEntry]) -> Self {
+        let relations = |source: &str, dependent: &str| -> Rc<[RecomplileDependency]> {
+            files
+                .iter()
+                .find(|file| file.path == source)
+                .map(|file| {
+                    file.recompile_dependencies
+                        .iter()
+                        .filter(|relation| relation.path == dependent)
+                        .cloned()
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+
+        Self {
+            relations: relations(&changed, &dependent),
+            reverse: !relations(&dependent, &changed).is_empty(),
+            dependent,
+            changed,
+        }
+    }
+}
+
+pub struct State {
+    // The causes of each chain of each relation
+    causes: Vec<Vec<Causes>>,
+    // The id of the last query, the responses to earlier queries are ignored
+    query_id: usize,
+    scroll_offset: usize,
+    // The number of lines in the last render, the report can't be scrolled past them
+    lines_count: usize,
+}
+
+impl State {
+    pub fn new() -> Self {
+        Self {
+            causes: vec![],
+            query_id: 0,
+            scroll_offset: 0,
+            lines_count: 0,
+        }
+    }
+
+    /// Requests the causes of every chain of the report, forgetting the previous report
+    pub fn query(
+        &mut self,
+        widget: &WhyReport,
+        adapter: &mut impl ServerAdapter,
+        dispatcher: mpsc::Sender<AppEvent>,
+    ) {
+        self.query_id += 1;
+        self.scroll_offset = 0;
+        self.causes = widget
+            .relations
+            .iter()
+            .map(|relation| {
+                let chains = relation.dependency_chains().len();
+                (0..chains)
+                    .map(|_| Causes::Loading(self.query_id))
+                    .collect()
+            })
+            .collect();
+
+        for (index, relation) in widget.relations.iter().enumerate() {
+            for chain in 0..relation.dependency_chains().len() {
+                let query_id = self.query_id;
+                let dispatcher = dispatcher.clone();
+
+                // The source and sink is reverse in this case
+                adapter.get_dependency_causes(
+                    &relation.path,
+                    &widget.changed,
+                    &relation.reason,
+                    chain,
+                    Box::new(move |causes| {
+                        dispatcher
+                            .send(AppEvent::GetWhyCausesDone(query_id, index, chain, causes))
+                            .unwrap();
+                    }),
+                );
+            }
+        }
+    }
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HandleEvent for State {
+    type Widget = WhyReport;
+
+    fn handle_event(
+        &mut self,
+        event: &AppEvent,
+        _widget: &Self::Widget,
+        _adapter: &mut impl ServerAdapter,
+        _dispatcher: mpsc::Sender<AppEvent>,
+    ) {
+        match event {
+            AppEvent::GetWhyCausesDone(query_id, relation, chain, causes)
+                if *query_id == self.query_id =>
+            {
+                let slot = self
+                    .causes
+                    .get_mut(*relation)
+                    .and_then(|chains| chains.get_mut(*chain));
+
+                if let Some(slot) = slot {
+                    *slot = match causes {
+                        Ok(causes) => Causes::Loaded(causes.clone()),
+                        Err(error) => Causes::Failed(format!("{:#}", error)),
+                    };
+                }
+            }
+
+            AppEvent::ScrollWhyReportDown if self.scroll_offset + 1 < self.lines_count => {
+                self.scroll_offset += 1;
+            }
+
+            AppEvent::ScrollWhyReportUp => {
+                self.scroll_offset = self.scroll_offset.saturating_sub(1);
+            }
+
+            _ => (),
+        }
+    }
+}
+
+impl StatefulWidget for WhyReport {
+    type State = State;
+
+    fn render(self, area: Rect, buf: &mut Buffer, state: &mut State) {
+        Clear.render(area, buf);
+
+        Block::default()
+            .borders(Borders::ALL)
+            .title("Why")
+            .border_type(BorderType::Rounded)
+            .border_style(theme::current().border)
+            .render(area, buf);
+
+        // The lines borrow from the state, so the state is only updated after rendering
+        let lines = self.lines(state);
+        let lines_count = lines.len();
+        let scroll_offset = state.scroll_offset.min(lines_count.saturating_sub(1));
+
+        Paragraph::new(lines)
+            .style(theme::current().text)
+            .scroll((scroll_offset as u16, 0))
+            .render(utils::padding(&area, 2, 1), buf);
+
+        state.lines_count = lines_count;
+        state.scroll_offset = scroll_offset;
+    }
+}
+
+impl WhyReport {
+    fn lines<'a>(&self, state: &'a State) -> Vec<Line<'a>> {
+        let mut lines = vec![
+            Line::from(vec![
+                Span::from("Why does "),
+                Span::styled(self.dependent.clone(), theme::current().title),
+                Span::from(" recompile when "),
+                Span::styled(self.changed.clone(), theme::current().title),
+                Span::from(" changes?"),
+            ]),
+            Line::from(""),
+        ];
+
+        if self.relations.is_empty() {
+            lines.push(Line::styled(
+                format!(
+                    "{} doesn't recompile when {} changes",
+                    self.dependent, self.changed
+                ),
+                bold(),
+            ));
+        }
+
+        for (index, relation) in self.relations.iter().enumerate() {
+            lines.push(relation_line(relation));
+
+            let chains = relation.dependency_chains();
+            for (chain_index, chain) in chains.iter().enumerate() {
+                lines.push(Line::from(""));
+                lines.push(Line::styled(
+                    format!("Path {}/{}", chain_index + 1, chains.len()),
+                    bold(),
+                ));
+
+                let causes = state
+                    .causes
+                    .get(index)
+                    .and_then(|chains| chains.get(chain_index));
+                lines.extend(chain_lines(chain, causes));
+            }
+
+            lines.push(Line::from(""));
+        }
+
+        if self.reverse {
+            lines.push(Line::styled(
+                format!(
+                    "{} recompiles when {} changes, see `why {} {}`",
+                    self.changed, self.dependent, self.changed, self.dependent
+                ),
+                theme::current().hint,
+            ));
+        }
+
+        lines
+    }
+}
+
+// The reason, whether it always recompiles and how many paths lead to it
+fn relation_line(relation: &RecomplileDependency) -> Line<'static> {
+    let (certainty, style) = if relation.reason.is_definite() {
+        ("always recompiles", theme::current().definite)
+    } else {
+        (
+            "recompiles if the exports change",
+            theme::current().indefinite,
+        )
+    };
+
+    let count = relation
+        .paths
+        .path_count
+        .max(relation.dependency_chains().len());
+    let shown = relation.dependency_chains().len();
+    let paths = match (count, relation.paths.more_paths) {
+        (_, true) => format!(
+            "more than {} paths, the {} shortest are shown",
+            count, shown
+        ),
+        (count, false) if count > shown => {
+            format!("{} paths, the {} shortest are shown", count, shown)
+        }
+        (1, false) => String::from("1 path"),
+        (count, false) => format!("{} paths", count),
+    };
+
+    Line::from(vec![
+        Span::styled(format!("● {}", relation.reason), style),
+        Span::from(format!(" – {}, {}", certainty, paths)),
+    ])
+}
+
+// Each link of the chain followed by the code causing it, once the server answered
+fn chain_lines<'a>(chain: &[DependencyLink], causes: Option<&'a Causes>) -> Vec<Line<'a>> {
+    let mut lines = vec![];
+
+    match causes {
+        Some(Causes::Failed(error)) => {
+            lines.push(Line::styled("Failed to get the dependency causes", bold()));
+            lines.push(Line::styled(error.clone(), theme::current().error));
+        }
+
+        Some(Causes::Loading(_)) | Some(Causes::NotRequested) | None => {
+            lines.push(Line::from(vec![
+                LoadingIcon::new().into(),
+                Span::from(" Loading dependency causes..."),
+            ]));
+        }
+
+        Some(Causes::Loaded(_)) => (),
+    }
+
+    for link in chain {
+        lines.push(Line::from(vec![
+            Span::from(format!("{} ─(", link.source)),
+            Span::styled(
+                link.dependency_type.to_string(),
+                theme::current().compile_link,
+            ),
+            Span::from(format!(")➤ {}", link.sink)),
+        ]));
+
+        if let Some(Causes::Loaded(causes)) = causes {
+            let cause = causes
+                .iter()
+                .find(|cause| cause.source == link.source && cause.sink == link.sink);
+
+            match cause {
+                Some(cause) if !cause.snippets.is_empty() => {
+                    lines.push(Line::from(""));
+                    lines.extend(cause.snippets.iter().flat_map(|snippet| {
+                        causes::code_snippet_text(cause.source.clone(), snippet)
+                    }));
+                }
+                _ => lines.push(Line::styled(
+                    causes::missing_cause_reason(link),
+                    theme::current().hint,
+                )),
+            }
+        }
+    }
+
+    lines
+}
+
+fn bold() -> Style {
+    Style::default().add_modifier(Modifier::BOLD)
+}
+
+#[cfg(test)]
+mod why_report_tests {
+    use super::*;
+    use crate::{DependencyCause, RecomplileDependencyReason};
+    use anyhow::Result;
+
+    // a recompiles when b changes, for two reasons
+    fn files() -> Vec<FileEntry> {
+        let relation =
+            |path: &str| RecomplileDependency::fixture(path).with_chain_through(&[path, "b"]);
+
+        vec![
+            FileEntry::fixture(
+                "b",
+                vec![
+                    relation("a"),
+                    relation("a").with_reason(RecomplileDependencyReason::Exports),
+                    relation("c"),
+                ],
+            ),
+            FileEntry::fixture("a", vec![]),
+        ]
+    }
+
+    // Records the requests rather than sending them
+    struct RecordingAdapter {
+        requests: Vec<(FilePath, FilePath, usize)>,
+    }
+
+    impl ServerAdapter for RecordingAdapter {
+        fn get_files(&mut self, _callback: Box<dyn FnOnce(Vec<FileEntry>)>) {}
+
+        fn get_dependency_causes(
+            &mut self,
+            source: &FilePath,
+            sink: &FilePath,
+            _reason: &RecomplileDependencyReason,
+            chain: usize,
+            _callback: Box<dyn FnOnce(Result<Vec<DependencyCause>>)>,
+        ) {
+            self.requests.push((source.clone(), sink.clone(), chain));
+        }
+    }
+
+    fn text(lines: &[Line]) -> Vec<String> {
+        lines
+            .iter()
+            .map(|line| {
+                line.spans
+                    .iter()
+                    .map(|span| span.content.as_ref())
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn every_relation_between_the_files() {
+        let report = WhyReport::new(String::from("a"), String::from("b"), &files());
+
+        let reasons: Vec<String> = report
+            .relations
+            .iter()
+            .map(|relation| relation.reason.to_string())
+            .collect();
+        assert_eq!(reasons, ["compile", "exports"]);
+        assert!(!report.reverse);
+
+        let reverse = WhyReport::new(String::from("b"), String::from("a"), &files());
+        assert!(reverse.relations.is_empty());
+        assert!(reverse.reverse);
+    }
+
+    #[test]
+    fn query_requests_the_causes_of_every_chain() {
+        let report = WhyReport::new(String::from("a"), String::from("b"), &files());
+        let mut adapter = RecordingAdapter { requests: vec![] };
+        let mut state = State::new();
+
+        let (tx, _rx) = mpsc::channel::<AppEvent>();
+        state.query(&report, &mut adapter, tx);
+
+        let request = (String::from("a"), String::from("b"), 0);
+        assert_eq!(adapter.requests, [request.clone(), request]);
+        assert_eq!(state.causes.len(), 2);
+    }
+
+    #[test]
+    fn responses_to_an_earlier_query_are_ignored() {
+        let report = WhyReport::new(String::from("a"), String::from("b"), &files());
+        let mut adapter = RecordingAdapter { requests: vec![] };
+        let mut state = State::new();
+
+        let (tx, _rx) = mpsc::channel::<AppEvent>();
+        state.query(&report, &mut adapter, tx.clone());
+        state.query(&report, &mut adapter, tx.clone());
+
+        for query_id in [1, 2] {
+            let event = AppEvent::GetWhyCausesDone(query_id, 0, 0, Ok(vec![]));
+            state.handle_event(&event, &report, &mut adapter, tx.clone());
+        }
+
+        assert!(matches!(state.causes[0][0], Causes::Loaded(_)));
+        assert!(matches!(state.causes[1][0], Causes::Loading(2)));
+
+        let lines = text(&report.lines(&state));
+        assert!(lines.contains(&String::from("a ─(compile)➤ b")));
+        assert!(lines.contains(&String::from(
+            "The server couldn't locate the code causing this link"
+        )));
+    }
+
+    #[test]
+    fn unrelated_files() {
+        let report = WhyReport::new(String::from("b"), String::from("a"), &files());
+        let lines = text(&report.lines(&State::new()));
+
+        assert_eq!(lines[2], "b doesn't recompile when a changes");
+        assert_eq!(lines[3], "a recompiles when b changes, see `why a b`");
+    }
+}
@@ -0,0 +1,236 @@
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::keymap::{self, Keymap};
+use crate::theme::{self, StyleOverride, Theme};
+
+/// Environment variable to point at a config file other than the default one
+pub const CONFIG_PATH_ENV: &str = "EX_COMPILE_GRAPH_CONFIG";
+
+/// User configuration, read from `<config dir>/ex_compile_graph/config.toml`
+///
+/// ```toml
+/// [keymap.global]
+/// move_down = ["C-n", "down"]
+/// move_up = ["C-p", "up"]
+///
+/// [keymap.file_panel]
+/// select = "right"
+///
+/// [theme]
+/// name = "light"
+/// selection = { fg = "black", bg = "#ffd75f", modifiers = ["bold"] }
+/// ```
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    keymap: HashMap<String, HashMap<String, Keys>>,
+    theme: ThemeConfig,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+struct ThemeConfig {
+    name: Option<String>,
+    // Style overrides, keyed by the theme field name
+    #[serde(flatten)]
+    styles: HashMap<String, StyleOverride>,
+}
+
+// A single key or a list of keys
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+enum Keys {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl Config {
+    pub fn parse(content: &str) -> Result<Self> {
+        Ok(toml::from_str(content)?)
+    }
+
+    pub fn keymap(&self) -> Result<Keymap> {
+        let overrides: HashMap<String, HashMap<String, Vec<String>>> = self
+            .keymap
+            .iter()
+            .map(|(scope, actions)| {
+                let actions = actions
+                    .iter()
+                    .map(|(action, keys)| {
+                        let keys = match keys {
+                            Keys::One(key) => vec![key.clone()],
+                            Keys::Many(keys) => keys.clone(),
+                        };
+
+                        (action.clone(), keys)
+                    })
+                    .collect();
+
+                (scope.clone(), actions)
+            })
+            .collect();
+
+        let keymap = Keymap::default().with_overrides(&overrides)?;
+        let conflicts = keymap.conflicts();
+
+        if conflicts.is_empty() {
+            Ok(keymap)
+        } else {
+            Err(anyhow!(
+                "Conflicting key bindings:\n  {}",
+                conflicts.join("\n  ")
+            ))
+        }
+    }
+
+    /// Returns the configured theme. NO_COLOR takes precedence over the config file
+    pub fn theme(&self, no_color: bool) -> Result<Theme> {
+        if no_color {
+            return Ok(Theme::monochrome());
+        }
+
+        let name = self.theme.name.as_deref().unwrap_or("dark");
+        Theme::from_name(name)?.with_overrides(&self.theme.styles)
+    }
+
+    /// Install the configuration for the rest of the program
+    pub fn install(&self) -> Result<()> {
+        keymap::init(self.keymap()?);
+        theme::init(self.theme(theme::no_color())?);
+        Ok(())
+    }
+}
+
+pub fn config_path() -> Option<PathBuf> {
+    match env::var(CONFIG_PATH_ENV) {
+        Ok(path) if !path.is_empty() => Some(PathBuf::from(path)),
+        _ => dirs::config_dir().map(|dir| dir.join("ex_compile_graph").join("config.toml")),
+    }
+}
+
+/// Load the config file. A missing config file is not an error, the defaults are used instead
+pub fn load() -> Result<Config> {
+    match config_path() {
+        Some(path) if path.exists() => {
+            let content = fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read config file {}", path.display()))?;
+
+            Config::parse(&content)
+                .with_context(|| format!("Invalid config file {}", path.display()))
+        }
+
+        _ => Ok(Config::default()),
+    }
+}
+
+#[cfg(test)]
+mod config_tests {
+    use super::*;
+    use crate::keymap::{Action, Scope};
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    #[test]
+    fn empty_config() {
+        let config = Config::parse("").unwrap();
+        let keymap = config.keymap().unwrap();
+
+        assert_eq!(keymap.bindings().len(), Keymap::default().bindings().len());
+    }
+
+    #[test]
+    fn keymap_overrides() {
+        let config = Config::parse(
+            r#"
+            [keymap.global]
+            move_down = ["C-n", "down"]
+
+            [keymap.file_panel]
+            select = "right"
+            "#,
+        )
+        .unwrap();
+        let keymap = config.keymap().unwrap();
+
+        assert_eq!(
+            keymap.action(
+                Scope::Global,
+                &KeyEvent::new(KeyCode::Char('n'), KeyModifiers::CONTROL)
+            ),
+            Some(Action::MoveDown)
+        );
+        assert_eq!(
+            keymap.action(
+                Scope::FilePanel,
+                &KeyEvent::new(KeyCode::Right, KeyModifiers::NONE)
+            ),
+            Some(Action::Select)
+        );
+    }
+
+    #[test]
+    fn conflicting_keymap() {
+        let config = Config::parse(
+            r#"
+            [keymap.global]
+            search = "k"
+            "#,
+        )
+        .unwrap();
+
+        let error = config.keymap().unwrap_err().to_string();
+        assert!(error.contains("k is bound to both global.move_up and global.search"));
+    }
+
+    #[test]
+    fn unknown_field() {
+        assert!(Config::parse("colour = true").is_err());
+    }
+
+    #[test]
+    fn theme() {
+        let config = Config::parse(
+            r#"
+            [theme]
+            name = "light"
+            count = { fg = "yellow" }
+            "#,
+        )
+        .unwrap();
+        let theme = config.theme(false).unwrap();
+
+        assert_eq!(theme.text, Theme::light().text);
+        assert_eq!(theme.count.fg, Some(ratatui::style::Color::Yellow));
+    }
+
+    #[test]
+    fn no_color_overrides_theme() {
+        let config = Config::parse(
+            r#"
+            [theme]
+            name = "light"
+            count = { fg = "yellow" }
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.theme(true).unwrap(), Theme::monochrome());
+    }
+
+    #[test]
+    fn unknown_theme() {
+        let config = Config::parse(
+            r#"
+            [theme]
+            name = "solarized"
+            "#,
+        )
+        .unwrap();
+
+        assert!(config.theme(false).is_err());
+    }
+}
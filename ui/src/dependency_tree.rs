@@ -0,0 +1,217 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::{FileEntry, FilePath};
+
+/// The dependents of a file as a tree: the children of a file are the files depending on it
+/// directly, through a compile or exports dependency. A file depended on by several files is
+/// only expanded where it is first reached, breadth first. Elsewhere it appears as a shared node,
+/// so that fanning out through the project doesn't repeat the same subtrees
+pub struct DependencyTree {
+    root: FilePath,
+    children: HashMap<FilePath, Vec<Node>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node {
+    pub path: FilePath,
+    // The subtree of the file is shown under another parent
+    pub shared: bool,
+    // Number of files recompiled when this one changes
+    pub dependents_count: usize,
+}
+
+/// A node as it is listed on screen
+#[derive(Debug, Clone, PartialEq)]
+pub struct Row {
+    pub node: Node,
+    pub depth: usize,
+    pub expandable: bool,
+    pub expanded: bool,
+}
+
+impl DependencyTree {
+    pub fn build(root: &FilePath, files: &[FileEntry]) -> Self {
+        let entries: HashMap<&str, &FileEntry> = files
+            .iter()
+            .map(|file| (file.path.as_str(), file))
+            .collect();
+
+        let mut reached = HashSet::from([root.clone()]);
+        let mut queue = VecDeque::from([root.clone()]);
+        let mut children = HashMap::new();
+
+        while let Some(path) = queue.pop_front() {
+            let nodes = direct_dependents(entries.get(path.as_str()).copied())
+                .into_iter()
+                .map(|dependent| {
+                    let shared = !reached.insert(dependent.clone());
+                    if !shared {
+                        queue.push_back(dependent.clone());
+                    }
+
+                    Node {
+                        dependents_count: dependents_count(
+                            entries.get(dependent.as_str()).copied(),
+                        ),
+                        path: dependent,
+                        shared,
+                    }
+                })
+                .collect();
+
+            children.insert(path, nodes);
+        }
+
+        Self {
+            root: root.clone(),
+            children,
+        }
+    }
+
+    pub fn root(&self) -> &FilePath {
+        &self.root
+    }
+
+    pub fn children(&self, path: &FilePath) -> &[Node] {
+        self.children.get(path).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    pub fn is_expandable(&self, node: &Node) -> bool {
+        !node.shared && !self.children(&node.path).is_empty()
+    }
+
+    /// The rows on screen, depth first, with the files of the set expanded
+    pub fn rows(&self, expanded: &HashSet<FilePath>) -> Vec<Row> {
+        let mut rows = vec![];
+        self.push_rows(&self.root, 0, expanded, &mut rows);
+        rows
+    }
+
+    fn push_rows(
+        &self,
+        path: &FilePath,
+        depth: usize,
+        expanded: &HashSet<FilePath>,
+        rows: &mut Vec<Row>,
+    ) {
+        for node in self.children(path) {
+            let expandable = self.is_expandable(node);
+            let is_expanded = expandable && expanded.contains(&node.path);
+
+            rows.push(Row {
+                node: node.clone(),
+                depth,
+                expandable,
+                expanded: is_expanded,
+            });
+
+            if is_expanded {
+                self.push_rows(&node.path, depth + 1, expanded, rows);
+            }
+        }
+    }
+}
+
+// A file can be a dependent for several reasons, it is listed once
+fn direct_dependents(entry: Option<&FileEntry>) -> Vec<FilePath> {
+    let mut dependents: Vec<FilePath> = entry
+        .map(|entry| entry.recompile_dependencies.as_slice())
+        .unwrap_or_default()
+        .iter()
+        .filter(|dependent| dependent.dependency_chain.len() == 1)
+        .map(|dependent| dependent.path.clone())
+        .collect();
+
+    dependents.sort();
+    dependents.dedup();
+    dependents
+}
+
+fn dependents_count(entry: Option<&FileEntry>) -> usize {
+    entry
+        .map(|entry| {
+            entry
+                .recompile_dependencies
+                .iter()
+                .map(|dependent| &dependent.path)
+                .collect::<HashSet<_>>()
+                .len()
+        })
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod dependency_tree_tests {
+    use super::*;
+    use crate::RecomplileDependency;
+
+    // The dependents of the file, each through a chain of files from the dependent to the file
+    fn file(path: &str, chains: &[&[&str]]) -> FileEntry {
+        let dependents = chains
+            .iter()
+            .map(|chain| {
+                let files = [*chain, &[path]].concat();
+                RecomplileDependency::fixture(chain[0]).with_chain_through(&files)
+            })
+            .collect();
+
+        FileEntry::fixture(path, dependents)
+    }
+
+    // b and c depend on a, d depends on both b and c, e depends on d
+    fn files() -> Vec<FileEntry> {
+        vec![
+            file(
+                "a",
+                &[&["b"], &["c"], &["d", "b"], &["d", "c"], &["e", "d", "b"]],
+            ),
+            file("b", &[&["d"], &["e", "d"]]),
+            file("c", &[&["d"], &["e", "d"]]),
+            file("d", &[&["e"]]),
+            file("e", &[]),
+        ]
+    }
+
+    fn paths(rows: &[Row]) -> Vec<(usize, &str, bool)> {
+        rows.iter()
+            .map(|row| (row.depth, row.node.path.as_str(), row.node.shared))
+            .collect()
+    }
+
+    #[test]
+    fn direct_dependents_are_children() {
+        let tree = DependencyTree::build(&String::from("a"), &files());
+        let rows = tree.rows(&HashSet::new());
+
+        assert_eq!(paths(&rows), vec![(0, "b", false), (0, "c", false)]);
+        assert_eq!(rows[0].node.dependents_count, 2);
+        assert!(rows[0].expandable);
+    }
+
+    #[test]
+    fn shared_subtrees_expand_once() {
+        let tree = DependencyTree::build(&String::from("a"), &files());
+        let expanded = HashSet::from([String::from("b"), String::from("c"), String::from("d")]);
+
+        assert_eq!(
+            paths(&tree.rows(&expanded)),
+            vec![
+                (0, "b", false),
+                (1, "d", false),
+                (2, "e", false),
+                (0, "c", false),
+                (1, "d", true),
+            ]
+        );
+    }
+
+    #[test]
+    fn cycles() {
+        let files = vec![file("a", &[&["b"]]), file("b", &[&["a"]])];
+        let tree = DependencyTree::build(&String::from("a"), &files);
+        let rows = tree.rows(&HashSet::from([String::from("b")]));
+
+        assert_eq!(paths(&rows), vec![(0, "b", false), (1, "a", true)]);
+        assert!(!rows[1].expandable);
+    }
+}
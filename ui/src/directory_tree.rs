@@ -0,0 +1,280 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::reason_filter::ReasonFilter;
+use crate::{FileEntry, FilePath};
+
+/// The files of the file panel grouped by directory. A folder sums up the files under it, so the
+/// contexts recompiling the most stand out before drilling into single files. Folders and files
+/// keep the order of the files list, a folder comes where its first file would be
+pub struct DirectoryTree {
+    files: Vec<FileEntry>,
+    // Number of unique dependents of each file
+    counts: Vec<usize>,
+    // The folders and files directly under each folder, the project root is ""
+    children: HashMap<FilePath, Vec<Node>>,
+    folders: HashMap<FilePath, Folder>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Folder(FilePath),
+    // Index of the file in the files list
+    File(usize),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Folder {
+    pub path: FilePath,
+    // Number of unique files recompiled when any file under the folder changes
+    pub unique_dependents: usize,
+    // The file with the most dependents under the folder, with its number of dependents
+    pub hotspot: Option<(usize, usize)>,
+}
+
+/// A folder or a file as it is listed on screen
+#[derive(Debug, Clone, PartialEq)]
+pub struct Row {
+    pub kind: RowKind,
+    pub depth: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RowKind {
+    Folder {
+        folder: Folder,
+        // Relative to the parent folder. A folder holding a single folder is listed along with
+        // it, e.g. `lib/my_app_web/`
+        name: String,
+        expanded: bool,
+    },
+    File(usize),
+}
+
+impl DirectoryTree {
+    /// The counts only include the dependents shown by the filter, if any
+    pub fn build(files: Vec<FileEntry>, filter: Option<&ReasonFilter>) -> Self {
+        let mut children: HashMap<FilePath, Vec<Node>> = HashMap::new();
+        let mut dependents: HashMap<FilePath, HashSet<&str>> = HashMap::new();
+        let mut hotspots: HashMap<FilePath, (usize, usize)> = HashMap::new();
+
+        let files_dependents: Vec<HashSet<&str>> = files
+            .iter()
+            .map(|file| {
+                file.recompile_dependencies
+                    .iter()
+                    .filter(|dependent| {
+                        filter.is_none_or(|filter| filter.is_shown(&dependent.reason))
+                    })
+                    .map(|dependent| dependent.path.as_str())
+                    .collect()
+            })
+            .collect();
+
+        let counts: Vec<usize> = files_dependents.iter().map(HashSet::len).collect();
+
+        for (index, file) in files.iter().enumerate() {
+            let mut parent = String::new();
+
+            for folder in ancestors(&file.path) {
+                if !children.contains_key(&folder) {
+                    children.insert(folder.clone(), vec![]);
+                    children
+                        .entry(parent.clone())
+                        .or_default()
+                        .push(Node::Folder(folder.clone()));
+                }
+
+                dependents
+                    .entry(folder.clone())
+                    .or_default()
+                    .extend(files_dependents[index].iter().copied());
+
+                // The first file listed wins a tie
+                let count = counts[index];
+                match hotspots.get(&folder) {
+                    Some(&(_, max)) if max >= count => (),
+                    _ if count == 0 => (),
+                    _ => {
+                        hotspots.insert(folder.clone(), (index, count));
+                    }
+                }
+
+                parent = folder;
+            }
+
+            children.entry(parent).or_default().push(Node::File(index));
+        }
+
+        let folders = dependents
+            .into_iter()
+            .map(|(path, dependents)| {
+                let folder = Folder {
+                    path: path.clone(),
+                    unique_dependents: dependents.len(),
+                    hotspot: hotspots.get(&path).copied(),
+                };
+
+                (path, folder)
+            })
+            .collect();
+
+        Self {
+            files,
+            counts,
+            children,
+            folders,
+        }
+    }
+
+    pub fn file(&self, index: usize) -> &FileEntry {
+        &self.files[index]
+    }
+
+    pub fn count(&self, index: usize) -> usize {
+        self.counts[index]
+    }
+
+    /// The rows on screen, depth first, with the folders of the set expanded
+    pub fn rows(&self, expanded: &HashSet<FilePath>) -> Vec<Row> {
+        let mut rows = vec![];
+        self.push_rows("", 0, expanded, &mut rows);
+        rows
+    }
+
+    fn children(&self, path: &str) -> &[Node] {
+        self.children.get(path).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    fn push_rows(
+        &self,
+        path: &str,
+        depth: usize,
+        expanded: &HashSet<FilePath>,
+        rows: &mut Vec<Row>,
+    ) {
+        for node in self.children(path) {
+            let mut folder = match node {
+                Node::File(index) => {
+                    rows.push(Row {
+                        kind: RowKind::File(*index),
+                        depth,
+                    });
+                    continue;
+                }
+                Node::Folder(folder) => folder,
+            };
+
+            while let [Node::Folder(only)] = self.children(folder) {
+                folder = only;
+            }
+
+            let is_expanded = expanded.contains(folder);
+            let name = folder[path.len()..].trim_start_matches('/');
+
+            rows.push(Row {
+                kind: RowKind::Folder {
+                    folder: self.folders[folder].clone(),
+                    name: format!("{}/", name),
+                    expanded: is_expanded,
+                },
+                depth,
+            });
+
+            if is_expanded {
+                self.push_rows(folder, depth + 1, expanded, rows);
+            }
+        }
+    }
+}
+
+// The folders holding the file, outermost first, e.g. `lib` and `lib/app` for `lib/app/user.ex`
+fn ancestors(path: &str) -> Vec<FilePath> {
+    path.match_indices('/')
+        .map(|(index, _)| path[..index].to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod directory_tree_tests {
+    use super::*;
+    use crate::{RecomplileDependency, RecomplileDependencyReason};
+
+    fn file(path: &str, dependents: &[&str]) -> FileEntry {
+        let dependents = dependents
+            .iter()
+            .map(|dependent| RecomplileDependency::fixture(dependent))
+            .collect();
+
+        FileEntry::fixture(path, dependents)
+    }
+
+    fn tree() -> DirectoryTree {
+        DirectoryTree::build(
+            vec![
+                file("lib/app_web/router.ex", &["a", "b", "c"]),
+                file("lib/app_web/live/page.ex", &["a"]),
+                file("lib/app/user.ex", &["a", "d"]),
+                file("mix.exs", &[]),
+            ],
+            None,
+        )
+    }
+
+    fn names(rows: &[Row]) -> Vec<String> {
+        rows.iter()
+            .map(|row| match row.kind {
+                RowKind::Folder { ref name, .. } => format!("{}{}", "  ".repeat(row.depth), name),
+                RowKind::File(index) => format!("{}{}", "  ".repeat(row.depth), index),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn single_folders_are_listed_together() {
+        let tree = DirectoryTree::build(
+            vec![
+                file("lib/app_web/router.ex", &[]),
+                file("lib/app_web/endpoint.ex", &[]),
+            ],
+            None,
+        );
+        let expanded = HashSet::from([String::from("lib/app_web")]);
+
+        assert_eq!(names(&tree.rows(&expanded)), ["lib/app_web/", "  0", "  1"]);
+    }
+
+    #[test]
+    fn expanded_folders() {
+        let tree = tree();
+        let expanded = HashSet::from([String::from("lib"), String::from("lib/app_web")]);
+
+        assert_eq!(
+            names(&tree.rows(&expanded)),
+            ["lib/", "  app_web/", "    0", "    live/", "  app/", "3"]
+        );
+    }
+
+    #[test]
+    fn folder_aggregates() {
+        let tree = tree();
+
+        let lib = &tree.folders["lib"];
+        assert_eq!(lib.unique_dependents, 4);
+        assert_eq!(lib.hotspot, Some((0, 3)));
+
+        let live = &tree.folders["lib/app_web/live"];
+        assert_eq!(live.unique_dependents, 1);
+        assert_eq!(live.hotspot, Some((1, 1)));
+    }
+
+    #[test]
+    fn filtered_counts() {
+        let mut filter = ReasonFilter::new();
+        filter.toggle(&RecomplileDependencyReason::Compile);
+
+        let tree = DirectoryTree::build(vec![file("lib/app.ex", &["a"])], Some(&filter));
+
+        assert_eq!(tree.count(0), 0);
+        assert_eq!(tree.folders["lib"].hotspot, None);
+    }
+}
@@ -0,0 +1,185 @@
+use std::env;
+use std::io;
+use std::path::Path;
+use std::process::ExitStatus;
+
+/// Environment variable holding a custom editor command. The command may contain the `{file}`
+/// and `{line}` placeholders, e.g. `EX_COMPILE_GRAPH_EDITOR="code --goto {file}:{line}"`
+pub const EDITOR_COMMAND_ENV: &str = "EX_COMPILE_GRAPH_EDITOR";
+
+const DEFAULT_EDITOR: &str = "vi";
+
+/// Build the command to open a file at a specific line. The editor is resolved from
+/// EX_COMPILE_GRAPH_EDITOR, $VISUAL and $EDITOR in that order. Returns the program and its
+/// arguments
+pub fn editor_command(file: &str, line: usize) -> (String, Vec<String>) {
+    if let Ok(template) = env::var(EDITOR_COMMAND_ENV) {
+        if !template.trim().is_empty() {
+            return build_command(&template, file, line);
+        }
+    }
+
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .ok()
+        .filter(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_EDITOR.to_string());
+
+    build_command(&editor, file, line)
+}
+
+/// Build the command from an editor command string. If the command has `{file}` or `{line}`
+/// placeholders, they are substituted. Otherwise, the line syntax is inferred from the editor
+/// program name
+pub fn build_command(editor: &str, file: &str, line: usize) -> (String, Vec<String>) {
+    let mut parts: Vec<String> = editor.split_whitespace().map(String::from).collect();
+    let program = parts.remove(0);
+
+    let has_placeholder = parts
+        .iter()
+        .any(|part| part.contains("{file}") || part.contains("{line}"));
+
+    if has_placeholder {
+        let args = parts
+            .into_iter()
+            .map(|part| {
+                part.replace("{file}", file)
+                    .replace("{line}", &line.to_string())
+            })
+            .collect();
+
+        return (program, args);
+    }
+
+    let program_name = Path::new(&program)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(&program)
+        .to_string();
+
+    let mut line_args = match program_name.as_str() {
+        "code" | "code-insiders" | "codium" | "cursor" => {
+            vec![String::from("--goto"), format!("{}:{}", file, line)]
+        }
+
+        "subl" | "zed" | "hx" | "helix" => vec![format!("{}:{}", file, line)],
+
+        "idea" | "kate" | "clion" | "rubymine" | "webstorm" => {
+            vec![String::from("--line"), line.to_string(), file.to_string()]
+        }
+
+        // vi, vim, nvim, nano, emacs, micro, kak and most terminal editors
+        _ => vec![format!("+{}", line), file.to_string()],
+    };
+
+    parts.append(&mut line_args);
+    (program, parts)
+}
+
+/// Why the editor failed to open the file, None when it exited successfully
+pub fn editor_failure(program: &str, status: &io::Result<ExitStatus>) -> Option<String> {
+    match status {
+        Ok(status) if status.success() => None,
+        Ok(status) => Some(format!("Editor {} exited with {}", program, status)),
+        Err(error) => Some(format!("Failed to start editor {}: {}", program, error)),
+    }
+}
+
+#[cfg(test)]
+mod build_command_tests {
+    use super::*;
+
+    fn command(editor: &str) -> (String, Vec<String>) {
+        build_command(editor, "lib/foo.ex", 12)
+    }
+
+    #[test]
+    fn vim() {
+        assert_eq!(
+            command("vim"),
+            (
+                String::from("vim"),
+                vec![String::from("+12"), String::from("lib/foo.ex")]
+            )
+        );
+    }
+
+    #[test]
+    fn program_with_path_and_args() {
+        assert_eq!(
+            command("/usr/bin/nvim -u NONE"),
+            (
+                String::from("/usr/bin/nvim"),
+                vec![
+                    String::from("-u"),
+                    String::from("NONE"),
+                    String::from("+12"),
+                    String::from("lib/foo.ex")
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn vscode() {
+        assert_eq!(
+            command("code --wait"),
+            (
+                String::from("code"),
+                vec![
+                    String::from("--wait"),
+                    String::from("--goto"),
+                    String::from("lib/foo.ex:12")
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn file_colon_line_editors() {
+        assert_eq!(
+            command("hx"),
+            (String::from("hx"), vec![String::from("lib/foo.ex:12")])
+        );
+    }
+
+    #[test]
+    fn placeholders() {
+        assert_eq!(
+            command("myeditor --open={file} --at {line}"),
+            (
+                String::from("myeditor"),
+                vec![
+                    String::from("--open=lib/foo.ex"),
+                    String::from("--at"),
+                    String::from("12")
+                ]
+            )
+        );
+    }
+}
+
+#[cfg(test)]
+mod editor_failure_tests {
+    use super::*;
+    use std::process::Command;
+
+    #[test]
+    fn missing_editor() {
+        let status = Command::new("no-such-editor-program").status();
+        let failure = editor_failure("no-such-editor-program", &status).unwrap();
+
+        assert!(failure.starts_with("Failed to start editor no-such-editor-program: "));
+    }
+
+    #[test]
+    fn editor_exit_status() {
+        assert_eq!(editor_failure("true", &Command::new("true").status()), None);
+
+        let failure = editor_failure("false", &Command::new("false").status());
+        assert_eq!(
+            failure,
+            Some(String::from("Editor false exited with exit status: 1"))
+        );
+    }
+}
@@ -0,0 +1,131 @@
+use std::collections::BTreeSet;
+
+use crate::impact::Impact;
+use crate::FileEntry;
+
+/// Render the dependency links of the files as a Graphviz graph. Links appearing in several
+/// dependency chains are only drawn once
+pub fn dot(files: &[FileEntry]) -> String {
+    let links: BTreeSet<(&str, &str, String)> = files
+        .iter()
+        .flat_map(|file| &file.recompile_dependencies)
+        .flat_map(|dependency| &dependency.dependency_chain)
+        .map(|link| {
+            (
+                link.source.as_str(),
+                link.sink.as_str(),
+                link.dependency_type.to_string(),
+            )
+        })
+        .collect();
+
+    let mut output = String::from("digraph dependencies {\n");
+
+    for (source, sink, dependency_type) in links {
+        output.push_str(&format!(
+            "  {:?} -> {:?} [label={:?}];\n",
+            source, sink, dependency_type
+        ));
+    }
+
+    output.push_str("}\n");
+    output
+}
+
+/// Render the files recompiled when the changed files of a diff against `base` change, one file
+/// per line with the changed files triggering it
+pub fn impact(impact: &Impact, base: &str) -> String {
+    let mut output = format!("Changed against {}: {} files\n", base, impact.changed.len());
+    for path in &impact.changed {
+        output.push_str(&format!("  {}\n", path));
+    }
+
+    let (definite, indefinite) = impact.counts();
+    output.push_str(&format!(
+        "\nRecompiled: {} files, {} definite, {} indefinite\n",
+        impact.affected.len(),
+        definite,
+        indefinite
+    ));
+
+    for file in &impact.affected {
+        let certainty = if file.is_definite() {
+            "definite"
+        } else {
+            "indefinite"
+        };
+        let triggers: Vec<String> = file
+            .triggers
+            .iter()
+            .map(|(source, reason)| format!("{} ({})", source, reason))
+            .collect();
+
+        output.push_str(&format!(
+            "  {: <10} {} <- {}\n",
+            certainty,
+            file.path,
+            triggers.join(", ")
+        ));
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod export_tests {
+    use super::*;
+    use crate::{DependencyLink, DependencyType, RecomplileDependency, RecomplileDependencyReason};
+
+    fn link(source: &str, sink: &str, dependency_type: DependencyType) -> DependencyLink {
+        DependencyLink {
+            dependency_type,
+            source: source.to_string(),
+            sink: sink.to_string(),
+        }
+    }
+
+    #[test]
+    fn dot_graph() {
+        let files = vec![FileEntry::fixture(
+            "lib/a.ex",
+            vec![
+                RecomplileDependency::fixture("lib/c.ex").with_chain(vec![
+                    link("lib/c.ex", "lib/b.ex", DependencyType::Compile),
+                    link("lib/b.ex", "lib/a.ex", DependencyType::Exports),
+                ]),
+                RecomplileDependency::fixture("lib/b.ex").with_chain(vec![link(
+                    "lib/b.ex",
+                    "lib/a.ex",
+                    DependencyType::Exports,
+                )]),
+            ],
+        )];
+
+        assert_eq!(
+            dot(&files),
+            "digraph dependencies {\n  \"lib/b.ex\" -> \"lib/a.ex\" [label=\"exports\"];\n  \"lib/c.ex\" -> \"lib/b.ex\" [label=\"compile\"];\n}\n"
+        );
+    }
+
+    #[test]
+    fn impact_text() {
+        let files = vec![
+            FileEntry::fixture(
+                "lib/a.ex",
+                vec![
+                    RecomplileDependency::fixture("lib/c.ex")
+                        .with_reason(RecomplileDependencyReason::Exports),
+                    RecomplileDependency::fixture("lib/d.ex")
+                        .with_reason(RecomplileDependencyReason::Exports),
+                ],
+            ),
+            FileEntry::fixture("lib/b.ex", vec![RecomplileDependency::fixture("lib/c.ex")]),
+        ];
+
+        let changed = [String::from("lib/a.ex"), String::from("lib/b.ex")];
+        assert_eq!(
+            impact(&Impact::compute(&changed, &files), "main"),
+            "Changed against main: 2 files\n  lib/a.ex\n  lib/b.ex\n\nRecompiled: 2 files, 1 definite, 1 indefinite\n  definite   lib/c.ex <- lib/a.ex (exports), lib/b.ex (compile)\n  indefinite lib/d.ex <- lib/a.ex (exports)\n"
+        );
+    }
+}
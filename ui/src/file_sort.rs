@@ -0,0 +1,266 @@
+use std::cmp::{Ordering, Reverse};
+
+use crate::{FileEntry, RecomplileDependencyReason};
+use RecomplileDependencyReason::{Compile, Exports};
+
+/// The order of the files in the file panel. Modes counting something list the highest counts
+/// first, the others are alphabetical
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum FileSort {
+    // Number of recompile dependencies, the order of the server
+    #[default]
+    Count,
+    // Number of dependents recompiled for a compile time dependency only
+    Compile,
+    // Number of dependents recompiled because of exports only
+    Exports,
+    Path,
+    // Files grouped by directory, the most dependents first within each directory
+    Directory,
+    // Length of the longest dependency chain to a dependent
+    ChainLength,
+}
+
+impl FileSort {
+    pub const ALL: [Self; 6] = [
+        Self::Count,
+        Self::Compile,
+        Self::Exports,
+        Self::Path,
+        Self::Directory,
+        Self::ChainLength,
+    ];
+
+    /// The name used in the `sort` command and in the panel title
+    pub fn name(&self) -> &'static str {
+        match self {
+            FileSort::Count => "count",
+            FileSort::Compile => "compile",
+            FileSort::Exports => "exports",
+            FileSort::Path => "path",
+            FileSort::Directory => "directory",
+            FileSort::ChainLength => "chain",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().find(|sort| sort.name() == name).copied()
+    }
+
+    /// The mode after this one, wrapping around
+    pub fn next(&self) -> Self {
+        let index = Self::ALL.iter().position(|sort| sort == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    /// Sort the files, the order of the server is kept between files which compare equal. Only
+    /// the comparison is reversed, so that is also the case in the reversed order
+    pub fn sort(&self, files: &mut [FileEntry], reversed: bool) {
+        let order = |ordering: Ordering| {
+            if reversed {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        };
+
+        match self {
+            FileSort::Count => sort_by_count(files, reversed, dependents_count),
+            FileSort::Compile => sort_by_count(files, reversed, |file| reason_count(file, Compile)),
+            FileSort::Exports => sort_by_count(files, reversed, |file| reason_count(file, Exports)),
+            FileSort::Path => files.sort_by(|a, b| order(a.path.cmp(&b.path))),
+            FileSort::Directory => files.sort_by(|a, b| {
+                let by_directory = directory(&a.path).cmp(directory(&b.path));
+                let by_count = dependents_count(b).cmp(&dependents_count(a));
+                order(by_directory.then(by_count))
+            }),
+            FileSort::ChainLength => sort_by_count(files, reversed, longest_chain),
+        }
+    }
+}
+
+// The highest counts first. Counting can go through all the dependents, so each file is only
+// counted once
+fn sort_by_count(files: &mut [FileEntry], reversed: bool, count: impl Fn(&FileEntry) -> usize) {
+    if reversed {
+        files.sort_by_cached_key(|file| count(file));
+    } else {
+        files.sort_by_cached_key(|file| Reverse(count(file)));
+    }
+}
+
+fn dependents_count(file: &FileEntry) -> usize {
+    file.recompile_dependencies.len()
+}
+
+fn reason_count(file: &FileEntry, reason: RecomplileDependencyReason) -> usize {
+    file.recompile_dependencies
+        .iter()
+        .filter(|dependent| dependent.reason == reason)
+        .count()
+}
+
+fn longest_chain(file: &FileEntry) -> usize {
+    file.recompile_dependencies
+        .iter()
+        .map(|dependent| dependent.dependency_chain.len())
+        .max()
+        .unwrap_or(0)
+}
+
+fn directory(path: &str) -> &str {
+    path.rsplit_once('/')
+        .map(|(directory, _)| directory)
+        .unwrap_or("")
+}
+
+#[cfg(test)]
+mod file_sort_tests {
+    use super::*;
+    use crate::RecomplileDependency;
+
+    // A dependent through a chain of up to 3 links
+    fn dependent(
+        path: &str,
+        reason: RecomplileDependencyReason,
+        chain_length: usize,
+    ) -> RecomplileDependency {
+        let chain = ["lib/d.ex", "lib/c.ex", "lib/b.ex", "lib/a.ex"];
+        RecomplileDependency::fixture(path)
+            .with_reason(reason)
+            .with_chain_through(&chain[..=chain_length])
+    }
+
+    fn files() -> Vec<FileEntry> {
+        vec![
+            FileEntry::fixture(
+                "lib/web/router.ex",
+                vec![
+                    dependent("lib/a.ex", Compile, 1),
+                    dependent("lib/b.ex", Compile, 1),
+                    dependent("lib/a.ex", Exports, 1),
+                ],
+            ),
+            FileEntry::fixture("lib/app.ex", vec![dependent("lib/a.ex", Exports, 3)]),
+            FileEntry::fixture(
+                "lib/web.ex",
+                vec![
+                    dependent("lib/a.ex", Compile, 2),
+                    dependent("lib/b.ex", Exports, 1),
+                ],
+            ),
+            FileEntry::fixture("lib/web/endpoint.ex", vec![]),
+        ]
+    }
+
+    fn sorted_paths(sort: FileSort, reversed: bool) -> Vec<String> {
+        let mut files = files();
+        sort.sort(&mut files, reversed);
+        files.into_iter().map(|file| file.path).collect()
+    }
+
+    #[test]
+    fn sort_modes() {
+        assert_eq!(
+            sorted_paths(FileSort::Count, false),
+            [
+                "lib/web/router.ex",
+                "lib/web.ex",
+                "lib/app.ex",
+                "lib/web/endpoint.ex"
+            ]
+        );
+        assert_eq!(
+            sorted_paths(FileSort::Compile, false),
+            [
+                "lib/web/router.ex",
+                "lib/web.ex",
+                "lib/app.ex",
+                "lib/web/endpoint.ex"
+            ]
+        );
+        assert_eq!(
+            sorted_paths(FileSort::Exports, false),
+            [
+                "lib/web/router.ex",
+                "lib/app.ex",
+                "lib/web.ex",
+                "lib/web/endpoint.ex"
+            ]
+        );
+        assert_eq!(
+            sorted_paths(FileSort::Path, false),
+            [
+                "lib/app.ex",
+                "lib/web.ex",
+                "lib/web/endpoint.ex",
+                "lib/web/router.ex"
+            ]
+        );
+        assert_eq!(
+            sorted_paths(FileSort::Directory, false),
+            [
+                "lib/web.ex",
+                "lib/app.ex",
+                "lib/web/router.ex",
+                "lib/web/endpoint.ex"
+            ]
+        );
+        assert_eq!(
+            sorted_paths(FileSort::ChainLength, false),
+            [
+                "lib/app.ex",
+                "lib/web.ex",
+                "lib/web/router.ex",
+                "lib/web/endpoint.ex"
+            ]
+        );
+    }
+
+    #[test]
+    fn reversed() {
+        assert_eq!(
+            sorted_paths(FileSort::Path, true),
+            [
+                "lib/web/router.ex",
+                "lib/web/endpoint.ex",
+                "lib/web.ex",
+                "lib/app.ex"
+            ]
+        );
+    }
+
+    #[test]
+    fn reversed_keeps_the_order_of_ties() {
+        assert_eq!(
+            sorted_paths(FileSort::Exports, true),
+            [
+                "lib/web/endpoint.ex",
+                "lib/web/router.ex",
+                "lib/app.ex",
+                "lib/web.ex"
+            ]
+        );
+        assert_eq!(
+            sorted_paths(FileSort::Directory, true),
+            [
+                "lib/web/endpoint.ex",
+                "lib/web/router.ex",
+                "lib/app.ex",
+                "lib/web.ex"
+            ]
+        );
+    }
+
+    #[test]
+    fn cycle() {
+        let mut sort = FileSort::default();
+        for _ in 0..FileSort::ALL.len() {
+            sort = sort.next();
+        }
+
+        assert_eq!(sort, FileSort::Count);
+        assert_eq!(FileSort::Count.next(), FileSort::Compile);
+        assert_eq!(FileSort::from_name("chain"), Some(FileSort::ChainLength));
+    }
+}
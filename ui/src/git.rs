@@ -0,0 +1,89 @@
+use anyhow::{anyhow, Context, Result};
+use std::path::Path;
+use std::process::Command;
+
+use crate::{FileEntry, FilePath};
+
+/// The Elixir sources changed against a git ref, listed by the changed files panel
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diff {
+    pub base: String,
+    pub files: Vec<FilePath>,
+}
+
+/// The files changed in the working tree since `base`, relative to the project directory. Files
+/// outside of the project are left out
+pub fn changed_files(project_dir: &Path, base: &str) -> Result<Vec<FilePath>> {
+    // The ref would be taken as an option by git
+    if base.starts_with('-') {
+        return Err(anyhow!("Invalid git ref {:?}", base));
+    }
+
+    let output = Command::new("git")
+        .args(["diff", "--name-only", "--relative", base, "--"])
+        .current_dir(project_dir)
+        .output()
+        .context("Failed to run git")?;
+
+    if !output.status.success() {
+        let error = String::from_utf8_lossy(&output.stderr);
+        return Err(anyhow!("git diff failed: {}", error.trim()));
+    }
+
+    Ok(parse_name_only(&String::from_utf8_lossy(&output.stdout)))
+}
+
+fn parse_name_only(output: &str) -> Vec<FilePath> {
+    output
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// The changed Elixir source files which are part of the dependency graph, in the order of the
+/// diff. Scripts, configs and files the graph doesn't know yet can't make anything recompile
+pub fn changed_sources(changed: &[FilePath], files: &[FileEntry]) -> Vec<FilePath> {
+    changed
+        .iter()
+        .filter(|path| path.ends_with(".ex"))
+        .filter(|path| files.iter().any(|file| file.path == **path))
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod git_tests {
+    use super::*;
+
+    #[test]
+    fn parse_diff_output() {
+        assert_eq!(
+            parse_name_only("lib/a.ex\nmix.exs\n\n"),
+            [String::from("lib/a.ex"), String::from("mix.exs")]
+        );
+        assert!(parse_name_only("").is_empty());
+    }
+
+    #[test]
+    fn only_sources_of_the_graph() {
+        let files: Vec<FileEntry> = ["lib/a.ex", "lib/b.ex", "mix.exs"]
+            .iter()
+            .map(|path| FileEntry::fixture(path, vec![]))
+            .collect();
+
+        let changed =
+            ["mix.exs", "lib/b.ex", "lib/new.ex", "README.md", "lib/a.ex"].map(String::from);
+        assert_eq!(
+            changed_sources(&changed, &files),
+            [String::from("lib/b.ex"), String::from("lib/a.ex")]
+        );
+    }
+
+    #[test]
+    fn ref_is_not_an_option() {
+        let error = changed_files(Path::new("."), "--output=x").unwrap_err();
+        assert_eq!(error.to_string(), "Invalid git ref \"--output=x\"");
+    }
+}
@@ -0,0 +1,386 @@
+use ratatui::style::Style;
+
+use crate::theme;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TokenKind {
+    Plain,
+    Keyword,
+    Atom,
+    ModuleAlias,
+    String,
+    Sigil,
+    Comment,
+}
+
+impl TokenKind {
+    pub fn style(&self) -> Style {
+        let theme = theme::current();
+
+        match self {
+            TokenKind::Plain => Style::default(),
+            TokenKind::Keyword => theme.keyword,
+            TokenKind::Atom => theme.atom,
+            TokenKind::ModuleAlias => theme.module_alias,
+            TokenKind::String => theme.string,
+            TokenKind::Sigil => theme.sigil,
+            TokenKind::Comment => theme.comment,
+        }
+    }
+}
+
+const KEYWORDS: &[&str] = &[
+    "after",
+    "alias",
+    "and",
+    "case",
+    "catch",
+    "cond",
+    "def",
+    "defdelegate",
+    "defexception",
+    "defguard",
+    "defguardp",
+    "defimpl",
+    "defmacro",
+    "defmacrop",
+    "defmodule",
+    "defoverridable",
+    "defp",
+    "defprotocol",
+    "defstruct",
+    "do",
+    "else",
+    "end",
+    "false",
+    "fn",
+    "for",
+    "if",
+    "import",
+    "in",
+    "nil",
+    "not",
+    "or",
+    "quote",
+    "raise",
+    "receive",
+    "require",
+    "rescue",
+    "true",
+    "try",
+    "unless",
+    "unquote",
+    "use",
+    "when",
+    "with",
+];
+
+/// Tokenize Elixir source code and split the tokens into lines. Tokens which span multiple
+/// lines (heredocs, multiline strings and sigils) are split at every line break, so each
+/// returned line can be rendered independently
+pub fn highlight_elixir(source: &str) -> Vec<Vec<(TokenKind, String)>> {
+    let mut lines = vec![vec![]];
+
+    for (kind, text) in tokenize(source) {
+        for (index, segment) in text.split('\n').enumerate() {
+            if index > 0 {
+                lines.push(vec![]);
+            }
+
+            if !segment.is_empty() {
+                lines.last_mut().unwrap().push((kind, segment.to_string()));
+            }
+        }
+    }
+
+    lines
+}
+
+fn tokenize(source: &str) -> Vec<(TokenKind, String)> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens: Vec<(TokenKind, String)> = vec![];
+    let mut index = 0;
+
+    while index < chars.len() {
+        let char = chars[index];
+        let previous = if index > 0 {
+            Some(chars[index - 1])
+        } else {
+            None
+        };
+
+        let (kind, end) = match char {
+            '#' => (TokenKind::Comment, scan_until_line_end(&chars, index)),
+            '"' | '\'' => (TokenKind::String, scan_string(&chars, index)),
+
+            '~' if sigil_delimiter_index(&chars, index).is_some() => {
+                let delimiter_index = sigil_delimiter_index(&chars, index).unwrap();
+                let end = scan_string(&chars, delimiter_index);
+                (
+                    TokenKind::Sigil,
+                    scan_while(&chars, end, |c| c.is_ascii_alphabetic()),
+                )
+            }
+
+            ':' if !matches!(previous, Some(':')) && is_atom_start(&chars, index + 1) => {
+                match chars[index + 1] {
+                    '"' | '\'' => (TokenKind::Atom, scan_string(&chars, index + 1)),
+                    _ => (TokenKind::Atom, scan_identifier(&chars, index + 1)),
+                }
+            }
+
+            c if c.is_ascii_uppercase() && !is_identifier_char(previous) => {
+                (TokenKind::ModuleAlias, scan_alias(&chars, index))
+            }
+
+            c if is_identifier_start(c) && !is_identifier_char(previous) => {
+                let end = scan_identifier(&chars, index);
+                let word: String = chars[index..end].iter().collect();
+
+                // Keyword list keys, e.g. `do: value`, are atoms
+                if chars.get(end) == Some(&':')
+                    && chars.get(end + 1).is_none_or(|c| c.is_whitespace())
+                {
+                    (TokenKind::Atom, end + 1)
+                } else if KEYWORDS.contains(&word.as_str()) && !matches!(previous, Some('.')) {
+                    (TokenKind::Keyword, end)
+                } else {
+                    (TokenKind::Plain, end)
+                }
+            }
+
+            c if c.is_ascii_digit() => (TokenKind::Plain, scan_identifier(&chars, index)),
+            _ => (TokenKind::Plain, index + 1),
+        };
+
+        let text: String = chars[index..end].iter().collect();
+
+        match tokens.last_mut() {
+            Some((last_kind, last_text)) if *last_kind == kind => last_text.push_str(&text),
+            _ => tokens.push((kind, text)),
+        }
+
+        index = end;
+    }
+
+    tokens
+}
+
+fn is_identifier_start(char: char) -> bool {
+    char.is_alphabetic() || char == '_'
+}
+
+fn is_identifier_char(char: Option<char>) -> bool {
+    match char {
+        Some(c) => c.is_alphanumeric() || c == '_' || c == '@',
+        None => false,
+    }
+}
+
+fn is_atom_start(chars: &[char], index: usize) -> bool {
+    match chars.get(index) {
+        Some(c) => is_identifier_start(*c) || *c == '"' || *c == '\'',
+        None => false,
+    }
+}
+
+fn scan_while(chars: &[char], start: usize, predicate: impl Fn(char) -> bool) -> usize {
+    let mut index = start;
+    while index < chars.len() && predicate(chars[index]) {
+        index += 1;
+    }
+
+    index
+}
+
+fn scan_until_line_end(chars: &[char], start: usize) -> usize {
+    scan_while(chars, start, |c| c != '\n')
+}
+
+// Identifiers may end with ? or !, e.g. `valid?` or `update!`
+fn scan_identifier(chars: &[char], start: usize) -> usize {
+    let end = scan_while(chars, start, |c| c.is_alphanumeric() || c == '_');
+
+    match chars.get(end) {
+        Some('?') | Some('!') => end + 1,
+        _ => end,
+    }
+}
+
+// Module aliases can be nested, e.g. `Foo.Bar.Baz`
+fn scan_alias(chars: &[char], start: usize) -> usize {
+    let mut end = scan_while(chars, start, |c| c.is_alphanumeric() || c == '_');
+
+    while chars.get(end) == Some(&'.') && chars.get(end + 1).is_some_and(|c| c.is_ascii_uppercase())
+    {
+        end = scan_while(chars, end + 1, |c| c.is_alphanumeric() || c == '_');
+    }
+
+    end
+}
+
+// Returns the index of the opening delimiter if a sigil starts at the given index
+fn sigil_delimiter_index(chars: &[char], start: usize) -> Option<usize> {
+    let name_end = match chars.get(start + 1) {
+        Some(c) if c.is_ascii_lowercase() => start + 2,
+        Some(c) if c.is_ascii_uppercase() => scan_while(chars, start + 1, |c| {
+            c.is_ascii_uppercase() || c.is_ascii_digit()
+        }),
+        _ => return None,
+    };
+
+    match chars.get(name_end) {
+        Some('/' | '|' | '"' | '\'' | '(' | '[' | '{' | '<') => Some(name_end),
+        _ => None,
+    }
+}
+
+// Scan a delimited literal starting at its opening delimiter. Handles escaping and
+// triple-quoted heredocs. Returns the index right after the closing delimiter, or the end of
+// the input if the literal is unterminated
+fn scan_string(chars: &[char], start: usize) -> usize {
+    let open = chars[start];
+    let heredoc = (open == '"' || open == '\'')
+        && chars.get(start + 1) == Some(&open)
+        && chars.get(start + 2) == Some(&open);
+
+    if heredoc {
+        let mut index = start + 3;
+        while index < chars.len() {
+            if chars[index] == '\\' {
+                index += 2;
+                continue;
+            }
+
+            if chars[index..].starts_with(&[open, open, open]) {
+                return index + 3;
+            }
+
+            index += 1;
+        }
+
+        return chars.len();
+    }
+
+    let close = match open {
+        '(' => ')',
+        '[' => ']',
+        '{' => '}',
+        '<' => '>',
+        c => c,
+    };
+
+    let mut index = start + 1;
+    while index < chars.len() {
+        match chars[index] {
+            '\\' => index += 2,
+            c if c == close => return index + 1,
+            _ => index += 1,
+        }
+    }
+
+    chars.len()
+}
+
+#[cfg(test)]
+mod highlight_elixir_tests {
+    use super::*;
+
+    fn kinds_of(source: &str, kind: TokenKind) -> Vec<String> {
+        highlight_elixir(source)
+            .into_iter()
+            .flatten()
+            .filter(|(k, _)| *k == kind)
+            .map(|(_, text)| text)
+            .collect()
+    }
+
+    #[test]
+    fn keywords() {
+        let source = "defmodule Foo do\n  def bar, do: nil\nend";
+        assert_eq!(
+            kinds_of(source, TokenKind::Keyword),
+            vec!["defmodule", "do", "def", "nil", "end"]
+        );
+    }
+
+    #[test]
+    fn keyword_as_function_call_is_not_keyword() {
+        assert_eq!(
+            kinds_of("Kernel.if(true)", TokenKind::Keyword),
+            vec!["true"]
+        );
+    }
+
+    #[test]
+    fn atoms() {
+        let source = "foo(:bar, :\"quoted atom\", baz?: 1, do: :ok)";
+        assert_eq!(
+            kinds_of(source, TokenKind::Atom),
+            vec![":bar", ":\"quoted atom\"", "baz?:", "do:", ":ok"]
+        );
+    }
+
+    #[test]
+    fn type_operator_is_not_atom() {
+        assert!(kinds_of("@spec foo() :: atom()", TokenKind::Atom).is_empty());
+    }
+
+    #[test]
+    fn module_aliases() {
+        let source = "alias Foo.Bar.Baz\n%MyStruct{}";
+        assert_eq!(
+            kinds_of(source, TokenKind::ModuleAlias),
+            vec!["Foo.Bar.Baz", "MyStruct"]
+        );
+    }
+
+    #[test]
+    fn strings() {
+        let source = r#"x = "escaped \" quote" <> 'charlist'"#;
+        assert_eq!(
+            kinds_of(source, TokenKind::String),
+            vec![r#""escaped \" quote""#, "'charlist'"]
+        );
+    }
+
+    #[test]
+    fn sigils() {
+        let source = "~r/foo|bar/i ++ ~w(a b c) ++ ~S[raw]";
+        assert_eq!(
+            kinds_of(source, TokenKind::Sigil),
+            vec!["~r/foo|bar/i", "~w(a b c)", "~S[raw]"]
+        );
+    }
+
+    #[test]
+    fn comments() {
+        let source = "foo() # call \"foo\"\nbar()";
+        assert_eq!(kinds_of(source, TokenKind::Comment), vec!["# call \"foo\""]);
+        assert!(kinds_of(source, TokenKind::String).is_empty());
+    }
+
+    #[test]
+    fn heredoc_spans_multiple_lines() {
+        let source = "@doc \"\"\"\nHello # not a comment\n\"\"\"\ndef foo";
+        let lines = highlight_elixir(source);
+
+        assert_eq!(lines.len(), 4);
+        assert_eq!(
+            lines[1],
+            vec![(TokenKind::String, String::from("Hello # not a comment"))]
+        );
+        assert_eq!(lines[3][0], (TokenKind::Keyword, String::from("def")));
+    }
+
+    #[test]
+    fn preserves_text() {
+        let source = "defmodule A.B do\n  @x ~s(y) <> \"z\" # c\nend\n";
+        let rebuilt: Vec<String> = highlight_elixir(source)
+            .into_iter()
+            .map(|line| line.into_iter().map(|(_, text)| text).collect())
+            .collect();
+
+        assert_eq!(rebuilt.join("\n"), source);
+    }
+}
@@ -0,0 +1,111 @@
+/// The number of entries kept in each direction. The oldest entries are dropped first
+const MAX_ENTRIES: usize = 100;
+
+/// Back and forward stacks of earlier states, like the history of a web browser
+#[derive(Debug)]
+pub struct History<T> {
+    back: Vec<T>,
+    forward: Vec<T>,
+}
+
+impl<T> Default for History<T> {
+    fn default() -> Self {
+        Self {
+            back: vec![],
+            forward: vec![],
+        }
+    }
+}
+
+impl<T> History<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the state being left. Going somewhere new drops the forward history
+    pub fn push(&mut self, current: T) {
+        push_bounded(&mut self.back, current);
+        self.forward.clear();
+    }
+
+    pub fn can_go_back(&self) -> bool {
+        !self.back.is_empty()
+    }
+
+    pub fn can_go_forward(&self) -> bool {
+        !self.forward.is_empty()
+    }
+
+    /// Returns the previous state, keeping the current one to go forward to
+    pub fn back(&mut self, current: T) -> Option<T> {
+        let previous = self.back.pop()?;
+        push_bounded(&mut self.forward, current);
+
+        Some(previous)
+    }
+
+    /// Returns the next state, keeping the current one to go back to
+    pub fn forward(&mut self, current: T) -> Option<T> {
+        let next = self.forward.pop()?;
+        push_bounded(&mut self.back, current);
+
+        Some(next)
+    }
+}
+
+fn push_bounded<T>(stack: &mut Vec<T>, entry: T) {
+    if stack.len() == MAX_ENTRIES {
+        stack.remove(0);
+    }
+
+    stack.push(entry);
+}
+
+#[cfg(test)]
+mod history_tests {
+    use super::*;
+
+    #[test]
+    fn back_and_forward() {
+        let mut history = History::new();
+        history.push(1);
+        history.push(2);
+
+        assert_eq!(history.back(3), Some(2));
+        assert_eq!(history.back(2), Some(1));
+        assert_eq!(history.back(1), None);
+        assert_eq!(history.forward(1), Some(2));
+        assert_eq!(history.forward(2), Some(3));
+        assert_eq!(history.forward(3), None);
+    }
+
+    #[test]
+    fn push_drops_forward_history() {
+        let mut history = History::new();
+        history.push(1);
+        history.back(2);
+        assert!(history.can_go_forward());
+
+        history.push(1);
+        assert!(!history.can_go_forward());
+        assert!(history.can_go_back());
+    }
+
+    #[test]
+    fn bounded() {
+        let mut history = History::new();
+        for entry in 0..MAX_ENTRIES + 10 {
+            history.push(entry);
+        }
+
+        let mut current = MAX_ENTRIES + 10;
+        let mut count = 0;
+        while let Some(previous) = history.back(current) {
+            current = previous;
+            count += 1;
+        }
+
+        assert_eq!(count, MAX_ENTRIES);
+        assert_eq!(current, 10);
+    }
+}
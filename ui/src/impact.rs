@@ -0,0 +1,132 @@
+use std::collections::BTreeMap;
+
+use crate::{FileEntry, FilePath, RecomplileDependencyReason};
+
+/// The files recompiled when several files change at once. A file recompiled because of several
+/// of the changed files, or for several reasons, is counted once
+#[derive(Debug, Clone, PartialEq)]
+pub struct Impact {
+    pub changed: Vec<FilePath>,
+    // The files which are always recompiled come first, then by path
+    pub affected: Vec<AffectedFile>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AffectedFile {
+    pub path: FilePath,
+    // The changed files making it recompile and why, in the order the files were changed
+    pub triggers: Vec<(FilePath, RecomplileDependencyReason)>,
+}
+
+impl AffectedFile {
+    /// Whether the file is always recompiled: one definite trigger is enough
+    pub fn is_definite(&self) -> bool {
+        self.triggers.iter().any(|(_, reason)| reason.is_definite())
+    }
+}
+
+impl Impact {
+    pub fn compute(changed: &[FilePath], files: &[FileEntry]) -> Self {
+        let mut triggers: BTreeMap<&FilePath, Vec<(FilePath, RecomplileDependencyReason)>> =
+            BTreeMap::new();
+
+        for path in changed {
+            let dependents = files
+                .iter()
+                .find(|file| file.path == *path)
+                .map(|file| file.recompile_dependencies.as_slice())
+                .unwrap_or_default();
+
+            for dependent in dependents {
+                triggers
+                    .entry(&dependent.path)
+                    .or_default()
+                    .push((path.clone(), dependent.reason.clone()));
+            }
+        }
+
+        let mut affected: Vec<AffectedFile> = triggers
+            .into_iter()
+            .map(|(path, triggers)| AffectedFile {
+                path: path.clone(),
+                triggers,
+            })
+            .collect();
+        // The sort is stable, the files stay sorted by path within each group
+        affected.sort_by_key(|file| !file.is_definite());
+
+        Self {
+            changed: changed.to_vec(),
+            affected,
+        }
+    }
+
+    /// The number of files always recompiled, and of those recompiled if the exports change
+    pub fn counts(&self) -> (usize, usize) {
+        let definite = self
+            .affected
+            .iter()
+            .filter(|file| file.is_definite())
+            .count();
+
+        (definite, self.affected.len() - definite)
+    }
+}
+
+#[cfg(test)]
+mod impact_tests {
+    use super::*;
+    use crate::RecomplileDependency;
+    use crate::RecomplileDependencyReason::*;
+
+    fn file(path: &str, dependents: &[(&str, RecomplileDependencyReason)]) -> FileEntry {
+        let dependents = dependents
+            .iter()
+            .map(|(dependent, reason)| {
+                RecomplileDependency::fixture(dependent).with_reason(reason.clone())
+            })
+            .collect();
+
+        FileEntry::fixture(path, dependents)
+    }
+
+    fn files() -> Vec<FileEntry> {
+        vec![
+            file("a", &[("c", Exports), ("d", Compile), ("e", Exports)]),
+            file("b", &[("c", Compile), ("e", ExportsThenCompile)]),
+            file("c", &[]),
+        ]
+    }
+
+    #[test]
+    fn dependents_are_counted_once() {
+        let changed = [String::from("a"), String::from("b")];
+        let impact = Impact::compute(&changed, &files());
+
+        let paths: Vec<&str> = impact.affected.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, ["c", "d", "e"]);
+        assert_eq!(impact.counts(), (2, 1));
+
+        assert_eq!(
+            impact.affected[0].triggers,
+            [(String::from("a"), Exports), (String::from("b"), Compile)]
+        );
+    }
+
+    #[test]
+    fn single_changed_file() {
+        let impact = Impact::compute(&[String::from("a")], &files());
+
+        let paths: Vec<&str> = impact.affected.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, ["d", "c", "e"]);
+        assert_eq!(impact.counts(), (1, 2));
+    }
+
+    #[test]
+    fn nothing_changed() {
+        let impact = Impact::compute(&[String::from("c"), String::from("x")], &files());
+
+        assert!(impact.affected.is_empty());
+        assert_eq!(impact.counts(), (0, 0));
+    }
+}
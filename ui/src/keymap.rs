@@ -0,0 +1,909 @@
+use anyhow::{anyhow, Result};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use crate::RecomplileDependencyReason;
+
+/// Abstract actions which keys are bound to. Each component decides what an action means
+/// in its own context, e.g. `Select` selects a file in the file panel but expands a dependent in
+/// the file dependent panel
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    MoveUp,
+    MoveDown,
+    Select,
+    Search,
+    Command,
+    SearchDelete,
+    SubmitSearch,
+    Complete,
+    CursorLeft,
+    CursorRight,
+    CursorHome,
+    CursorEnd,
+    DeleteWord,
+    ClearLine,
+    PreviousQuery,
+    NextQuery,
+    OpenInEditor,
+    Pivot,
+    ToggleTree,
+    GroupByReason,
+    ExpandAll,
+    CollapseAll,
+    NextPath,
+    PreviousPath,
+    ToggleCompile,
+    ToggleExports,
+    ToggleExportsThenCompile,
+    ToggleCompileThenRuntime,
+    FilterCounts,
+    Pin,
+    Mark,
+    ClearMarks,
+    CycleSort,
+    ReverseSort,
+    ShrinkPanel,
+    GrowPanel,
+    HistoryBack,
+    HistoryForward,
+    ToggleHelp,
+    ToggleDashboard,
+    ToggleImpact,
+    ToggleChangedFiles,
+    Cancel,
+    Quit,
+}
+
+const ACTIONS: &[Action] = &[
+    Action::MoveUp,
+    Action::MoveDown,
+    Action::Select,
+    Action::Search,
+    Action::Command,
+    Action::SearchDelete,
+    Action::SubmitSearch,
+    Action::Complete,
+    Action::CursorLeft,
+    Action::CursorRight,
+    Action::CursorHome,
+    Action::CursorEnd,
+    Action::DeleteWord,
+    Action::ClearLine,
+    Action::PreviousQuery,
+    Action::NextQuery,
+    Action::OpenInEditor,
+    Action::Pivot,
+    Action::ToggleTree,
+    Action::GroupByReason,
+    Action::ExpandAll,
+    Action::CollapseAll,
+    Action::NextPath,
+    Action::PreviousPath,
+    Action::ToggleCompile,
+    Action::ToggleExports,
+    Action::ToggleExportsThenCompile,
+    Action::ToggleCompileThenRuntime,
+    Action::FilterCounts,
+    Action::Pin,
+    Action::Mark,
+    Action::ClearMarks,
+    Action::CycleSort,
+    Action::ReverseSort,
+    Action::ShrinkPanel,
+    Action::GrowPanel,
+    Action::HistoryBack,
+    Action::HistoryForward,
+    Action::ToggleHelp,
+    Action::ToggleDashboard,
+    Action::ToggleImpact,
+    Action::ToggleChangedFiles,
+    Action::Cancel,
+    Action::Quit,
+];
+
+impl Action {
+    /// The name used to refer to the action in the config file
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::MoveUp => "move_up",
+            Action::MoveDown => "move_down",
+            Action::Select => "select",
+            Action::Search => "search",
+            Action::Command => "command",
+            Action::SearchDelete => "search_delete",
+            Action::SubmitSearch => "submit_search",
+            Action::Complete => "complete",
+            Action::CursorLeft => "cursor_left",
+            Action::CursorRight => "cursor_right",
+            Action::CursorHome => "cursor_home",
+            Action::CursorEnd => "cursor_end",
+            Action::DeleteWord => "delete_word",
+            Action::ClearLine => "clear_line",
+            Action::PreviousQuery => "previous_query",
+            Action::NextQuery => "next_query",
+            Action::OpenInEditor => "open_in_editor",
+            Action::Pivot => "pivot",
+            Action::ToggleTree => "toggle_tree",
+            Action::GroupByReason => "group_by_reason",
+            Action::ExpandAll => "expand_all",
+            Action::CollapseAll => "collapse_all",
+            Action::NextPath => "next_path",
+            Action::PreviousPath => "previous_path",
+            Action::ToggleCompile => "toggle_compile",
+            Action::ToggleExports => "toggle_exports",
+            Action::ToggleExportsThenCompile => "toggle_exports_then_compile",
+            Action::ToggleCompileThenRuntime => "toggle_compile_then_runtime",
+            Action::FilterCounts => "filter_counts",
+            Action::Pin => "pin",
+            Action::Mark => "mark",
+            Action::ClearMarks => "clear_marks",
+            Action::CycleSort => "cycle_sort",
+            Action::ReverseSort => "reverse_sort",
+            Action::ShrinkPanel => "shrink_panel",
+            Action::GrowPanel => "grow_panel",
+            Action::HistoryBack => "history_back",
+            Action::HistoryForward => "history_forward",
+            Action::ToggleHelp => "toggle_help",
+            Action::ToggleDashboard => "toggle_dashboard",
+            Action::ToggleImpact => "toggle_impact",
+            Action::ToggleChangedFiles => "toggle_changed_files",
+            Action::Cancel => "cancel",
+            Action::Quit => "quit",
+        }
+    }
+
+    /// The recompile reason shown or hidden by the action
+    pub fn toggled_reason(&self) -> Option<RecomplileDependencyReason> {
+        match self {
+            Action::ToggleCompile => Some(RecomplileDependencyReason::Compile),
+            Action::ToggleExports => Some(RecomplileDependencyReason::Exports),
+            Action::ToggleExportsThenCompile => {
+                Some(RecomplileDependencyReason::ExportsThenCompile)
+            }
+            Action::ToggleCompileThenRuntime => {
+                Some(RecomplileDependencyReason::CompileThenRuntime)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        ACTIONS.iter().find(|action| action.name() == name).copied()
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Action::MoveUp => "Move up",
+            Action::MoveDown => "Move down",
+            Action::Select => "Select",
+            Action::Search => "Search",
+            Action::Command => "Run a command",
+            Action::SearchDelete => "Delete character",
+            Action::SubmitSearch => "Submit search",
+            Action::Complete => "Complete command",
+            Action::CursorLeft => "Move cursor left",
+            Action::CursorRight => "Move cursor right",
+            Action::CursorHome => "Move cursor to start",
+            Action::CursorEnd => "Move cursor to end",
+            Action::DeleteWord => "Delete word",
+            Action::ClearLine => "Clear line",
+            Action::PreviousQuery => "Previous query",
+            Action::NextQuery => "Next query",
+            Action::OpenInEditor => "Open cause in editor",
+            Action::Pivot => "Make the focused file the source",
+            Action::ToggleTree => "Toggle tree view",
+            Action::GroupByReason => "Group dependents by reason",
+            Action::ExpandAll => "Expand all dependents",
+            Action::CollapseAll => "Collapse all dependents",
+            Action::NextPath => "Next dependency path",
+            Action::PreviousPath => "Previous dependency path",
+            Action::ToggleCompile => "Show or hide compile dependents",
+            Action::ToggleExports => "Show or hide exports dependents",
+            Action::ToggleExportsThenCompile => "Show or hide exports then compile dependents",
+            Action::ToggleCompileThenRuntime => "Show or hide compile then runtime dependents",
+            Action::FilterCounts => "Apply the reason filter to counts",
+            Action::Pin => "Pin or unpin file",
+            Action::Mark => "Mark or unmark file as changed",
+            Action::ClearMarks => "Unmark all files",
+            Action::CycleSort => "Next sort mode",
+            Action::ReverseSort => "Reverse sort order",
+            Action::ShrinkPanel => "Shrink left panel",
+            Action::GrowPanel => "Grow left panel",
+            Action::HistoryBack => "Go back",
+            Action::HistoryForward => "Go forward",
+            Action::ToggleHelp => "Toggle help",
+            Action::ToggleDashboard => "Toggle statistics",
+            Action::ToggleImpact => "Toggle files recompiled by the marked files",
+            Action::ToggleChangedFiles => "Toggle files changed against the diffed ref",
+            Action::Cancel => "Cancel",
+            Action::Quit => "Quit",
+        }
+    }
+}
+
+/// The context in which a binding is active
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Scope {
+    // Active in every view, unless a prompt is active
+    Global,
+    // Active while a prompt is active, in place of all other scopes
+    Prompt,
+    FilePanel,
+    FileDependentPanel,
+    DependencyCausePanel,
+}
+
+const SCOPES: &[Scope] = &[
+    Scope::Global,
+    Scope::Prompt,
+    Scope::FilePanel,
+    Scope::FileDependentPanel,
+    Scope::DependencyCausePanel,
+];
+
+/// Scopes which are active at the same time. A key must not be bound to more than one action
+/// within each group
+const CONCURRENT_SCOPES: &[&[Scope]] = &[
+    &[Scope::Prompt],
+    &[Scope::Global, Scope::FilePanel],
+    &[
+        Scope::Global,
+        Scope::FileDependentPanel,
+        Scope::DependencyCausePanel,
+    ],
+];
+
+impl Scope {
+    /// The name used to refer to the scope in the config file
+    pub fn name(&self) -> &'static str {
+        match self {
+            Scope::Global => "global",
+            Scope::Prompt => "prompt",
+            Scope::FilePanel => "file_panel",
+            Scope::FileDependentPanel => "file_dependent_panel",
+            Scope::DependencyCausePanel => "dependency_cause_panel",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        SCOPES.iter().find(|scope| scope.name() == name).copied()
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            Scope::Global => "Global",
+            Scope::Prompt => "Prompt",
+            Scope::FilePanel => "Files",
+            Scope::FileDependentPanel => "Recompile files",
+            Scope::DependencyCausePanel => "Dependency causes",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyBinding {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    pub fn new(code: KeyCode) -> Self {
+        Self {
+            code,
+            modifiers: KeyModifiers::NONE,
+        }
+    }
+
+    pub fn matches(&self, key: &KeyEvent) -> bool {
+        // Shift is already reflected in the character, e.g. `?` or `G`
+        let modifiers = match key.code {
+            KeyCode::Char(_) => key.modifiers - KeyModifiers::SHIFT,
+            _ => key.modifiers,
+        };
+
+        self.code == key.code && self.modifiers == modifiers
+    }
+}
+
+impl FromStr for KeyBinding {
+    type Err = anyhow::Error;
+
+    /// Parse a key from its textual form, e.g. `j`, `G`, `enter`, `<C-w>` or `C-w`
+    fn from_str(input: &str) -> Result<Self> {
+        let trimmed = input.trim();
+        let mut rest = trimmed
+            .strip_prefix('<')
+            .and_then(|s| s.strip_suffix('>'))
+            .unwrap_or(trimmed);
+
+        let mut modifiers = KeyModifiers::NONE;
+        loop {
+            if let Some(stripped) = rest.strip_prefix("C-") {
+                modifiers |= KeyModifiers::CONTROL;
+                rest = stripped;
+            } else if let Some(stripped) = rest.strip_prefix("M-") {
+                modifiers |= KeyModifiers::ALT;
+                rest = stripped;
+            } else {
+                break;
+            }
+        }
+
+        let mut chars = rest.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(char), None) => KeyCode::Char(char),
+            _ => match rest.to_lowercase().as_str() {
+                "space" => KeyCode::Char(' '),
+                "enter" => KeyCode::Enter,
+                "esc" => KeyCode::Esc,
+                "backspace" => KeyCode::Backspace,
+                "tab" => KeyCode::Tab,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                "delete" => KeyCode::Delete,
+                name => match name.strip_prefix('f').map(|n| n.parse::<u8>()) {
+                    Some(Ok(number)) => KeyCode::F(number),
+                    _ => return Err(anyhow!("Unknown key {:?}", input)),
+                },
+            },
+        };
+
+        Ok(Self { code, modifiers })
+    }
+}
+
+impl std::fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let key = match self.code {
+            KeyCode::Char(' ') => String::from("space"),
+            KeyCode::Char(char) => char.to_string(),
+            KeyCode::Enter => String::from("enter"),
+            KeyCode::Esc => String::from("esc"),
+            KeyCode::Backspace => String::from("backspace"),
+            KeyCode::Tab => String::from("tab"),
+            KeyCode::Up => String::from("up"),
+            KeyCode::Down => String::from("down"),
+            KeyCode::Left => String::from("left"),
+            KeyCode::Right => String::from("right"),
+            KeyCode::Home => String::from("home"),
+            KeyCode::End => String::from("end"),
+            KeyCode::PageUp => String::from("pageup"),
+            KeyCode::PageDown => String::from("pagedown"),
+            KeyCode::Delete => String::from("delete"),
+            KeyCode::F(number) => format!("f{}", number),
+            code => format!("{:?}", code).to_lowercase(),
+        };
+
+        let mut prefix = String::new();
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            prefix.push_str("C-");
+        }
+
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            prefix.push_str("M-");
+        }
+
+        match self.code {
+            KeyCode::Char(_) if prefix.is_empty() => write!(f, "{}", key),
+            _ => write!(f, "<{}{}>", prefix, key),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Binding {
+    pub scope: Scope,
+    pub key: KeyBinding,
+    pub action: Action,
+}
+
+/// The table of all key bindings. Components look up their actions here, and the help overlay
+/// is generated from it, so the two can't drift apart
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: Vec<Binding>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let bindings = [
+            (Scope::Global, KeyCode::Char('j'), Action::MoveDown),
+            (Scope::Global, KeyCode::Down, Action::MoveDown),
+            (Scope::Global, KeyCode::Char('k'), Action::MoveUp),
+            (Scope::Global, KeyCode::Up, Action::MoveUp),
+            (Scope::Global, KeyCode::Char('/'), Action::Search),
+            (Scope::Global, KeyCode::Char(':'), Action::Command),
+            (Scope::Global, KeyCode::Char('['), Action::HistoryBack),
+            (Scope::Global, KeyCode::Char(']'), Action::HistoryForward),
+            (Scope::Global, KeyCode::Char('<'), Action::ShrinkPanel),
+            (Scope::Global, KeyCode::Char('>'), Action::GrowPanel),
+            (Scope::Global, KeyCode::Char('?'), Action::ToggleHelp),
+            (Scope::Global, KeyCode::Char('d'), Action::ToggleDashboard),
+            (Scope::Global, KeyCode::Char('i'), Action::ToggleImpact),
+            (
+                Scope::Global,
+                KeyCode::Char('c'),
+                Action::ToggleChangedFiles,
+            ),
+            (Scope::Global, KeyCode::Esc, Action::Cancel),
+            (Scope::Global, KeyCode::Char('q'), Action::Quit),
+            (Scope::Prompt, KeyCode::Up, Action::PreviousQuery),
+            (Scope::Prompt, KeyCode::Down, Action::NextQuery),
+            (Scope::Prompt, KeyCode::Left, Action::CursorLeft),
+            (Scope::Prompt, KeyCode::Right, Action::CursorRight),
+            (Scope::Prompt, KeyCode::Home, Action::CursorHome),
+            (Scope::Prompt, KeyCode::End, Action::CursorEnd),
+            (Scope::Prompt, KeyCode::Backspace, Action::SearchDelete),
+            (Scope::Prompt, KeyCode::Enter, Action::SubmitSearch),
+            (Scope::Prompt, KeyCode::Tab, Action::Complete),
+            (Scope::Prompt, KeyCode::Esc, Action::Cancel),
+            (Scope::FilePanel, KeyCode::Enter, Action::Select),
+            (Scope::FilePanel, KeyCode::Char('m'), Action::Pin),
+            (Scope::FilePanel, KeyCode::Char(' '), Action::Mark),
+            (Scope::FilePanel, KeyCode::Char('u'), Action::ClearMarks),
+            (Scope::FilePanel, KeyCode::Char('f'), Action::FilterCounts),
+            (Scope::FilePanel, KeyCode::Char('s'), Action::CycleSort),
+            (Scope::FilePanel, KeyCode::Char('S'), Action::ReverseSort),
+            (Scope::FilePanel, KeyCode::Char('t'), Action::ToggleTree),
+            (Scope::FileDependentPanel, KeyCode::Enter, Action::Select),
+            (Scope::FileDependentPanel, KeyCode::Char('p'), Action::Pivot),
+            (
+                Scope::FileDependentPanel,
+                KeyCode::Char('t'),
+                Action::ToggleTree,
+            ),
+            (
+                Scope::FileDependentPanel,
+                KeyCode::Char('g'),
+                Action::GroupByReason,
+            ),
+            (
+                Scope::FileDependentPanel,
+                KeyCode::Char('+'),
+                Action::ExpandAll,
+            ),
+            (
+                Scope::FileDependentPanel,
+                KeyCode::Char('-'),
+                Action::CollapseAll,
+            ),
+            (
+                Scope::FileDependentPanel,
+                KeyCode::Char('n'),
+                Action::NextPath,
+            ),
+            (
+                Scope::FileDependentPanel,
+                KeyCode::Char('N'),
+                Action::PreviousPath,
+            ),
+            (
+                Scope::FileDependentPanel,
+                KeyCode::Char('1'),
+                Action::ToggleCompile,
+            ),
+            (
+                Scope::FileDependentPanel,
+                KeyCode::Char('2'),
+                Action::ToggleExports,
+            ),
+            (
+                Scope::FileDependentPanel,
+                KeyCode::Char('3'),
+                Action::ToggleExportsThenCompile,
+            ),
+            (
+                Scope::FileDependentPanel,
+                KeyCode::Char('4'),
+                Action::ToggleCompileThenRuntime,
+            ),
+            (
+                Scope::DependencyCausePanel,
+                KeyCode::Char('e'),
+                Action::OpenInEditor,
+            ),
+        ]
+        .into_iter()
+        .map(|(scope, code, action)| Binding {
+            scope,
+            key: KeyBinding::new(code),
+            action,
+        })
+        .chain(
+            [
+                (Scope::Prompt, 'a', Action::CursorHome),
+                (Scope::Prompt, 'e', Action::CursorEnd),
+                (Scope::Prompt, 'w', Action::DeleteWord),
+                (Scope::Prompt, 'u', Action::ClearLine),
+            ]
+            .into_iter()
+            .map(|(scope, char, action)| Binding {
+                scope,
+                key: KeyBinding {
+                    code: KeyCode::Char(char),
+                    modifiers: KeyModifiers::CONTROL,
+                },
+                action,
+            }),
+        )
+        .collect();
+
+        Self { bindings }
+    }
+}
+
+impl Keymap {
+    pub fn new(bindings: Vec<Binding>) -> Self {
+        Self { bindings }
+    }
+
+    pub fn bindings(&self) -> &[Binding] {
+        &self.bindings
+    }
+
+    /// Apply overrides from the config file. Overrides are grouped by scope name, then by
+    /// action name. Overriding an action replaces all of its default keys in that scope
+    pub fn with_overrides(
+        mut self,
+        overrides: &HashMap<String, HashMap<String, Vec<String>>>,
+    ) -> Result<Self> {
+        // Sort the overrides, so the resulting table doesn't depend on the map ordering
+        let mut overrides: Vec<(&String, &HashMap<String, Vec<String>>)> =
+            overrides.iter().collect();
+        overrides.sort_by_key(|(scope, _)| *scope);
+
+        for (scope_name, actions) in overrides {
+            let scope = Scope::from_name(scope_name)
+                .ok_or_else(|| anyhow!("Unknown keymap scope {:?}", scope_name))?;
+
+            let mut actions: Vec<(&String, &Vec<String>)> = actions.iter().collect();
+            actions.sort_by_key(|(action, _)| *action);
+
+            for (action_name, keys) in actions {
+                let action = Action::from_name(action_name).ok_or_else(|| {
+                    anyhow!(
+                        "Unknown action {:?} in keymap scope {:?}",
+                        action_name,
+                        scope_name
+                    )
+                })?;
+
+                let keys = keys
+                    .iter()
+                    .map(|key| key.parse::<KeyBinding>())
+                    .collect::<Result<Vec<KeyBinding>>>()?;
+
+                let position = self
+                    .bindings
+                    .iter()
+                    .position(|b| b.scope == scope && b.action == action)
+                    .unwrap_or(self.bindings.len());
+
+                self.bindings
+                    .retain(|b| !(b.scope == scope && b.action == action));
+
+                let position = position.min(self.bindings.len());
+                for (offset, key) in keys.into_iter().enumerate() {
+                    self.bindings
+                        .insert(position + offset, Binding { scope, key, action });
+                }
+            }
+        }
+
+        Ok(self)
+    }
+
+    /// Returns a description of every key which is bound to more than one action within scopes
+    /// that are active at the same time
+    pub fn conflicts(&self) -> Vec<String> {
+        let mut conflicts: Vec<String> = vec![];
+
+        for scopes in CONCURRENT_SCOPES {
+            let bindings: Vec<&Binding> = self
+                .bindings
+                .iter()
+                .filter(|b| scopes.contains(&b.scope))
+                .collect();
+
+            for (index, binding) in bindings.iter().enumerate() {
+                for other in &bindings[index + 1..] {
+                    if binding.key != other.key || binding.action == other.action {
+                        continue;
+                    }
+
+                    let message = format!(
+                        "{} is bound to both {}.{} and {}.{}",
+                        binding.key,
+                        binding.scope.name(),
+                        binding.action.name(),
+                        other.scope.name(),
+                        other.action.name()
+                    );
+
+                    if !conflicts.contains(&message) {
+                        conflicts.push(message);
+                    }
+                }
+            }
+        }
+
+        conflicts
+    }
+
+    /// Returns the action bound to the key in the given scope
+    pub fn action(&self, scope: Scope, key: &KeyEvent) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|binding| binding.scope == scope && binding.key.matches(key))
+            .map(|binding| binding.action)
+    }
+
+    /// Returns all keys bound to the action in the given scope
+    pub fn keys(&self, scope: Scope, action: Action) -> Vec<KeyBinding> {
+        self.bindings
+            .iter()
+            .filter(|binding| binding.scope == scope && binding.action == action)
+            .map(|binding| binding.key)
+            .collect()
+    }
+
+    /// Returns the bindings of a scope, grouping keys bound to the same action together.
+    /// Actions are listed in the order they first appear in the table
+    pub fn describe(&self, scope: Scope) -> Vec<(Vec<KeyBinding>, Action)> {
+        let mut result: Vec<(Vec<KeyBinding>, Action)> = vec![];
+
+        for binding in self.bindings.iter().filter(|b| b.scope == scope) {
+            match result
+                .iter_mut()
+                .find(|(_, action)| *action == binding.action)
+            {
+                Some((keys, _)) => keys.push(binding.key),
+                None => result.push((vec![binding.key], binding.action)),
+            }
+        }
+
+        result
+    }
+}
+
+static KEYMAP: OnceLock<Keymap> = OnceLock::new();
+
+/// Install the keymap to use. Must be called before the keymap is first used, otherwise the
+/// default keymap stays in place
+pub fn init(keymap: Keymap) {
+    let _ = KEYMAP.set(keymap);
+}
+
+/// Returns the keymap in use. If none is installed, the default keymap is used
+pub fn current() -> &'static Keymap {
+    KEYMAP.get_or_init(Keymap::default)
+}
+
+#[cfg(test)]
+mod keymap_tests {
+    use super::*;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn action_lookup_by_scope() {
+        let keymap = Keymap::default();
+
+        assert_eq!(
+            keymap.action(Scope::Global, &key(KeyCode::Char('j'), KeyModifiers::NONE)),
+            Some(Action::MoveDown)
+        );
+        assert_eq!(
+            keymap.action(Scope::Prompt, &key(KeyCode::Char('j'), KeyModifiers::NONE)),
+            None
+        );
+        assert_eq!(
+            keymap.action(Scope::FilePanel, &key(KeyCode::Enter, KeyModifiers::NONE)),
+            Some(Action::Select)
+        );
+        assert_eq!(
+            keymap.action(
+                Scope::Prompt,
+                &key(KeyCode::Char('w'), KeyModifiers::CONTROL)
+            ),
+            Some(Action::DeleteWord)
+        );
+        assert_eq!(
+            keymap.action(Scope::Prompt, &key(KeyCode::Char('w'), KeyModifiers::NONE)),
+            None
+        );
+    }
+
+    #[test]
+    fn shift_is_ignored_for_characters() {
+        let keymap = Keymap::default();
+
+        assert_eq!(
+            keymap.action(Scope::Global, &key(KeyCode::Char('?'), KeyModifiers::SHIFT)),
+            Some(Action::ToggleHelp)
+        );
+    }
+
+    #[test]
+    fn modifiers_must_match() {
+        let keymap = Keymap::default();
+
+        assert_eq!(
+            keymap.action(
+                Scope::Global,
+                &key(KeyCode::Char('j'), KeyModifiers::CONTROL)
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn describe_groups_keys_by_action() {
+        let keymap = Keymap::default();
+        let description = keymap.describe(Scope::Global);
+
+        assert_eq!(
+            description[0],
+            (
+                vec![
+                    KeyBinding::new(KeyCode::Char('j')),
+                    KeyBinding::new(KeyCode::Down)
+                ],
+                Action::MoveDown
+            )
+        );
+        assert_eq!(description.len(), 14);
+    }
+
+    #[test]
+    fn key_binding_display() {
+        let ctrl_w = KeyBinding {
+            code: KeyCode::Char('w'),
+            modifiers: KeyModifiers::CONTROL,
+        };
+
+        assert_eq!(KeyBinding::new(KeyCode::Char('q')).to_string(), "q");
+        assert_eq!(KeyBinding::new(KeyCode::Enter).to_string(), "<enter>");
+        assert_eq!(ctrl_w.to_string(), "<C-w>");
+    }
+
+    #[test]
+    fn parse_key_binding() {
+        assert_eq!(
+            "j".parse::<KeyBinding>().unwrap(),
+            KeyBinding::new(KeyCode::Char('j'))
+        );
+        assert_eq!(
+            "G".parse::<KeyBinding>().unwrap(),
+            KeyBinding::new(KeyCode::Char('G'))
+        );
+        assert_eq!(
+            "pagedown".parse::<KeyBinding>().unwrap(),
+            KeyBinding::new(KeyCode::PageDown)
+        );
+        assert_eq!(
+            "<C-n>".parse::<KeyBinding>().unwrap(),
+            KeyBinding {
+                code: KeyCode::Char('n'),
+                modifiers: KeyModifiers::CONTROL
+            }
+        );
+        assert_eq!(
+            "M-C-x".parse::<KeyBinding>().unwrap(),
+            KeyBinding {
+                code: KeyCode::Char('x'),
+                modifiers: KeyModifiers::CONTROL | KeyModifiers::ALT
+            }
+        );
+        assert!("hyper".parse::<KeyBinding>().is_err());
+    }
+
+    #[test]
+    fn parse_key_binding_round_trip() {
+        for binding in Keymap::default().bindings() {
+            assert_eq!(
+                binding.key.to_string().parse::<KeyBinding>().unwrap(),
+                binding.key
+            );
+        }
+    }
+
+    fn overrides(
+        entries: &[(&str, &str, &[&str])],
+    ) -> HashMap<String, HashMap<String, Vec<String>>> {
+        let mut result: HashMap<String, HashMap<String, Vec<String>>> = HashMap::new();
+
+        for (scope, action, keys) in entries {
+            result.entry(scope.to_string()).or_default().insert(
+                action.to_string(),
+                keys.iter().map(|key| key.to_string()).collect(),
+            );
+        }
+
+        result
+    }
+
+    #[test]
+    fn overrides_replace_default_keys() {
+        let keymap = Keymap::default()
+            .with_overrides(&overrides(&[("global", "move_down", &["C-n", "down"])]))
+            .unwrap();
+
+        assert_eq!(
+            keymap.action(Scope::Global, &key(KeyCode::Char('j'), KeyModifiers::NONE)),
+            None
+        );
+        assert_eq!(
+            keymap.action(
+                Scope::Global,
+                &key(KeyCode::Char('n'), KeyModifiers::CONTROL)
+            ),
+            Some(Action::MoveDown)
+        );
+        // The overridden action keeps its position in the table
+        assert_eq!(keymap.describe(Scope::Global)[0].1, Action::MoveDown);
+    }
+
+    #[test]
+    fn overrides_with_unknown_names() {
+        assert!(Keymap::default()
+            .with_overrides(&overrides(&[("nowhere", "move_down", &["j"])]))
+            .is_err());
+        assert!(Keymap::default()
+            .with_overrides(&overrides(&[("global", "fly", &["j"])]))
+            .is_err());
+        assert!(Keymap::default()
+            .with_overrides(&overrides(&[("global", "quit", &["hyper"])]))
+            .is_err());
+    }
+
+    #[test]
+    fn no_conflicts_in_default_keymap() {
+        assert!(Keymap::default().conflicts().is_empty());
+    }
+
+    #[test]
+    fn conflicts_within_scope() {
+        let keymap = Keymap::default()
+            .with_overrides(&overrides(&[("global", "quit", &["j"])]))
+            .unwrap();
+
+        assert_eq!(
+            keymap.conflicts(),
+            vec!["j is bound to both global.move_down and global.quit"]
+        );
+    }
+
+    #[test]
+    fn conflicts_across_concurrent_scopes() {
+        let keymap = Keymap::default()
+            .with_overrides(&overrides(&[("file_panel", "select", &["enter", "q"])]))
+            .unwrap();
+
+        assert_eq!(
+            keymap.conflicts(),
+            vec!["q is bound to both global.quit and file_panel.select"]
+        );
+    }
+
+    #[test]
+    fn same_key_in_exclusive_scopes_is_not_a_conflict() {
+        let keymap = Keymap::default()
+            .with_overrides(&overrides(&[("file_panel", "select", &["enter", "e"])]))
+            .unwrap();
+
+        // `e` also opens the editor, but only in the file dependents view
+        assert!(keymap.conflicts().is_empty());
+    }
+}
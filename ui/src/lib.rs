@@ -9,12 +9,32 @@ use adapter::ServerAdapter;
 pub mod adapter;
 pub mod app_event;
 pub mod app_state;
+pub mod command;
 pub mod components;
+pub mod config;
+pub mod dependency_tree;
+pub mod directory_tree;
+pub mod editor;
+pub mod export;
+pub mod file_sort;
+pub mod git;
+pub mod highlight;
+pub mod history;
+pub mod impact;
+pub mod keymap;
+pub mod query;
+pub mod reason_filter;
+pub mod session;
+pub mod stats;
+pub mod theme;
 pub mod utils;
 
 pub static mut FRAME_COUNT: usize = 0;
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+// The mix project is located at the parent directory
+pub const PROJECT_DIR: &str = "..";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub enum RecomplileDependencyReason {
     #[serde(rename = "compile")]
     Compile,
@@ -26,6 +46,37 @@ pub enum RecomplileDependencyReason {
     CompileThenRuntime,
 }
 
+impl RecomplileDependencyReason {
+    pub const ALL: [Self; 4] = [
+        Self::Compile,
+        Self::Exports,
+        Self::ExportsThenCompile,
+        Self::CompileThenRuntime,
+    ];
+
+    /// Whether the dependent is always recompiled. Exports dependents are only recompiled when
+    /// the exports of the source change, the compiler can't know beforehand
+    pub fn is_definite(&self) -> bool {
+        match self {
+            Self::Compile | Self::CompileThenRuntime => true,
+            Self::Exports | Self::ExportsThenCompile => false,
+        }
+    }
+}
+
+impl Display for RecomplileDependencyReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            RecomplileDependencyReason::Compile => "compile",
+            RecomplileDependencyReason::ExportsThenCompile => "exports_then_compile",
+            RecomplileDependencyReason::Exports => "exports",
+            RecomplileDependencyReason::CompileThenRuntime => "compile_then_runtime",
+        };
+
+        write!(f, "{}", text)
+    }
+}
+
 #[derive(Deserialize, Debug, Clone)]
 pub enum DependencyType {
     #[serde(rename = "compile")]
@@ -60,7 +111,129 @@ pub struct RecomplileDependency {
     id: String,
     path: FilePath,
     reason: RecomplileDependencyReason,
+    // The chain shown, one of the paths
     dependency_chain: Vec<DependencyLink>,
+    #[serde(flatten)]
+    paths: DependencyPaths,
+}
+
+/// Every path making a dependent recompile. All of them have to be broken for the dependent to
+/// stop recompiling, only the shortest ones are sent by the server
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct DependencyPaths {
+    #[serde(default)]
+    dependency_chains: Vec<Vec<DependencyLink>>,
+    #[serde(default)]
+    path_count: usize,
+    // The server stopped counting, there are more paths than `path_count`
+    #[serde(default)]
+    more_paths: bool,
+    // Index of the chain shown in `dependency_chains`
+    #[serde(skip)]
+    chain_index: usize,
+}
+
+impl RecomplileDependency {
+    /// The index of the chain shown among the paths, the shortest path is 0
+    pub fn chain_index(&self) -> usize {
+        self.paths.chain_index
+    }
+
+    /// Every path sent by the server, or the chain shown when there is no other
+    pub fn dependency_chains(&self) -> &[Vec<DependencyLink>] {
+        if self.paths.dependency_chains.is_empty() {
+            std::slice::from_ref(&self.dependency_chain)
+        } else {
+            &self.paths.dependency_chains
+        }
+    }
+
+    /// Shows the next path of the dependent, or the previous one. Returns false when there is
+    /// no other path to show
+    pub fn cycle_chain(&mut self, forward: bool) -> bool {
+        let count = self.paths.dependency_chains.len();
+        if count < 2 {
+            return false;
+        }
+
+        let step = if forward { 1 } else { count - 1 };
+        self.paths.chain_index = (self.paths.chain_index + step) % count;
+        self.dependency_chain = self.paths.dependency_chains[self.paths.chain_index].clone();
+
+        true
+    }
+
+    /// The position of the chain shown among all the paths, e.g. `path 2/7`, or `path 2/100+`
+    /// when the server stopped counting. None when the chain is the only path
+    pub fn paths_label(&self) -> Option<String> {
+        let count = self
+            .paths
+            .path_count
+            .max(self.paths.dependency_chains.len());
+        if count < 2 {
+            return None;
+        }
+
+        let more = if self.paths.more_paths { "+" } else { "" };
+        Some(format!(
+            "path {}/{}{}",
+            self.paths.chain_index + 1,
+            count,
+            more
+        ))
+    }
+}
+
+/// The dependents the tests are built from: recompiled for a compile time dependency, through no
+/// chain, with the id the server gives them. The `with_` methods change that
+#[cfg(test)]
+impl RecomplileDependency {
+    pub fn fixture(path: &str) -> Self {
+        Self {
+            id: format!("{}_{}", path, RecomplileDependencyReason::Compile),
+            path: path.to_string(),
+            reason: RecomplileDependencyReason::Compile,
+            dependency_chain: vec![],
+            paths: Default::default(),
+        }
+    }
+
+    pub fn with_id(self, id: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            ..self
+        }
+    }
+
+    /// The id follows the reason, as the server's does
+    pub fn with_reason(self, reason: RecomplileDependencyReason) -> Self {
+        Self {
+            id: format!("{}_{}", self.path, reason),
+            reason,
+            ..self
+        }
+    }
+
+    pub fn with_chain(self, dependency_chain: Vec<DependencyLink>) -> Self {
+        Self {
+            dependency_chain,
+            ..self
+        }
+    }
+
+    /// A chain of compile time dependencies, each of the files depending on the next one
+    pub fn with_chain_through(self, files: &[&str]) -> Self {
+        let dependency_chain = files
+            .windows(2)
+            .map(|pair| DependencyLink {
+                dependency_type: DependencyType::Compile,
+                source: pair[0].to_string(),
+                sink: pair[1].to_string(),
+            })
+            .collect();
+
+        self.with_chain(dependency_chain)
+    }
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -69,6 +242,16 @@ pub struct FileEntry {
     pub recompile_dependencies: Vec<RecomplileDependency>,
 }
 
+#[cfg(test)]
+impl FileEntry {
+    pub fn fixture(path: &str, recompile_dependencies: Vec<RecomplileDependency>) -> Self {
+        Self {
+            path: path.to_string(),
+            recompile_dependencies,
+        }
+    }
+}
+
 pub type FilePath = String;
 
 #[derive(Deserialize, Debug, Clone)]
@@ -82,6 +265,7 @@ pub struct DependencyCause {
 
 #[derive(Deserialize, Debug, Clone, PartialEq)]
 pub struct CodeSnippet {
+    origin_file: FilePath,
     content: String,
     highlight: (usize, usize),
     lines_span: (usize, usize),
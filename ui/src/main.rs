@@ -1,36 +1,87 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use ratatui::backend::CrosstermBackend;
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
 use ratatui::terminal::Terminal;
 use ratatui::Frame;
 use std::io::Stderr;
+use std::path::Path;
 use std::process::{Command, Stdio};
+use std::rc::Rc;
 use std::sync::mpsc;
-use ui::components::dependency_cause_panel::DependencyCausePanel;
+use std::time::Duration;
 
 use ui::adapter::{Adapter, ServerAdapter};
 use ui::app_event::AppEvent;
 use ui::app_state::StateMachine;
 use ui::app_state::{AppState, NoopWidget};
+use ui::components::changed_files_panel::ChangedFilesPanel;
+use ui::components::dashboard::Dashboard;
+use ui::components::dependency_cause_panel::DependencyCausePanel;
+use ui::components::dependents_tree::DependentsTree;
 use ui::components::file_dependent_panel::FileDependentPanel;
 use ui::components::file_panel::FilePanel;
+use ui::components::file_tree::FileTree;
+use ui::components::help_overlay::HelpOverlay;
+use ui::components::impact_report::ImpactReport;
 use ui::components::instructions::Instructions;
-use ui::components::search_input::SearchInput;
-use ui::utils::filter_files_list;
-use ui::{FileEntry, RecomplileDependency, FRAME_COUNT};
+use ui::components::message_line::{Message, MessageLine};
+use ui::components::search_input::{self, SearchInput};
+use ui::components::why_report::WhyReport;
+use ui::config;
+use ui::dependency_tree::DependencyTree;
+use ui::directory_tree::DirectoryTree;
+use ui::editor::{editor_command, editor_failure};
+use ui::export;
+use ui::file_sort::FileSort;
+use ui::git;
+use ui::impact::Impact;
+use ui::query::{FilterCache, Query};
+use ui::reason_filter::ReasonFilter;
+use ui::session::{self, Session};
+use ui::stats::Stats;
+use ui::{FileEntry, FilePath, RecomplileDependency, FRAME_COUNT, PROJECT_DIR};
 use ui::{HandleEvent, ProduceEvent};
 
 #[derive(Clone)]
 struct WidgetBoard {
     file_panel: FilePanel,
+    file_tree: Option<FileTree>,
     file_dependent_panel: Option<FileDependentPanel>,
+    dependents_tree: Option<DependentsTree>,
     dependency_cause_panel: DependencyCausePanel,
+    why_report: Option<WhyReport>,
+    impact_report: Option<ImpactReport>,
+    changed_files_panel: Option<ChangedFilesPanel>,
 }
 
 fn main() {
+    // `--impact <ref>` prints what the changes against the ref recompile, without the TUI
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let impact_base = match &args[..] {
+        [] => None,
+        [flag, base] if flag == "--impact" => Some(base.clone()),
+        _ => {
+            eprintln!("Usage: ui [--impact <git ref>]");
+            std::process::exit(2);
+        }
+    };
+
+    // The diff is checked before the server spends time collecting the files
+    let changed = impact_base.as_ref().map(|base| {
+        git::changed_files(Path::new(PROJECT_DIR), base).unwrap_or_else(|error| {
+            eprintln!("{:#}", error);
+            std::process::exit(1);
+        })
+    });
+
+    if let Err(error) = config::load().and_then(|config| config.install()) {
+        eprintln!("{:#}", error);
+        std::process::exit(1);
+    }
+
     let child_proc = Command::new("mix")
         .args(["run", "--no-halt"])
-        .current_dir("..")
+        .current_dir(PROJECT_DIR)
         .stdin(Stdio::piped())
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
@@ -40,18 +91,75 @@ fn main() {
     let mut adapter = Adapter::new(child_proc);
     adapter.init_server();
 
-    let _ = render(adapter);
+    match impact_base.zip(changed) {
+        Some((base, changed)) => {
+            if let Err(error) = print_impact(adapter, &base, &changed) {
+                eprintln!("{:#}", error);
+                std::process::exit(1);
+            }
+        }
+
+        None => {
+            let _ = render(adapter);
+        }
+    }
+}
+
+// Wait for the files of the project, then print the files recompiled by the changed ones
+fn print_impact(mut adapter: Adapter, base: &str, changed: &[FilePath]) -> Result<()> {
+    let (tx, rx) = mpsc::channel::<Vec<FileEntry>>();
+    adapter.get_files(Box::new(move |files| {
+        tx.send(files).unwrap();
+    }));
+
+    let files = loop {
+        adapter.poll_responses();
+
+        if let Ok(files) = rx.try_recv() {
+            break files;
+        }
+
+        if let Some(output) = adapter.check_server_status() {
+            return Err(anyhow!("The server exited\n{}", output));
+        }
+
+        std::thread::sleep(Duration::from_millis(25));
+    };
+
+    let impact = Impact::compute(&git::changed_sources(changed, &files), &files);
+    print!("{}", export::impact(&impact, base));
+
+    Ok(())
 }
 
 fn render(mut adapter: Adapter) -> Result<()> {
     // startup: Enable raw mode for the terminal, giving us fine control over user input
     crossterm::terminal::enable_raw_mode()?;
-    crossterm::execute!(std::io::stderr(), crossterm::terminal::EnterAlternateScreen)?;
+    crossterm::execute!(
+        std::io::stderr(),
+        crossterm::terminal::EnterAlternateScreen,
+        crossterm::event::EnableMouseCapture
+    )?;
 
     let mut terminal = Terminal::new(CrosstermBackend::new(std::io::stderr()))?;
     let mut app_state = AppState::new();
     let mut exit_output = String::new();
     let (tx, rx) = std::sync::mpsc::channel::<AppEvent>();
+    // The session can only be restored once the files are loaded. A broken session file is
+    // ignored, it gets overwritten on quit
+    let mut session = Some(session::load(Path::new(PROJECT_DIR)).unwrap_or_default());
+
+    // What is shown is only built again when what it is built from changes, not on every frame
+    let mut file_filter_cache = FilterCache::new();
+    let mut dependent_filter_cache = FilterCache::new();
+    let mut files_list_cache = None;
+    let mut file_dependent_panel_cache = None;
+    let mut dependents_tree_cache = None;
+    let mut directory_tree_cache = None;
+    let mut stats_cache = None;
+    let mut impact_cache = None;
+    let mut changed_files_cache = None;
+    let mut why_report_cache = None;
 
     let tx_clone = tx.clone();
     adapter.get_files(Box::new(move |files| {
@@ -64,22 +172,40 @@ fn render(mut adapter: Adapter) -> Result<()> {
             FRAME_COUNT += 1;
         }
 
-        let (files_list, file_panel_title) = get_files_list(&app_state);
+        let impact = get_impact(&app_state, &mut impact_cache);
+        let (files_list, file_panel_title) = get_files_list(
+            &app_state,
+            impact.as_deref(),
+            &mut file_filter_cache,
+            &mut files_list_cache,
+        );
 
         let widget_board = WidgetBoard {
-            file_panel: FilePanel::new(files_list, file_panel_title),
-            file_dependent_panel: app_state.global.selected_dependency_source.as_ref().map(
-                |file| {
-                    let (dependencies_list, file_dependent_panel_title) =
-                        get_dependent_files_list(&app_state, file);
-
-                    FileDependentPanel::new(
-                        file.path.clone(),
-                        dependencies_list,
-                        file_dependent_panel_title,
+            file_tree: get_directory_tree(&app_state, &files_list, &mut directory_tree_cache).map(
+                |tree| {
+                    FileTree::new(
+                        tree,
+                        app_state.global.pinned_files.clone(),
+                        app_state.global.marked_files.clone(),
+                        file_panel_title.clone(),
                     )
                 },
             ),
+            file_panel: FilePanel::new(
+                files_list,
+                app_state.global.pinned_files.clone(),
+                app_state.global.marked_files.clone(),
+                Query::from_search(&app_state.global.file_panel_search).unwrap_or_default(),
+                counts_filter(&app_state),
+                file_panel_title,
+            ),
+            file_dependent_panel: get_file_dependent_panel(
+                &app_state,
+                &mut dependent_filter_cache,
+                &mut file_dependent_panel_cache,
+            ),
+            dependents_tree: get_dependents_tree(&app_state, &mut dependents_tree_cache)
+                .map(DependentsTree::new),
             dependency_cause_panel: DependencyCausePanel::new(
                 app_state
                     .global
@@ -87,13 +213,19 @@ fn render(mut adapter: Adapter) -> Result<()> {
                     .as_ref()
                     .map(|f| f.path.clone()),
             ),
+            why_report: get_why_report(&app_state, &mut why_report_cache),
+            impact_report: impact
+                .filter(|_| app_state.global.show_impact)
+                .map(ImpactReport::new),
+            changed_files_panel: get_changed_files_panel(&app_state, &mut changed_files_cache),
         };
 
         terminal.draw(|f| {
             let widget_board = widget_board.clone();
             let frame_rect = f.size();
 
-            let [left_rect, right_rect, bottom_rect] = calculate_layout(frame_rect);
+            let [left_rect, right_rect, bottom_rect] =
+                calculate_layout(frame_rect, app_state.global.left_panel_percentage);
 
             render_left_panel(f, &widget_board, &mut app_state, left_rect);
 
@@ -104,6 +236,45 @@ fn render(mut adapter: Adapter) -> Result<()> {
             );
 
             render_footer(f, &mut app_state, bottom_rect);
+
+            // The footer stays visible, another query can be typed in the command prompt
+            if let Some(report) = widget_board.why_report {
+                let area = Rect {
+                    height: bottom_rect.y - frame_rect.y,
+                    ..frame_rect
+                };
+                f.render_stateful_widget(report, area, &mut app_state.why_report);
+            }
+
+            if let Some(report) = widget_board.impact_report {
+                let area = Rect {
+                    height: bottom_rect.y - frame_rect.y,
+                    ..frame_rect
+                };
+                f.render_stateful_widget(report, area, &mut app_state.impact_report);
+            }
+
+            if let Some(panel) = widget_board.changed_files_panel {
+                let area = Rect {
+                    height: bottom_rect.y - frame_rect.y,
+                    ..frame_rect
+                };
+                f.render_stateful_widget(panel, area, &mut app_state.changed_files_panel);
+            }
+
+            if app_state.global.show_dashboard {
+                f.render_widget(
+                    Dashboard::new(get_stats(&app_state, &mut stats_cache)),
+                    frame_rect,
+                );
+            }
+
+            if app_state.global.show_help {
+                f.render_widget(
+                    HelpOverlay::new(app_state.global.active_scopes()),
+                    frame_rect,
+                );
+            }
         })?;
 
         adapter.poll_responses();
@@ -114,6 +285,11 @@ fn render(mut adapter: Adapter) -> Result<()> {
         for event in terminal_events.into_iter().chain(dispatcher_events) {
             match event {
                 AppEvent::Quit => break 'main_loop,
+                AppEvent::OpenInEditor(file, line) => {
+                    if let Some(failure) = open_in_editor(&mut terminal, &file, line)? {
+                        app_state.global.message = Some(Message::Error(failure));
+                    }
+                }
                 event => dispatch_event(
                     &mut app_state,
                     &event,
@@ -124,6 +300,12 @@ fn render(mut adapter: Adapter) -> Result<()> {
             }
         }
 
+        if app_state.global.files_list.is_some() {
+            if let Some(session) = session.take() {
+                session.restore(&mut app_state, tx.clone());
+            }
+        }
+
         match adapter.check_server_status() {
             Some(output) => {
                 exit_output = output;
@@ -134,66 +316,398 @@ fn render(mut adapter: Adapter) -> Result<()> {
         }
     }
 
+    // Keep the previous session if the app quits before it got restored
+    let session = session.unwrap_or_else(|| Session::capture(&app_state));
+    let save_result = session::save(Path::new(PROJECT_DIR), &session);
+
     // shutdown down: reset terminal back to original state
-    crossterm::execute!(std::io::stderr(), crossterm::terminal::LeaveAlternateScreen)?;
+    crossterm::execute!(
+        std::io::stderr(),
+        crossterm::event::DisableMouseCapture,
+        crossterm::terminal::LeaveAlternateScreen
+    )?;
     crossterm::terminal::disable_raw_mode()?;
     println!("{}", exit_output);
 
+    if let Err(error) = save_result {
+        eprintln!("{:#}", error);
+    }
+
     Ok(())
 }
 
-fn get_files_list(app_state: &AppState) -> (Option<Vec<FileEntry>>, Option<String>) {
-    let filtered_files_list = app_state
-        .global
-        .files_list
-        .as_ref()
-        .map(|files| filter_files_list(files, &app_state.global.file_panel_search));
-
-    let total_files_count = app_state
-        .global
-        .files_list
-        .as_ref()
-        .map(|f| f.len())
-        .unwrap_or(0);
-    let total_filtered_files_count = filtered_files_list.as_ref().map(|f| f.len()).unwrap_or(0);
-
-    let title = if app_state.global.file_panel_search.is_searching() {
-        Some(format!(
-            " ({} of {})",
-            total_filtered_files_count, total_files_count
-        ))
-    } else {
-        None
+// Suspend the TUI while the editor is running, then resume it once the editor exits. Returns
+// why the editor failed, if it did
+fn open_in_editor(
+    terminal: &mut Terminal<CrosstermBackend<Stderr>>,
+    file: &FilePath,
+    line: usize,
+) -> Result<Option<String>> {
+    crossterm::execute!(
+        std::io::stderr(),
+        crossterm::event::DisableMouseCapture,
+        crossterm::terminal::LeaveAlternateScreen
+    )?;
+    crossterm::terminal::disable_raw_mode()?;
+
+    let (program, args) = editor_command(file, line);
+    let status = Command::new(&program)
+        .args(args)
+        .current_dir(PROJECT_DIR)
+        .status();
+
+    crossterm::terminal::enable_raw_mode()?;
+    crossterm::execute!(
+        std::io::stderr(),
+        crossterm::terminal::EnterAlternateScreen,
+        crossterm::event::EnableMouseCapture
+    )?;
+    terminal.clear()?;
+
+    Ok(editor_failure(&program, &status))
+}
+
+// What the files list is built from: the files, whether a search is on and its text, the order
+// and the pinned files. The cursor of the search prompt doesn't change the list
+type FilesListKey = (u64, bool, String, FileSort, bool, Vec<FilePath>);
+
+// The list is only filtered and sorted again when what it is built from changes
+fn get_files_list(
+    app_state: &AppState,
+    impact: Option<&Impact>,
+    filter_cache: &mut FilterCache,
+    cache: &mut Option<(FilesListKey, Rc<[FileEntry]>)>,
+) -> (Option<Rc<[FileEntry]>>, Option<String>) {
+    let global = &app_state.global;
+    let files = match global.files_list {
+        Some(ref files) => files,
+        None => return (None, None),
     };
 
-    return (filtered_files_list, title);
+    let search = &global.file_panel_search;
+    let filtered = filter_cache.filter("", global.files_generation, files, search);
+    let mut title = search_title(search, &filtered, files.len());
+
+    let key = (
+        global.files_generation,
+        search.is_active(),
+        search.query_text().to_string(),
+        global.file_sort,
+        global.file_sort_reversed,
+        global.pinned_files.clone(),
+    );
+
+    let files_list = match cache {
+        Some((cached_key, files_list)) if *cached_key == key => files_list.clone(),
+        _ => {
+            // An invalid query matches nothing, the panel title tells why
+            let mut files_list: Vec<FileEntry> = filtered
+                .unwrap_or_default()
+                .iter()
+                .map(|&index| files[index].clone())
+                .collect();
+
+            // While searching the best matches come first
+            if !search.is_active() {
+                global
+                    .file_sort
+                    .sort(&mut files_list, global.file_sort_reversed);
+            }
+            // Pinned files come first, the order is kept otherwise
+            files_list.sort_by_key(|file| !global.pinned_files.contains(&file.path));
+
+            let files_list: Rc<[FileEntry]> = files_list.into();
+            *cache = Some((key, files_list.clone()));
+            files_list
+        }
+    };
+
+    if !search.is_active() {
+        let reversed = if global.file_sort_reversed {
+            ", reversed"
+        } else {
+            ""
+        };
+        let sort_title = format!(" [sort={}{}]", global.file_sort.name(), reversed);
+        title.get_or_insert_with(String::new).push_str(&sort_title);
+    }
+
+    if counts_filter(app_state).is_some() {
+        title
+            .get_or_insert_with(String::new)
+            .push_str(" [counts filtered by reason]");
+    }
+
+    if let Some(impact) = impact.filter(|impact| !impact.changed.is_empty()) {
+        let impact_title = format!(
+            " [{} marked: {} recompile]",
+            impact.changed.len(),
+            impact.affected.len()
+        );
+        title
+            .get_or_insert_with(String::new)
+            .push_str(&impact_title);
+    }
+
+    return (Some(files_list), title);
+}
+
+// What the dependents panel is built from: the dependents, whether a search is on and its text,
+// the reason filter and the grouping
+type DependentPanelKey = (u64, bool, String, ReasonFilter, bool);
+
+// The panel is only built again when what it is built from changes
+fn get_file_dependent_panel(
+    app_state: &AppState,
+    filter_cache: &mut FilterCache,
+    cache: &mut Option<(DependentPanelKey, FileDependentPanel)>,
+) -> Option<FileDependentPanel> {
+    let global = &app_state.global;
+    let source = global.selected_dependency_source.as_ref()?;
+
+    let search = &global.file_dependent_panel_search;
+    let key = (
+        global.dependents_generation,
+        search.is_active(),
+        search.query_text().to_string(),
+        global.reason_filter.clone(),
+        global.group_dependents,
+    );
+
+    match cache {
+        Some((cached_key, panel)) if *cached_key == key => Some(panel.clone()),
+        _ => {
+            let (dependents, title) = get_dependent_files_list(app_state, source, filter_cache);
+            let panel = FileDependentPanel::new(
+                source.path.clone(),
+                dependents,
+                Query::from_search(search).unwrap_or_default(),
+                title,
+                global.group_dependents,
+            );
+
+            *cache = Some((key, panel.clone()));
+            Some(panel)
+        }
+    }
 }
 
 fn get_dependent_files_list(
     app_state: &AppState,
     file_entry: &FileEntry,
+    filter_cache: &mut FilterCache,
 ) -> (Vec<RecomplileDependency>, Option<String>) {
-    let filtered_dependencies_list = filter_files_list(
-        &file_entry.recompile_dependencies,
-        &app_state.global.file_dependent_panel_search,
+    let dependents = &file_entry.recompile_dependencies;
+    let search = &app_state.global.file_dependent_panel_search;
+    let filtered = filter_cache.filter(
+        &file_entry.path,
+        app_state.global.dependents_generation,
+        dependents,
+        search,
     );
 
-    let total_files_count = file_entry.recompile_dependencies.len();
-    let total_filtered_files_count = filtered_dependencies_list.len();
+    let mut panel_title = search_title(search, &filtered, dependents.len());
 
-    let panel_title = if app_state.global.file_dependent_panel_search.is_searching() {
-        Some(format!(
-            " ({} of {})",
-            total_filtered_files_count, total_files_count
-        ))
+    let reason_filter = &app_state.global.reason_filter;
+    let counts_title = reason_filter.counts_title(dependents);
+    let title = panel_title.get_or_insert_with(String::new);
+    title.push_str(&format!(" [{}]", counts_title));
+
+    // An invalid query matches nothing, the panel title tells why
+    let filtered = filtered
+        .unwrap_or_default()
+        .iter()
+        .map(|&index| dependents[index].clone())
+        .collect();
+
+    return (reason_filter.filter(filtered), panel_title);
+}
+
+// The counts of the file panel follow the reason filter when asked to
+fn counts_filter(app_state: &AppState) -> Option<ReasonFilter> {
+    let reason_filter = &app_state.global.reason_filter;
+
+    if app_state.global.filter_file_counts && reason_filter.is_active() {
+        Some(reason_filter.clone())
     } else {
         None
-    };
+    }
+}
 
-    return (filtered_dependencies_list, panel_title);
+// The tree is only built again when another file is selected or the files are collected again
+fn get_dependents_tree(
+    app_state: &AppState,
+    cache: &mut Option<((FilePath, u64), Rc<DependencyTree>)>,
+) -> Option<Rc<DependencyTree>> {
+    let source = app_state.global.selected_dependency_source.as_ref()?;
+    let files = app_state.global.files_list.as_ref()?;
+
+    if !app_state.global.show_dependents_tree {
+        return None;
+    }
+
+    let key = (source.path.clone(), app_state.global.files_generation);
+    match cache {
+        Some((cached_key, tree)) if *cached_key == key => Some(tree.clone()),
+        _ => {
+            let tree = Rc::new(DependencyTree::build(&source.path, files));
+            *cache = Some((key, tree.clone()));
+            Some(tree)
+        }
+    }
+}
+
+// What the directory tree is built from: the files, the search, the order of the files
+// and how they are counted
+type DirectoryTreeKey = (
+    u64,
+    search_input::State,
+    FileSort,
+    bool,
+    Vec<FilePath>,
+    Option<ReasonFilter>,
+);
+
+// The tree is only built again when the files listed in the file panel change
+fn get_directory_tree(
+    app_state: &AppState,
+    files_list: &Option<Rc<[FileEntry]>>,
+    cache: &mut Option<(DirectoryTreeKey, Rc<DirectoryTree>)>,
+) -> Option<Rc<DirectoryTree>> {
+    let files_list = files_list.as_ref()?;
+
+    if !app_state.global.show_directory_tree {
+        return None;
+    }
+
+    let key = (
+        app_state.global.files_generation,
+        app_state.global.file_panel_search.clone(),
+        app_state.global.file_sort,
+        app_state.global.file_sort_reversed,
+        app_state.global.pinned_files.clone(),
+        counts_filter(app_state),
+    );
+
+    match cache {
+        Some((cached_key, tree)) if *cached_key == key => Some(tree.clone()),
+        _ => {
+            let tree = Rc::new(DirectoryTree::build(
+                files_list.to_vec(),
+                counts_filter(app_state).as_ref(),
+            ));
+            *cache = Some((key, tree.clone()));
+            Some(tree)
+        }
+    }
+}
+
+// The statistics are only computed again when the files are collected again
+fn get_stats(app_state: &AppState, cache: &mut Option<(u64, Rc<Stats>)>) -> Option<Rc<Stats>> {
+    let files = app_state.global.files_list.as_ref()?;
+
+    let key = app_state.global.files_generation;
+    match cache {
+        Some((cached_key, stats)) if *cached_key == key => Some(stats.clone()),
+        _ => {
+            let stats = Rc::new(Stats::compute(files));
+            *cache = Some((key, stats.clone()));
+            Some(stats)
+        }
+    }
+}
+
+// The what if report is only computed again when the marks change or the files are collected
+// again
+fn get_impact(
+    app_state: &AppState,
+    cache: &mut Option<((u64, u64), Rc<Impact>)>,
+) -> Option<Rc<Impact>> {
+    let files = app_state.global.files_list.as_ref()?;
+
+    let key = (
+        app_state.global.files_generation,
+        app_state.global.marks_revision,
+    );
+    match cache {
+        Some((cached_key, impact)) if *cached_key == key => Some(impact.clone()),
+        _ => {
+            let impact = Rc::new(Impact::compute(&app_state.global.marked_files, files));
+            *cache = Some((key, impact.clone()));
+            Some(impact)
+        }
+    }
+}
+
+// What the changed files recompile is only computed again after another diff, or when the files
+// are collected again
+fn get_changed_files_panel(
+    app_state: &AppState,
+    cache: &mut Option<((u64, u64), ChangedFilesPanel)>,
+) -> Option<ChangedFilesPanel> {
+    if !app_state.global.show_changed_files {
+        return None;
+    }
+
+    let diff = app_state.global.diff.as_ref()?;
+    let files = app_state.global.files_list.as_ref()?;
+
+    let key = (
+        app_state.global.files_generation,
+        app_state.global.diff_revision,
+    );
+    match cache {
+        Some((cached_key, panel)) if *cached_key == key => Some(panel.clone()),
+        _ => {
+            let impact = Rc::new(Impact::compute(&diff.files, files));
+            let panel = ChangedFilesPanel::new(impact, diff.base.clone());
+            *cache = Some((key, panel.clone()));
+            Some(panel)
+        }
+    }
+}
+
+// The why report is only built again when another query is made or the files are collected again
+fn get_why_report(
+    app_state: &AppState,
+    cache: &mut Option<((FilePath, FilePath, u64), WhyReport)>,
+) -> Option<WhyReport> {
+    let (dependent, changed) = app_state.global.why_query.as_ref()?;
+
+    let key = (
+        dependent.clone(),
+        changed.clone(),
+        app_state.global.files_generation,
+    );
+    match cache {
+        Some((cached_key, report)) if *cached_key == key => Some(report.clone()),
+        _ => {
+            let files = app_state.global.files_list.as_deref().unwrap_or_default();
+            let report = WhyReport::new(dependent.clone(), changed.clone(), files);
+            *cache = Some((key, report.clone()));
+            Some(report)
+        }
+    }
+}
+
+// The panel title while searching, or typing a search, shows the match count, or why the query
+// is invalid
+fn search_title(
+    search: &search_input::State,
+    filtered: &Result<&[usize]>,
+    total: usize,
+) -> Option<String> {
+    if !search.is_active() {
+        return None;
+    }
+
+    match filtered {
+        Ok(filtered) => Some(format!(" ({} of {})", filtered.len(), total)),
+        Err(error) => Some(format!(" ({})", error)),
+    }
 }
 
-fn calculate_layout(root_rect: Rect) -> [Rect; 3] {
+fn calculate_layout(root_rect: Rect, left_panel_percentage: u16) -> [Rect; 3] {
     let layouts = Layout::default()
         .direction(Direction::Vertical)
         .constraints(vec![Constraint::Percentage(100), Constraint::Min(1)])
@@ -201,7 +715,10 @@ fn calculate_layout(root_rect: Rect) -> [Rect; 3] {
 
     let main_rect = Layout::default()
         .direction(Direction::Horizontal)
-        .constraints(vec![Constraint::Percentage(50), Constraint::Percentage(50)])
+        .constraints(vec![
+            Constraint::Percentage(left_panel_percentage),
+            Constraint::Percentage(100 - left_panel_percentage),
+        ])
         .split(layouts[0]);
 
     return [main_rect[0], main_rect[1], layouts[1]];
@@ -214,24 +731,45 @@ fn render_left_panel(
     area: Rect,
 ) {
     match &app_state.global.state_machine {
-        StateMachine::FilePanelView => f.render_stateful_widget(
-            widget_board.file_panel.clone(),
-            area,
-            &mut app_state.file_panel,
-        ),
+        StateMachine::FilePanelView => match widget_board.file_tree {
+            Some(ref tree) => {
+                f.render_stateful_widget(tree.clone(), area, &mut app_state.file_tree)
+            }
 
-        StateMachine::FileDependentsView => {
-            f.render_stateful_widget(
+            None => f.render_stateful_widget(
+                widget_board.file_panel.clone(),
+                area,
+                &mut app_state.file_panel,
+            ),
+        },
+
+        StateMachine::FileDependentsView => match widget_board.dependents_tree {
+            Some(ref tree) => {
+                f.render_stateful_widget(tree.clone(), area, &mut app_state.dependents_tree)
+            }
+
+            None => f.render_stateful_widget(
                 // It is guarantee that the widget exists if the app is in this state
                 widget_board.file_dependent_panel.clone().unwrap(),
                 area,
                 &mut app_state.file_dependent_panel,
-            )
-        }
+            ),
+        },
     };
 }
 
 fn render_footer(f: &mut Frame<CrosstermBackend<Stderr>>, app_state: &mut AppState, area: Rect) {
+    if app_state.global.command_prompt.is_active() {
+        return f.render_widget(
+            SearchInput::command(app_state.global.command_prompt.clone()),
+            area,
+        );
+    }
+
+    if let Some(ref message) = app_state.global.message {
+        return f.render_widget(MessageLine::new(message.clone()), area);
+    }
+
     match app_state.global.state_machine {
         StateMachine::FilePanelView => {
             if app_state.global.file_panel_search.is_active() {
@@ -272,24 +810,51 @@ fn poll_terminal_event(
             app_events.push(event)
         };
 
+        if app_state.global.show_help
+            || app_state.global.show_dashboard
+            || app_state.global.show_impact
+            || app_state.global.show_changed_files
+            || app_state.global.why_query.is_some()
+        {
+            return Ok(app_events);
+        }
+
         match app_state.global.state_machine {
             StateMachine::FilePanelView => {
-                if !app_state.global.file_panel_search.is_prompting() {
-                    if let Some(event) = app_state
-                        .file_panel
-                        .produce_event(&terminal_event, &widget_board.file_panel)
-                    {
+                if !app_state.global.is_prompting() {
+                    let event = match widget_board.file_tree {
+                        Some(ref tree) => app_state.file_tree.produce_event(&terminal_event, tree),
+                        None => app_state
+                            .file_panel
+                            .produce_event(&terminal_event, &widget_board.file_panel),
+                    };
+
+                    if let Some(event) = event {
                         app_events.push(event)
                     }
                 }
             }
 
             StateMachine::FileDependentsView => {
-                if !app_state.global.file_dependent_panel_search.is_prompting() {
-                    if let Some(event) = app_state.file_dependent_panel.produce_event(
-                        &terminal_event,
-                        &widget_board.file_dependent_panel.clone().unwrap(),
-                    ) {
+                if !app_state.global.is_prompting() {
+                    let event = match widget_board.dependents_tree {
+                        Some(ref tree) => app_state
+                            .dependents_tree
+                            .produce_event(&terminal_event, tree),
+                        None => app_state.file_dependent_panel.produce_event(
+                            &terminal_event,
+                            &widget_board.file_dependent_panel.clone().unwrap(),
+                        ),
+                    };
+
+                    if let Some(event) = event {
+                        app_events.push(event)
+                    }
+
+                    if let Some(event) = app_state
+                        .dependency_cause_panel
+                        .produce_event(&terminal_event, &widget_board.dependency_cause_panel)
+                    {
                         app_events.push(event)
                     }
                 }
@@ -310,21 +875,35 @@ fn dispatch_event(
     dispatcher: mpsc::Sender<AppEvent>,
 ) {
     match app_state.global.state_machine {
-        StateMachine::FilePanelView => app_state.file_panel.handle_event(
-            event,
-            &widget_board.file_panel,
-            adapter,
-            dispatcher.clone(),
-        ),
-        StateMachine::FileDependentsView => {
-            app_state.file_dependent_panel.handle_event(
+        StateMachine::FilePanelView => match widget_board.file_tree {
+            Some(ref tree) => {
+                app_state
+                    .file_tree
+                    .handle_event(event, tree, adapter, dispatcher.clone())
+            }
+
+            None => app_state.file_panel.handle_event(
+                event,
+                &widget_board.file_panel,
+                adapter,
+                dispatcher.clone(),
+            ),
+        },
+        StateMachine::FileDependentsView => match widget_board.dependents_tree {
+            Some(ref tree) => {
+                app_state
+                    .dependents_tree
+                    .handle_event(event, tree, adapter, dispatcher.clone())
+            }
+
+            None => app_state.file_dependent_panel.handle_event(
                 event,
                 // It is guarantee that the widget exists if the app is in this state
                 &widget_board.file_dependent_panel.clone().unwrap(),
                 adapter,
                 dispatcher.clone(),
-            )
-        }
+            ),
+        },
     };
 
     app_state.dependency_cause_panel.handle_event(
@@ -334,6 +913,24 @@ fn dispatch_event(
         dispatcher.clone(),
     );
 
+    if let Some(ref report) = widget_board.why_report {
+        app_state
+            .why_report
+            .handle_event(event, report, adapter, dispatcher.clone());
+    }
+
+    if let Some(ref report) = widget_board.impact_report {
+        app_state
+            .impact_report
+            .handle_event(event, report, adapter, dispatcher.clone());
+    }
+
+    if let Some(ref panel) = widget_board.changed_files_panel {
+        app_state
+            .changed_files_panel
+            .handle_event(event, panel, adapter, dispatcher.clone());
+    }
+
     // AppState is a special case since it doesn't have a concrete widget associated with it
     // We create a dummy widget to solve that
     app_state.handle_event(&event, &NoopWidget {}, adapter, dispatcher);
@@ -0,0 +1,540 @@
+use anyhow::{anyhow, Result};
+use fuzzy_matcher::skim::SkimMatcherV2;
+use fuzzy_matcher::FuzzyMatcher;
+use regex::{Regex, RegexBuilder};
+use std::cmp::Reverse;
+
+use crate::components::search_input;
+use crate::FilePath;
+
+/// A search query made of space separated terms, which must all match:
+///
+/// - `term` matches fuzzily
+/// - `'term` matches an exact substring
+/// - `/term/` matches a regular expression
+/// - a term with `*` or `?` is a glob, matched against the file name, or against the whole path
+///   if it contains a `/`. `**` also matches across directories
+/// - `!term` excludes the paths matching the term, which can be any of the above
+///
+/// Terms are case insensitive, unless they contain an uppercase character
+#[derive(Debug, Clone, Default)]
+pub struct Query {
+    terms: Vec<Term>,
+}
+
+#[derive(Debug, Clone)]
+struct Term {
+    // The term as typed, e.g. `!'foo`
+    input: String,
+    pattern: Pattern,
+    negated: bool,
+}
+
+#[derive(Debug, Clone)]
+enum Pattern {
+    Fuzzy { text: String, case_sensitive: bool },
+    Exact { text: String, case_sensitive: bool },
+    // The case sensitivity is compiled into the regex
+    Regex(Regex),
+    Glob { regex: Regex, whole_path: bool },
+}
+
+/// A successful match. Indices are the positions of the matched characters, not bytes
+#[derive(Debug, Clone, PartialEq)]
+pub struct Match {
+    pub score: i64,
+    pub indices: Vec<usize>,
+}
+
+// The fuzzy matchers are expensive to create, they are shared by all the matches of a thread
+struct Matchers {
+    ignore_case: SkimMatcherV2,
+    respect_case: SkimMatcherV2,
+}
+
+impl Matchers {
+    fn new() -> Self {
+        Self {
+            ignore_case: SkimMatcherV2::default().ignore_case(),
+            respect_case: SkimMatcherV2::default().respect_case(),
+        }
+    }
+}
+
+thread_local! {
+    static MATCHERS: Matchers = Matchers::new();
+}
+
+impl Query {
+    pub fn parse(input: &str) -> Result<Self> {
+        let terms = input
+            .split_whitespace()
+            .map(Term::parse)
+            .collect::<Result<Vec<Term>>>()?;
+
+        Ok(Self { terms })
+    }
+
+    /// The query of a search, which applies while it is being typed in the prompt too. No search
+    /// matches everything
+    pub fn from_search(search: &search_input::State) -> Result<Self> {
+        match search {
+            search_input::State::Search(input) => Self::parse(input),
+            search_input::State::Prompt(input) => Self::parse(input.text()),
+            search_input::State::None => Ok(Self::default()),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    pub fn match_path(&self, path: &str) -> Option<Match> {
+        MATCHERS.with(|matchers| self.match_with(matchers, path))
+    }
+
+    /// Returns the files matching the query, the best matches first
+    pub fn filter<T: Into<FilePath> + Clone>(&self, files: &[T]) -> Vec<T> {
+        if self.is_empty() {
+            return files.to_vec();
+        }
+
+        self.rank(files, 0..files.len())
+            .into_iter()
+            .map(|index| files[index].clone())
+            .collect()
+    }
+
+    // Returns the indices of the candidate files matching the query, the best matches first.
+    // Equal scores keep the order of the files
+    fn rank<T: Into<FilePath> + Clone>(
+        &self,
+        files: &[T],
+        candidates: impl IntoIterator<Item = usize>,
+    ) -> Vec<usize> {
+        let mut ranked: Vec<(usize, i64)> = MATCHERS.with(|matchers| {
+            candidates
+                .into_iter()
+                .filter_map(|index| {
+                    let file_path: FilePath = files[index].clone().into();
+                    let found = self.match_with(matchers, &file_path)?;
+
+                    Some((index, found.score))
+                })
+                .collect()
+        });
+
+        ranked.sort_by_key(|&(index, score)| (Reverse(score), index));
+        ranked.into_iter().map(|(index, _)| index).collect()
+    }
+
+    // Whether everything matching this query also matches the previous one, which is the case
+    // when typing more characters at the end of a fuzzy or exact term, or adding terms
+    fn refines(&self, previous: &Query) -> bool {
+        previous.terms.len() <= self.terms.len()
+            && previous
+                .terms
+                .iter()
+                .zip(&self.terms)
+                .all(|(previous, term)| term.refines(previous))
+    }
+
+    fn match_with(&self, matchers: &Matchers, path: &str) -> Option<Match> {
+        let mut score = 0;
+        let mut indices = vec![];
+
+        for term in &self.terms {
+            match (term.pattern.find(matchers, path), term.negated) {
+                (Some(_), true) | (None, false) => return None,
+                (Some(found), false) => {
+                    score += found.score;
+                    indices.extend(found.indices);
+                }
+                (None, true) => (),
+            }
+        }
+
+        indices.sort_unstable();
+        indices.dedup();
+
+        Some(Match { score, indices })
+    }
+}
+
+impl Term {
+    fn parse(input: &str) -> Result<Self> {
+        let term_input = input.to_string();
+        let (negated, input) = match input.strip_prefix('!') {
+            Some("") => return Err(anyhow!("Nothing to exclude after !")),
+            Some(rest) => (true, rest),
+            None => (false, input),
+        };
+
+        let case_sensitive = input.chars().any(char::is_uppercase);
+
+        let pattern = if let Some(text) = input.strip_prefix('\'') {
+            Pattern::Exact {
+                text: text.to_string(),
+                case_sensitive,
+            }
+        } else if input.len() >= 2 && input.starts_with('/') && input.ends_with('/') {
+            let source = &input[1..input.len() - 1];
+            Pattern::Regex(build_regex(source, case_sensitive)?)
+        } else if input.contains(['*', '?']) {
+            Pattern::Glob {
+                regex: build_regex(&glob_to_regex(input), case_sensitive)?,
+                whole_path: input.contains('/'),
+            }
+        } else {
+            Pattern::Fuzzy {
+                text: input.to_string(),
+                case_sensitive,
+            }
+        };
+
+        Ok(Self {
+            input: term_input,
+            pattern,
+            negated,
+        })
+    }
+
+    fn refines(&self, previous: &Term) -> bool {
+        if self.input == previous.input {
+            return true;
+        }
+
+        // Adding characters can only make a positive term stricter. The case sensitivity can only
+        // become stricter too, since the characters of the previous term are still there
+        match (&previous.pattern, &self.pattern) {
+            _ if self.negated || previous.negated => false,
+            (Pattern::Fuzzy { text: previous, .. }, Pattern::Fuzzy { text, .. })
+            | (Pattern::Exact { text: previous, .. }, Pattern::Exact { text, .. }) => {
+                text.starts_with(previous.as_str())
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Remembers the last filtered list, so that a list is only filtered again when the query or
+/// the list change. When the query refines the previous one, only the previous matches are
+/// filtered again, which keeps filtering while typing fast on large lists
+#[derive(Default)]
+pub struct FilterCache {
+    last: Option<Filtered>,
+}
+
+struct Filtered {
+    // Identifies the filtered list: a key and a generation given by the caller
+    list: (String, u64),
+    input: String,
+    query: Query,
+    // Indices of the matching files, the best matches first
+    matches: Vec<usize>,
+}
+
+impl FilterCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the indices of the files matching the query of the search, the best matches
+    /// first. The key tells apart different lists, e.g. the dependents of different files, and
+    /// the generation changes whenever the list itself changes
+    pub fn filter<T: Into<FilePath> + Clone>(
+        &mut self,
+        key: &str,
+        generation: u64,
+        files: &[T],
+        search: &search_input::State,
+    ) -> Result<&[usize]> {
+        let input = search.query_text();
+
+        let list = (key.to_string(), generation);
+        let same_list = self.last.as_ref().is_some_and(|last| last.list == list);
+
+        match self.last {
+            Some(ref last) if same_list && last.input == input => {}
+
+            _ => {
+                let query = Query::parse(input)?;
+
+                let matches = match self.last {
+                    _ if query.is_empty() => (0..files.len()).collect(),
+                    Some(ref last) if same_list && query.refines(&last.query) => {
+                        query.rank(files, last.matches.iter().copied())
+                    }
+                    _ => query.rank(files, 0..files.len()),
+                };
+
+                self.last = Some(Filtered {
+                    list,
+                    input: input.to_string(),
+                    query,
+                    matches,
+                });
+            }
+        }
+
+        Ok(self
+            .last
+            .as_ref()
+            .map(|last| &last.matches[..])
+            .unwrap_or(&[]))
+    }
+}
+
+impl Pattern {
+    fn find(&self, matchers: &Matchers, path: &str) -> Option<Match> {
+        match self {
+            Pattern::Fuzzy {
+                text,
+                case_sensitive,
+            } => {
+                let matcher = if *case_sensitive {
+                    &matchers.respect_case
+                } else {
+                    &matchers.ignore_case
+                };
+
+                match matcher.fuzzy_indices(path, text) {
+                    Some((score, indices)) if score > 0 => Some(Match { score, indices }),
+                    _ => None,
+                }
+            }
+
+            Pattern::Exact {
+                text,
+                case_sensitive,
+            } => find_exact(path, text, *case_sensitive).map(|indices| Match { score: 0, indices }),
+
+            Pattern::Regex(regex) => {
+                let found = regex.find(path)?;
+                let start = path[..found.start()].chars().count();
+                let length = found.as_str().chars().count();
+
+                Some(Match {
+                    score: 0,
+                    indices: (start..start + length).collect(),
+                })
+            }
+
+            Pattern::Glob { regex, whole_path } => {
+                let start = match path.rfind('/') {
+                    Some(index) if !whole_path => index + 1,
+                    _ => 0,
+                };
+
+                if !regex.is_match(&path[start..]) {
+                    return None;
+                }
+
+                let start = path[..start].chars().count();
+                Some(Match {
+                    score: 0,
+                    indices: (start..path.chars().count()).collect(),
+                })
+            }
+        }
+    }
+}
+
+fn build_regex(source: &str, case_sensitive: bool) -> Result<Regex> {
+    RegexBuilder::new(source)
+        .case_insensitive(!case_sensitive)
+        .build()
+        .map_err(|error| anyhow!("Invalid pattern {:?}: {}", source, error))
+}
+
+fn glob_to_regex(glob: &str) -> String {
+    let mut regex = String::from("^");
+    let mut chars = glob.chars().peekable();
+
+    while let Some(char) = chars.next() {
+        match char {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                regex.push_str(".*");
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            char => regex.push_str(&regex::escape(&char.to_string())),
+        }
+    }
+
+    regex.push('$');
+    regex
+}
+
+fn find_exact(path: &str, text: &str, case_sensitive: bool) -> Option<Vec<usize>> {
+    let fold = |char: char| {
+        if case_sensitive {
+            char
+        } else {
+            char.to_lowercase().next().unwrap_or(char)
+        }
+    };
+
+    let haystack: Vec<char> = path.chars().map(fold).collect();
+    let needle: Vec<char> = text.chars().map(fold).collect();
+
+    if needle.is_empty() {
+        return Some(vec![]);
+    }
+
+    let start = haystack
+        .windows(needle.len())
+        .position(|window| window == needle.as_slice())?;
+
+    Some((start..start + needle.len()).collect())
+}
+
+#[cfg(test)]
+mod query_tests {
+    use super::*;
+
+    fn matches(query: &str, path: &str) -> bool {
+        Query::parse(query).unwrap().match_path(path).is_some()
+    }
+
+    fn indices(query: &str, path: &str) -> Vec<usize> {
+        Query::parse(query)
+            .unwrap()
+            .match_path(path)
+            .unwrap()
+            .indices
+    }
+
+    #[test]
+    fn fuzzy() {
+        assert!(matches("abc", "lib/a_b_c.ex"));
+        assert!(!matches("abcd", "lib/a_b_c.ex"));
+        assert_eq!(indices("lbx", "lib/x.ex"), vec![0, 2, 4]);
+    }
+
+    #[test]
+    fn exact() {
+        assert!(matches("'lib/a", "lib/abc.ex"));
+        assert!(!matches("'lib/c", "lib/abc.ex"));
+        assert_eq!(indices("'abc", "lib/abc.ex"), vec![4, 5, 6]);
+    }
+
+    #[test]
+    fn regex() {
+        assert!(matches("/_test\\.exs$/", "test/foo_test.exs"));
+        assert!(!matches("/_test\\.exs$/", "test/foo_test.ex"));
+        assert_eq!(indices("/o+/", "lib/foo.ex"), vec![5, 6]);
+        assert!(Query::parse("/(/").is_err());
+    }
+
+    #[test]
+    fn glob() {
+        assert!(matches("*_test.exs", "test/foo_test.exs"));
+        assert!(!matches("lib/*.ex", "lib/nested/foo.ex"));
+        assert!(matches("lib/**.ex", "lib/nested/foo.ex"));
+        assert!(matches("fo?.ex", "lib/foo.ex"));
+        assert_eq!(indices("*.ex", "lib/foo.ex"), vec![4, 5, 6, 7, 8, 9]);
+    }
+
+    #[test]
+    fn smart_case() {
+        assert!(matches("'foo", "lib/Foo.ex"));
+        assert!(!matches("'Foo", "lib/foo.ex"));
+        assert!(matches("'Foo", "lib/Foo.ex"));
+        assert!(!matches("FO", "lib/foo.ex"));
+    }
+
+    #[test]
+    fn and_terms() {
+        assert!(matches("lib 'web", "lib/web/router.ex"));
+        assert!(!matches("lib 'web", "lib/core/router.ex"));
+        assert_eq!(indices("'lib 'web", "lib/web.ex"), vec![0, 1, 2, 4, 5, 6]);
+    }
+
+    #[test]
+    fn negation() {
+        assert!(matches("!'test", "lib/foo.ex"));
+        assert!(!matches("!'test", "test/foo_test.exs"));
+        assert!(matches("foo !*.exs", "lib/foo.ex"));
+        assert!(!matches("foo !*.exs", "test/foo_test.exs"));
+        assert!(Query::parse("!").is_err());
+    }
+
+    #[test]
+    fn non_ascii() {
+        assert!(matches("'ünï", "lib/ÜNÏCODE.ex"));
+        assert_eq!(indices("'ünï", "lib/ünïcode.ex"), vec![4, 5, 6]);
+        assert_eq!(indices("/ï./", "lib/ünïcode.ex"), vec![6, 7]);
+    }
+
+    #[test]
+    fn refinement() {
+        let refines = |query: &str, previous: &str| {
+            Query::parse(query)
+                .unwrap()
+                .refines(&Query::parse(previous).unwrap())
+        };
+
+        assert!(refines("foo", "fo"));
+        assert!(refines("fo bar", "fo"));
+        assert!(refines("'foo", "'fo"));
+        assert!(refines("!'x foo", "!'x fo"));
+        assert!(refines("fo", ""));
+        assert!(!refines("fo", "foo"));
+        assert!(!refines("!'foo", "!'fo"));
+        assert!(!refines("fo*", "fo"));
+        assert!(!refines("'fo", "fo"));
+    }
+
+    #[test]
+    fn filter_cache() {
+        let files: Vec<String> = ["lib/abc.ex", "lib/b.ex", "lib/ab.ex", "test/abc_test.exs"]
+            .iter()
+            .map(|f| f.to_string())
+            .collect();
+        let mut cache = FilterCache::new();
+
+        // Every step must give the same result as filtering from scratch
+        for input in [
+            "", "a", "ab", "ab !tes", "ab !test", "ab", "b", "/b\\./", "/(",
+        ] {
+            let search = search_input::State::prompt(input);
+            let expected = Query::parse(input).map(|query| query.filter(&files));
+
+            let filtered = cache.filter("", 0, &files, &search).map(|matches| {
+                matches
+                    .iter()
+                    .map(|&i| files[i].clone())
+                    .collect::<Vec<_>>()
+            });
+
+            match (filtered, expected) {
+                (Ok(filtered), Ok(expected)) => assert_eq!(filtered, expected, "{}", input),
+                (Err(_), Err(_)) => (),
+                (filtered, _) => panic!("{:?} for {}", filtered, input),
+            }
+        }
+
+        let other_files = files[1..].to_vec();
+        let search = search_input::State::Search(String::from("abc"));
+        assert_eq!(cache.filter("", 1, &other_files, &search).unwrap(), [2]);
+    }
+
+    #[test]
+    fn filter_sorts_by_score() {
+        let files: Vec<String> = ["lib/b.ex", "lib/abc.ex", "lib/c.ex"]
+            .iter()
+            .map(|f| f.to_string())
+            .collect();
+
+        let query = Query::parse("abc").unwrap();
+        assert_eq!(query.filter(&files), vec![String::from("lib/abc.ex")]);
+
+        let query = Query::parse("!'b.").unwrap();
+        assert_eq!(
+            query.filter(&files),
+            vec![String::from("lib/abc.ex"), String::from("lib/c.ex")]
+        );
+    }
+}
@@ -0,0 +1,143 @@
+use crate::{RecomplileDependency, RecomplileDependencyReason};
+
+/// The recompile reasons of the dependents which are shown, all of them by default
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ReasonFilter {
+    hidden: Vec<RecomplileDependencyReason>,
+}
+
+impl ReasonFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only show the dependents recompiled for the reason
+    pub fn only(reason: &RecomplileDependencyReason) -> Self {
+        Self {
+            hidden: RecomplileDependencyReason::ALL
+                .into_iter()
+                .filter(|other| other != reason)
+                .collect(),
+        }
+    }
+
+    pub fn is_shown(&self, reason: &RecomplileDependencyReason) -> bool {
+        !self.hidden.contains(reason)
+    }
+
+    // Some reason is hidden
+    pub fn is_active(&self) -> bool {
+        !self.hidden.is_empty()
+    }
+
+    pub fn toggle(&mut self, reason: &RecomplileDependencyReason) {
+        match self.hidden.iter().position(|hidden| hidden == reason) {
+            Some(index) => {
+                self.hidden.remove(index);
+            }
+            None => self.hidden.push(reason.clone()),
+        }
+    }
+
+    pub fn filter(&self, dependents: Vec<RecomplileDependency>) -> Vec<RecomplileDependency> {
+        dependents
+            .into_iter()
+            .filter(|dependent| self.is_shown(&dependent.reason))
+            .collect()
+    }
+
+    pub fn count(&self, dependents: &[RecomplileDependency]) -> usize {
+        dependents
+            .iter()
+            .filter(|dependent| self.is_shown(&dependent.reason))
+            .count()
+    }
+
+    /// The number of dependents for each reason, e.g. `compile 12 · -exports 3 · ...`. Hidden
+    /// reasons are prefixed with a `-`
+    pub fn counts_title(&self, dependents: &[RecomplileDependency]) -> String {
+        let counts: Vec<String> = RecomplileDependencyReason::ALL
+            .iter()
+            .map(|reason| {
+                let count = dependents
+                    .iter()
+                    .filter(|dependent| dependent.reason == *reason)
+                    .count();
+                let prefix = if self.is_shown(reason) { "" } else { "-" };
+
+                format!("{}{} {}", prefix, label(reason), count)
+            })
+            .collect();
+
+        counts.join(" · ")
+    }
+}
+
+// Shorter than the names of the reasons, the panel title has little room
+fn label(reason: &RecomplileDependencyReason) -> &'static str {
+    match reason {
+        RecomplileDependencyReason::Compile => "compile",
+        RecomplileDependencyReason::Exports => "exports",
+        RecomplileDependencyReason::ExportsThenCompile => "exports→compile",
+        RecomplileDependencyReason::CompileThenRuntime => "compile→runtime",
+    }
+}
+
+#[cfg(test)]
+mod reason_filter_tests {
+    use super::*;
+    use RecomplileDependencyReason::*;
+
+    fn dependents(reasons: &[RecomplileDependencyReason]) -> Vec<RecomplileDependency> {
+        reasons
+            .iter()
+            .enumerate()
+            .map(|(index, reason)| {
+                RecomplileDependency::fixture(&format!("lib/{}.ex", index))
+                    .with_reason(reason.clone())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn toggle() {
+        let dependents = dependents(&[Compile, Exports, Compile, CompileThenRuntime]);
+        let mut filter = ReasonFilter::new();
+        assert!(!filter.is_active());
+        assert_eq!(filter.count(&dependents), 4);
+
+        filter.toggle(&Compile);
+        assert!(filter.is_active());
+        assert_eq!(
+            filter
+                .filter(dependents.clone())
+                .into_iter()
+                .map(|dependent| dependent.id)
+                .collect::<Vec<_>>(),
+            vec!["lib/1.ex_exports", "lib/3.ex_compile_then_runtime"]
+        );
+
+        filter.toggle(&Compile);
+        assert_eq!(filter, ReasonFilter::new());
+    }
+
+    #[test]
+    fn only() {
+        let filter = ReasonFilter::only(&Exports);
+
+        assert!(filter.is_shown(&Exports));
+        assert!(!filter.is_shown(&Compile));
+        assert!(!filter.is_shown(&ExportsThenCompile));
+    }
+
+    #[test]
+    fn counts_title() {
+        let mut filter = ReasonFilter::new();
+        filter.toggle(&Exports);
+
+        assert_eq!(
+            filter.counts_title(&dependents(&[Compile, Exports, Compile])),
+            "compile 2 · -exports 1 · exports→compile 0 · compile→runtime 0"
+        );
+    }
+}
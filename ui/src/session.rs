@@ -0,0 +1,319 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+
+use crate::app_event::AppEvent;
+use crate::app_state::{self, AppState, StateMachine};
+use crate::components::file_dependent_panel::{self, Cursor};
+use crate::components::search_input;
+use crate::utils;
+use crate::FilePath;
+
+/// The part of the app state which is kept between runs, saved per project in
+/// `<state dir>/ex_compile_graph/sessions/`
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct Session {
+    selected_source: Option<FilePath>,
+    expanded_dependents: Vec<String>,
+    file_panel_search: Option<String>,
+    file_dependent_panel_search: Option<String>,
+    pinned_files: Vec<FilePath>,
+    left_panel_percentage: Option<u16>,
+}
+
+impl Session {
+    pub fn capture(app_state: &AppState) -> Self {
+        let global = &app_state.global;
+        let in_dependents_view = global.state_machine == StateMachine::FileDependentsView;
+
+        Self {
+            selected_source: global
+                .selected_dependency_source
+                .as_ref()
+                .map(|source| source.path.clone()),
+            expanded_dependents: if in_dependents_view {
+                expanded_dependents(app_state)
+            } else {
+                vec![]
+            },
+            file_panel_search: search_term(&global.file_panel_search),
+            file_dependent_panel_search: search_term(&global.file_dependent_panel_search),
+            pinned_files: global.pinned_files.clone(),
+            left_panel_percentage: Some(global.left_panel_percentage),
+        }
+    }
+
+    /// Put the session back into the app state. Must be called once the files list is loaded,
+    /// since files which no longer exist in it are dropped
+    pub fn restore(self, app_state: &mut AppState, dispatcher: mpsc::Sender<AppEvent>) {
+        let files = match app_state.global.files_list {
+            Some(ref files) => files,
+            None => return,
+        };
+        let exists = |path: &FilePath| files.iter().any(|file| file.path == *path);
+
+        let pinned_files: Vec<FilePath> = self.pinned_files.into_iter().filter(exists).collect();
+        let selected_source = self
+            .selected_source
+            .and_then(|path| files.iter().find(|file| file.path == path))
+            .cloned();
+
+        let global = &mut app_state.global;
+        global.pinned_files = pinned_files;
+
+        if let Some(percentage) = self.left_panel_percentage {
+            global.left_panel_percentage = app_state::clamp_panel_percentage(percentage);
+        }
+
+        if let Some(term) = self.file_panel_search {
+            global.file_panel_search = search_input::State::Search(term);
+        }
+
+        let source = match selected_source {
+            Some(source) => source,
+            None => return,
+        };
+
+        if let Some(term) = self.file_dependent_panel_search {
+            global.file_dependent_panel_search = search_input::State::Search(term);
+        }
+
+        let dependents = utils::filter_files_list(
+            &source.recompile_dependencies,
+            &global.file_dependent_panel_search,
+        );
+        let expanded: HashSet<String> = dependents
+            .iter()
+            .filter(|dependent| self.expanded_dependents.contains(&dependent.id))
+            .map(|dependent| dependent.id.clone())
+            .collect();
+
+        global.state_machine = StateMachine::FileDependentsView;
+        global.select_dependency_source(Some(source.clone()));
+
+        // The cursor goes to the first expanded dependent, the cause panel shows its causes
+        let first_expanded = dependents
+            .iter()
+            .position(|dependent| expanded.contains(&dependent.id));
+
+        if let Some(index) = first_expanded {
+            let dependent = dependents[index].clone();
+
+            app_state.file_dependent_panel = file_dependent_panel::State::restore(
+                Cursor::File(index),
+                expanded,
+                Some(dependent.id.clone()),
+            );
+
+            dispatcher
+                .send(AppEvent::ResetDependencyCauses(
+                    Some((source.path.clone(), dependent)),
+                    None,
+                ))
+                .unwrap();
+        }
+    }
+}
+
+// Sorted, so the session file doesn't change when nothing else does
+fn expanded_dependents(app_state: &AppState) -> Vec<String> {
+    let mut expanded: Vec<String> = app_state
+        .file_dependent_panel
+        .expanded()
+        .iter()
+        .cloned()
+        .collect();
+    expanded.sort();
+    expanded
+}
+
+fn search_term(search: &search_input::State) -> Option<String> {
+    match search {
+        search_input::State::Search(term) if !term.is_empty() => Some(term.clone()),
+        _ => None,
+    }
+}
+
+/// Returns the session file of a project. Each project gets its own file, named after the
+/// project directory and a hash of its full path
+pub fn session_path(project_dir: &Path) -> Option<PathBuf> {
+    let project_dir = project_dir.canonicalize().ok()?;
+    let name = project_dir
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    let file_name = format!(
+        "{}-{:016x}.json",
+        name,
+        fnv1a(project_dir.as_os_str().as_encoded_bytes())
+    );
+
+    dirs::state_dir().or_else(dirs::data_local_dir).map(|dir| {
+        dir.join("ex_compile_graph")
+            .join("sessions")
+            .join(file_name)
+    })
+}
+
+// A hash which is stable across runs and Rust versions, unlike the std hashers
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Load the session of a project. A missing session file gives an empty session
+pub fn load(project_dir: &Path) -> Result<Session> {
+    match session_path(project_dir) {
+        Some(path) if path.exists() => {
+            let content = fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read session file {}", path.display()))?;
+
+            serde_json::from_str(&content)
+                .with_context(|| format!("Invalid session file {}", path.display()))
+        }
+
+        _ => Ok(Session::default()),
+    }
+}
+
+pub fn save(project_dir: &Path, session: &Session) -> Result<()> {
+    let path = match session_path(project_dir) {
+        Some(path) => path,
+        None => return Ok(()),
+    };
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create directory {}", dir.display()))?;
+    }
+
+    fs::write(&path, serde_json::to_string_pretty(session)?)
+        .with_context(|| format!("Failed to write session file {}", path.display()))
+}
+
+#[cfg(test)]
+mod session_tests {
+    use super::*;
+    use crate::{FileEntry, RecomplileDependency};
+
+    fn app_state() -> AppState {
+        let dependents = vec![
+            RecomplileDependency::fixture("lib/b.ex"),
+            RecomplileDependency::fixture("lib/c.ex"),
+        ];
+
+        let mut app_state = AppState::new();
+        app_state.global.files_list = Some(vec![
+            FileEntry::fixture("lib/a.ex", dependents),
+            FileEntry::fixture("lib/b.ex", vec![]),
+        ]);
+
+        app_state
+    }
+
+    #[test]
+    fn capture_and_restore() {
+        let mut app_state = app_state();
+        let source = app_state.global.files_list.as_ref().unwrap()[0].clone();
+        app_state.global.state_machine = StateMachine::FileDependentsView;
+        app_state.global.selected_dependency_source = Some(source);
+        app_state.global.file_panel_search = search_input::State::Search(String::from("lib"));
+        app_state.global.pinned_files = vec![String::from("lib/b.ex")];
+        app_state.global.left_panel_percentage = 60;
+        app_state.file_dependent_panel = file_dependent_panel::State::restore(
+            Cursor::File(1),
+            HashSet::from([String::from("lib/c.ex_compile")]),
+            None,
+        );
+
+        let session = Session::capture(&app_state);
+        let json = serde_json::to_string(&session).unwrap();
+        let session: Session = serde_json::from_str(&json).unwrap();
+
+        let mut restored = self::app_state();
+        let (tx, rx) = mpsc::channel::<AppEvent>();
+        session.restore(&mut restored, tx);
+
+        assert_eq!(
+            restored.global.state_machine,
+            StateMachine::FileDependentsView
+        );
+        assert_eq!(
+            restored.global.selected_dependency_source.unwrap().path,
+            "lib/a.ex"
+        );
+        assert_eq!(
+            restored.global.file_panel_search,
+            search_input::State::Search(String::from("lib"))
+        );
+        assert_eq!(restored.global.pinned_files, vec![String::from("lib/b.ex")]);
+        assert_eq!(restored.global.left_panel_percentage, 60);
+        assert_eq!(restored.file_dependent_panel.selected(), Cursor::File(1));
+        assert_eq!(
+            restored.file_dependent_panel.expanded(),
+            &HashSet::from([String::from("lib/c.ex_compile")])
+        );
+        assert!(matches!(
+            &rx.try_iter().collect::<Vec<AppEvent>>()[..],
+            [AppEvent::ResetDependencyCauses(Some(_), None)]
+        ));
+    }
+
+    #[test]
+    fn drop_stale_entries() {
+        let session = Session {
+            selected_source: Some(String::from("lib/deleted.ex")),
+            expanded_dependents: vec![String::from("lib/b.ex_compile")],
+            pinned_files: vec![String::from("lib/deleted.ex"), String::from("lib/a.ex")],
+            ..Default::default()
+        };
+
+        let mut app_state = app_state();
+        let (tx, rx) = mpsc::channel::<AppEvent>();
+        session.restore(&mut app_state, tx);
+
+        assert_eq!(app_state.global.state_machine, StateMachine::FilePanelView);
+        assert!(app_state.global.selected_dependency_source.is_none());
+        assert_eq!(
+            app_state.global.pinned_files,
+            vec![String::from("lib/a.ex")]
+        );
+        assert_eq!(rx.try_iter().count(), 0);
+    }
+
+    #[test]
+    fn drop_stale_expanded_dependent() {
+        let session = Session {
+            selected_source: Some(String::from("lib/a.ex")),
+            expanded_dependents: vec![String::from("lib/deleted.ex")],
+            ..Default::default()
+        };
+
+        let mut app_state = app_state();
+        let (tx, rx) = mpsc::channel::<AppEvent>();
+        session.restore(&mut app_state, tx);
+
+        assert_eq!(
+            app_state.global.state_machine,
+            StateMachine::FileDependentsView
+        );
+        assert!(app_state.file_dependent_panel.expanded().is_empty());
+        assert_eq!(rx.try_iter().count(), 0);
+    }
+
+    #[test]
+    fn session_path_per_project() {
+        let dir = std::env::temp_dir();
+        let path = session_path(&dir).unwrap();
+        let file_name = path.file_name().unwrap().to_string_lossy();
+
+        assert!(file_name.ends_with(".json"));
+        assert_ne!(Some(path.clone()), session_path(Path::new("/")));
+    }
+}
@@ -0,0 +1,163 @@
+use std::collections::HashSet;
+
+use crate::{FileEntry, FilePath, RecomplileDependencyReason};
+
+/// Summaries of the whole project, shown in the dashboard
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stats {
+    pub total_files: usize,
+    pub files_with_dependents: usize,
+    // Number of files per range of dependents count, in the order of `HISTOGRAM_BUCKETS`
+    pub histogram: Vec<(&'static str, u64)>,
+    // Number of recompile dependencies per reason, over all files
+    pub reasons: Vec<(RecomplileDependencyReason, usize)>,
+    // The files recompiling the most files when they change, with that number
+    pub top_files: Vec<(FilePath, usize)>,
+}
+
+// Upper bound, inclusive, of each range of dependents count
+const HISTOGRAM_BUCKETS: &[(&str, usize)] = &[
+    ("0", 0),
+    ("1", 1),
+    ("2-5", 5),
+    ("6-10", 10),
+    ("11-25", 25),
+    ("26-50", 50),
+    ("51-100", 100),
+    ("101+", usize::MAX),
+];
+
+pub const TOP_FILES_COUNT: usize = 20;
+
+impl Stats {
+    pub fn compute(files: &[FileEntry]) -> Self {
+        // The blast radius of a file: a dependent recompiled for several reasons counts once
+        let mut blast_radiuses: Vec<(FilePath, usize)> = files
+            .iter()
+            .map(|file| {
+                let dependents: HashSet<&FilePath> = file
+                    .recompile_dependencies
+                    .iter()
+                    .map(|dependent| &dependent.path)
+                    .collect();
+
+                (file.path.clone(), dependents.len())
+            })
+            .collect();
+
+        let mut histogram: Vec<(&'static str, u64)> = HISTOGRAM_BUCKETS
+            .iter()
+            .map(|(label, _)| (*label, 0))
+            .collect();
+
+        for (_, count) in &blast_radiuses {
+            let bucket = HISTOGRAM_BUCKETS
+                .iter()
+                .position(|(_, max)| count <= max)
+                .unwrap_or(HISTOGRAM_BUCKETS.len() - 1);
+
+            histogram[bucket].1 += 1;
+        }
+
+        let reasons = RecomplileDependencyReason::ALL
+            .iter()
+            .map(|reason| {
+                let count = files
+                    .iter()
+                    .flat_map(|file| &file.recompile_dependencies)
+                    .filter(|dependent| dependent.reason == *reason)
+                    .count();
+
+                (reason.clone(), count)
+            })
+            .collect();
+
+        let files_with_dependents = blast_radiuses
+            .iter()
+            .filter(|(_, count)| *count > 0)
+            .count();
+
+        // Ties are listed by path, so the list doesn't change between refreshes
+        blast_radiuses.sort_by(|(a_path, a), (b_path, b)| b.cmp(a).then(a_path.cmp(b_path)));
+        blast_radiuses.truncate(TOP_FILES_COUNT);
+
+        Self {
+            total_files: files.len(),
+            files_with_dependents,
+            histogram,
+            reasons,
+            top_files: blast_radiuses,
+        }
+    }
+}
+
+#[cfg(test)]
+mod stats_tests {
+    use super::*;
+    use crate::RecomplileDependency;
+    use RecomplileDependencyReason::*;
+
+    #[test]
+    fn compute() {
+        let files = vec![
+            FileEntry::fixture("lib/a.ex", vec![]),
+            FileEntry::fixture(
+                "lib/b.ex",
+                vec![
+                    RecomplileDependency::fixture("lib/a.ex"),
+                    RecomplileDependency::fixture("lib/a.ex").with_reason(Exports),
+                ],
+            ),
+            FileEntry::fixture(
+                "lib/c.ex",
+                vec![
+                    RecomplileDependency::fixture("lib/a.ex"),
+                    RecomplileDependency::fixture("lib/b.ex"),
+                ],
+            ),
+        ];
+
+        let stats = Stats::compute(&files);
+
+        assert_eq!(stats.total_files, 3);
+        assert_eq!(stats.files_with_dependents, 2);
+        assert_eq!(&stats.histogram[..3], &[("0", 1), ("1", 1), ("2-5", 1)]);
+        assert_eq!(stats.reasons[..2], [(Compile, 3), (Exports, 1)]);
+        assert_eq!(
+            stats.top_files,
+            vec![
+                (String::from("lib/c.ex"), 2),
+                (String::from("lib/b.ex"), 1),
+                (String::from("lib/a.ex"), 0)
+            ]
+        );
+    }
+
+    #[test]
+    fn top_files_are_capped() {
+        let files: Vec<FileEntry> = (0..30)
+            .map(|index| FileEntry::fixture(&format!("lib/{:02}.ex", index), vec![]))
+            .collect();
+
+        let stats = Stats::compute(&files);
+
+        assert_eq!(stats.top_files.len(), TOP_FILES_COUNT);
+        assert_eq!(stats.top_files[0].0, "lib/00.ex");
+        assert_eq!(stats.histogram[0], ("0", 30));
+    }
+
+    #[test]
+    fn last_bucket_starts_after_100() {
+        let file_with = |count: usize| {
+            let dependents = (0..count)
+                .map(|index| RecomplileDependency::fixture(&format!("lib/{}.ex", index)))
+                .collect();
+            FileEntry::fixture(&format!("lib/with_{}.ex", count), dependents)
+        };
+
+        let stats = Stats::compute(&[file_with(100), file_with(101)]);
+
+        assert_eq!(stats.histogram[6], ("51-100", 1));
+        assert_eq!(stats.histogram[7], ("101+", 1));
+    }
+}
@@ -0,0 +1,406 @@
+use anyhow::{anyhow, Result};
+use ratatui::style::{Color, Modifier, Style};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// Styles shared by every widget. Widgets must read their colours from the current theme
+/// instead of hard-coding them
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    pub text: Style,
+    pub border: Style,
+    pub title: Style,
+    pub selection: Style,
+    pub count: Style,
+    pub search_match: Style,
+    pub compile_link: Style,
+    // Dependents which are always recompiled, and those which may be
+    pub definite: Style,
+    pub indefinite: Style,
+    pub code_highlight: Style,
+    pub line_number: Style,
+    pub prompt: Style,
+    pub hint: Style,
+    pub error: Style,
+    pub scrollbar: Style,
+
+    // Syntax highlighting
+    pub keyword: Style,
+    pub atom: Style,
+    pub module_alias: Style,
+    pub string: Style,
+    pub sigil: Style,
+    pub comment: Style,
+}
+
+const THEME_NAMES: &[&str] = &["dark", "light", "high_contrast", "monochrome"];
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+impl Theme {
+    pub fn dark() -> Self {
+        Self {
+            text: fg(Color::White),
+            border: fg(Color::White),
+            title: fg(Color::Cyan),
+            selection: Style::default()
+                .bg(Color::Blue)
+                .add_modifier(Modifier::BOLD),
+            count: fg(Color::Yellow),
+            search_match: fg(Color::LightMagenta).add_modifier(Modifier::BOLD),
+            compile_link: fg(Color::Red),
+            definite: fg(Color::LightRed),
+            indefinite: fg(Color::Gray),
+            code_highlight: fg(Color::Green).add_modifier(Modifier::BOLD),
+            line_number: fg(Color::DarkGray),
+            prompt: fg(Color::Cyan),
+            hint: fg(Color::Yellow),
+            error: fg(Color::LightRed),
+            scrollbar: fg(Color::Gray),
+
+            keyword: fg(Color::Magenta),
+            atom: fg(Color::Cyan),
+            module_alias: fg(Color::Yellow),
+            string: fg(Color::LightGreen),
+            sigil: fg(Color::LightRed),
+            comment: fg(Color::DarkGray),
+        }
+    }
+
+    pub fn light() -> Self {
+        Self {
+            text: fg(Color::Black),
+            border: fg(Color::DarkGray),
+            title: fg(Color::Blue),
+            selection: Style::default()
+                .fg(Color::Black)
+                .bg(Color::LightBlue)
+                .add_modifier(Modifier::BOLD),
+            count: fg(Color::Magenta),
+            search_match: fg(Color::Red).add_modifier(Modifier::BOLD),
+            compile_link: fg(Color::Red),
+            definite: fg(Color::Red),
+            indefinite: fg(Color::DarkGray),
+            code_highlight: fg(Color::Green).add_modifier(Modifier::BOLD),
+            line_number: fg(Color::Gray),
+            prompt: fg(Color::Blue),
+            hint: fg(Color::Magenta),
+            error: fg(Color::Red),
+            scrollbar: fg(Color::DarkGray),
+
+            keyword: fg(Color::Magenta),
+            atom: fg(Color::Blue),
+            module_alias: fg(Color::Cyan),
+            string: fg(Color::Green),
+            sigil: fg(Color::Red),
+            comment: fg(Color::Gray),
+        }
+    }
+
+    pub fn high_contrast() -> Self {
+        let bold = |color: Color| fg(color).add_modifier(Modifier::BOLD);
+
+        Self {
+            text: fg(Color::White),
+            border: fg(Color::White),
+            title: bold(Color::LightCyan),
+            selection: Style::default()
+                .fg(Color::Black)
+                .bg(Color::White)
+                .add_modifier(Modifier::BOLD),
+            count: bold(Color::LightYellow),
+            search_match: bold(Color::LightMagenta).add_modifier(Modifier::UNDERLINED),
+            compile_link: bold(Color::LightRed),
+            definite: bold(Color::LightRed),
+            indefinite: fg(Color::White),
+            code_highlight: bold(Color::LightGreen),
+            line_number: fg(Color::White),
+            prompt: bold(Color::LightCyan),
+            hint: bold(Color::LightYellow),
+            error: bold(Color::LightRed),
+            scrollbar: fg(Color::White),
+
+            keyword: bold(Color::LightMagenta),
+            atom: fg(Color::LightCyan),
+            module_alias: fg(Color::LightYellow),
+            string: fg(Color::LightGreen),
+            sigil: fg(Color::LightRed),
+            comment: fg(Color::Gray),
+        }
+    }
+
+    /// A theme without colours, used when NO_COLOR is set. Modifiers take over the role of
+    /// colours
+    pub fn monochrome() -> Self {
+        let modifier = |modifier: Modifier| Style::default().add_modifier(modifier);
+
+        Self {
+            text: Style::default(),
+            border: Style::default(),
+            title: modifier(Modifier::BOLD),
+            selection: modifier(Modifier::REVERSED | Modifier::BOLD),
+            count: modifier(Modifier::BOLD),
+            search_match: modifier(Modifier::UNDERLINED),
+            compile_link: modifier(Modifier::BOLD | Modifier::UNDERLINED),
+            definite: modifier(Modifier::BOLD),
+            indefinite: modifier(Modifier::DIM),
+            code_highlight: modifier(Modifier::BOLD),
+            line_number: modifier(Modifier::DIM),
+            prompt: modifier(Modifier::BOLD),
+            hint: modifier(Modifier::ITALIC),
+            error: modifier(Modifier::BOLD | Modifier::REVERSED),
+            scrollbar: Style::default(),
+
+            keyword: modifier(Modifier::BOLD),
+            atom: Style::default(),
+            module_alias: modifier(Modifier::UNDERLINED),
+            string: modifier(Modifier::ITALIC),
+            sigil: modifier(Modifier::ITALIC),
+            comment: modifier(Modifier::DIM),
+        }
+    }
+
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name {
+            "dark" => Ok(Self::dark()),
+            "light" => Ok(Self::light()),
+            "high_contrast" => Ok(Self::high_contrast()),
+            "monochrome" => Ok(Self::monochrome()),
+            _ => Err(anyhow!(
+                "Unknown theme {:?}, expect one of: {}",
+                name,
+                THEME_NAMES.join(", ")
+            )),
+        }
+    }
+
+    /// Apply style overrides from the config file, keyed by the theme field name
+    pub fn with_overrides(mut self, overrides: &HashMap<String, StyleOverride>) -> Result<Self> {
+        for (name, style_override) in overrides {
+            let style = self
+                .style_mut(name)
+                .ok_or_else(|| anyhow!("Unknown theme style {:?}", name))?;
+
+            *style = style_override.apply(*style)?;
+        }
+
+        Ok(self)
+    }
+
+    fn style_mut(&mut self, name: &str) -> Option<&mut Style> {
+        let style = match name {
+            "text" => &mut self.text,
+            "border" => &mut self.border,
+            "title" => &mut self.title,
+            "selection" => &mut self.selection,
+            "count" => &mut self.count,
+            "search_match" => &mut self.search_match,
+            "compile_link" => &mut self.compile_link,
+            "definite" => &mut self.definite,
+            "indefinite" => &mut self.indefinite,
+            "code_highlight" => &mut self.code_highlight,
+            "line_number" => &mut self.line_number,
+            "prompt" => &mut self.prompt,
+            "hint" => &mut self.hint,
+            "error" => &mut self.error,
+            "scrollbar" => &mut self.scrollbar,
+            "keyword" => &mut self.keyword,
+            "atom" => &mut self.atom,
+            "module_alias" => &mut self.module_alias,
+            "string" => &mut self.string,
+            "sigil" => &mut self.sigil,
+            "comment" => &mut self.comment,
+            _ => return None,
+        };
+
+        Some(style)
+    }
+}
+
+fn fg(color: Color) -> Style {
+    Style::default().fg(color)
+}
+
+/// A style override from the config file, e.g.
+/// `selection = { fg = "black", bg = "#ffd75f", modifiers = ["bold"] }`.
+/// Omitted fields keep the value of the base theme
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct StyleOverride {
+    fg: Option<String>,
+    bg: Option<String>,
+    modifiers: Option<Vec<String>>,
+}
+
+impl StyleOverride {
+    fn apply(&self, mut style: Style) -> Result<Style> {
+        if let Some(ref color) = self.fg {
+            style.fg = Some(parse_color(color)?);
+        }
+
+        if let Some(ref color) = self.bg {
+            style.bg = Some(parse_color(color)?);
+        }
+
+        if let Some(ref modifiers) = self.modifiers {
+            let mut modifier = Modifier::empty();
+            for name in modifiers {
+                modifier |= parse_modifier(name)?;
+            }
+
+            style.add_modifier = modifier;
+            style.sub_modifier = Modifier::empty();
+        }
+
+        Ok(style)
+    }
+}
+
+fn parse_color(color: &str) -> Result<Color> {
+    color
+        .parse::<Color>()
+        .map_err(|_| anyhow!("Invalid color {:?}", color))
+}
+
+fn parse_modifier(modifier: &str) -> Result<Modifier> {
+    match modifier {
+        "bold" => Ok(Modifier::BOLD),
+        "dim" => Ok(Modifier::DIM),
+        "italic" => Ok(Modifier::ITALIC),
+        "underlined" => Ok(Modifier::UNDERLINED),
+        "reversed" => Ok(Modifier::REVERSED),
+        "crossed_out" => Ok(Modifier::CROSSED_OUT),
+        _ => Err(anyhow!("Invalid modifier {:?}", modifier)),
+    }
+}
+
+/// Whether colours are disabled through the NO_COLOR environment variable.
+/// See https://no-color.org
+pub fn no_color() -> bool {
+    std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty())
+}
+
+static THEME: OnceLock<Theme> = OnceLock::new();
+
+/// Install the theme to use. Must be called before the theme is first used, otherwise the
+/// default theme stays in place
+pub fn init(theme: Theme) {
+    let _ = THEME.set(theme);
+}
+
+/// Returns the theme in use. If none is installed, the default theme is used
+pub fn current() -> &'static Theme {
+    THEME.get_or_init(Theme::default)
+}
+
+#[cfg(test)]
+mod theme_tests {
+    use super::*;
+
+    fn overrides(entries: &[(&str, StyleOverride)]) -> HashMap<String, StyleOverride> {
+        entries
+            .iter()
+            .map(|(name, style)| (name.to_string(), style.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn built_in_themes() {
+        for name in THEME_NAMES {
+            assert!(Theme::from_name(name).is_ok());
+        }
+
+        assert!(Theme::from_name("solarized").is_err());
+    }
+
+    #[test]
+    fn monochrome_has_no_colors() {
+        let theme = Theme::monochrome();
+        let styles = [
+            theme.text,
+            theme.border,
+            theme.title,
+            theme.selection,
+            theme.count,
+            theme.search_match,
+            theme.compile_link,
+            theme.definite,
+            theme.indefinite,
+            theme.code_highlight,
+            theme.line_number,
+            theme.prompt,
+            theme.hint,
+            theme.error,
+            theme.scrollbar,
+            theme.keyword,
+            theme.atom,
+            theme.module_alias,
+            theme.string,
+            theme.sigil,
+            theme.comment,
+        ];
+
+        for style in styles {
+            assert_eq!(style.fg, None);
+            assert_eq!(style.bg, None);
+        }
+    }
+
+    #[test]
+    fn override_keeps_omitted_fields() {
+        let theme = Theme::dark()
+            .with_overrides(&overrides(&[(
+                "selection",
+                StyleOverride {
+                    bg: Some(String::from("#ffd75f")),
+                    ..Default::default()
+                },
+            )]))
+            .unwrap();
+
+        assert_eq!(theme.selection.bg, Some(Color::Rgb(255, 215, 95)));
+        assert!(theme.selection.add_modifier.contains(Modifier::BOLD));
+    }
+
+    #[test]
+    fn override_replaces_modifiers() {
+        let theme = Theme::dark()
+            .with_overrides(&overrides(&[(
+                "selection",
+                StyleOverride {
+                    modifiers: Some(vec![String::from("reversed")]),
+                    ..Default::default()
+                },
+            )]))
+            .unwrap();
+
+        assert_eq!(theme.selection.add_modifier, Modifier::REVERSED);
+    }
+
+    #[test]
+    fn invalid_overrides() {
+        let invalid_color = StyleOverride {
+            fg: Some(String::from("blurple")),
+            ..Default::default()
+        };
+        let invalid_modifier = StyleOverride {
+            modifiers: Some(vec![String::from("blinking")]),
+            ..Default::default()
+        };
+
+        assert!(Theme::dark()
+            .with_overrides(&overrides(&[("text", invalid_color)]))
+            .is_err());
+        assert!(Theme::dark()
+            .with_overrides(&overrides(&[("text", invalid_modifier)]))
+            .is_err());
+        assert!(Theme::dark()
+            .with_overrides(&overrides(&[("background", StyleOverride::default())]))
+            .is_err());
+    }
+}
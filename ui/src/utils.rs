@@ -1,10 +1,10 @@
-use fuzzy_matcher::skim::SkimMatcherV2;
-use fuzzy_matcher::FuzzyMatcher;
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::Style;
+use ratatui::text::Span;
 use std::cmp;
-use std::cmp::Reverse;
 
 use crate::components::search_input;
+use crate::query::Query;
 use crate::FilePath;
 
 #[allow(dead_code)]
@@ -77,6 +77,11 @@ pub fn center_rect_in_container(child: &mut Rect, container: &Rect) {
     child.y = center_y - child.height / 2;
 }
 
+/// Check whether a terminal position lies within a rect
+pub fn contains(rect: &Rect, column: u16, row: u16) -> bool {
+    column >= rect.x && column < rect.x + rect.width && row >= rect.y && row < rect.y + rect.height
+}
+
 /// Compact a file path to fit a maximum width. If the file path is longer than the maximum
 /// width, it will get truncated and have the leading ...
 ///
@@ -98,7 +103,8 @@ pub fn center_rect_in_container(child: &mut Rect, container: &Rect) {
 /// assert_eq!(result, ".../d");
 /// ```
 pub fn compact_file_path(file_path: &str, maximum: usize) -> String {
-    if file_path.len() <= maximum {
+    // Measured in characters, as the matched indices of the highlighting are
+    if file_path.chars().count() <= maximum {
         return file_path.to_string();
     }
 
@@ -107,8 +113,9 @@ pub fn compact_file_path(file_path: &str, maximum: usize) -> String {
     let mut truncated = false;
 
     for substring in substrings.into_iter().rev() {
-        let total_length = result.len() - 1 + result.iter().map(|f| f.len()).sum::<usize>();
-        if total_length + substring.len() + 1 > maximum {
+        let total_length =
+            result.len() - 1 + result.iter().map(|f| f.chars().count()).sum::<usize>();
+        if total_length + substring.chars().count() + 1 > maximum {
             truncated = true;
             break;
         }
@@ -123,32 +130,70 @@ pub fn compact_file_path(file_path: &str, maximum: usize) -> String {
     result.join("/")
 }
 
-pub fn filter_files_list<'a, 'b, T: Into<FilePath> + Clone>(
-    files: &'a [T],
+pub fn filter_files_list<T: Into<FilePath> + Clone>(
+    files: &[T],
     search_term: &search_input::State,
 ) -> Vec<T> {
-    match search_term {
-        search_input::State::Search(term) => {
-            let matcher = SkimMatcherV2::default();
-
-            let mut filtered = files
-                .iter()
-                .filter_map(|file| {
-                    let file_path: FilePath = file.clone().into();
-                    let score = matcher.fuzzy_match(&file_path, term);
-
-                    match score {
-                        Some(score) if score > 0 => Some((file, score)),
-                        _ => None,
-                    }
-                })
-                .collect::<Vec<(&T, i64)>>();
-
-            filtered.sort_by_key(|item| Reverse(item.1));
-            filtered.into_iter().map(|(file, _)| file.clone()).collect()
+    match Query::from_search(search_term) {
+        Ok(query) => query.filter(files),
+        // An invalid query matches nothing, the panel title tells why
+        Err(_) => vec![],
+    }
+}
+
+/// Split a file path compacted by `compact_file_path` into spans, applying the style to the
+/// characters at the matched indices of the full path
+pub fn highlight_file_path(
+    file_path: &str,
+    compacted: &str,
+    indices: &[usize],
+    style: Style,
+) -> Vec<Span<'static>> {
+    // A compacted path is the end of the full path behind a leading ..., or only the ... when
+    // even the file name doesn't fit, which can be longer than a short path
+    let (skipped, offset) = if compacted == file_path {
+        (0, 0)
+    } else {
+        (
+            3,
+            file_path
+                .chars()
+                .count()
+                .saturating_sub(compacted.chars().count()),
+        )
+    };
+
+    let mut spans: Vec<Span> = vec![];
+    let mut current = String::new();
+    let mut current_highlighted = false;
+
+    for (index, char) in compacted.chars().enumerate() {
+        let highlighted = index >= skipped && indices.contains(&(index + offset));
+
+        if highlighted != current_highlighted && !current.is_empty() {
+            spans.push(styled_span(
+                std::mem::take(&mut current),
+                current_highlighted,
+                style,
+            ));
         }
 
-        _ => files.to_vec(),
+        current_highlighted = highlighted;
+        current.push(char);
+    }
+
+    if !current.is_empty() {
+        spans.push(styled_span(current, current_highlighted, style));
+    }
+
+    spans
+}
+
+fn styled_span(content: String, highlighted: bool, style: Style) -> Span<'static> {
+    if highlighted {
+        Span::styled(content, style)
+    } else {
+        Span::from(content)
     }
 }
 
@@ -398,6 +443,28 @@ mod center_rect_in_container_tests {
     }
 }
 
+#[cfg(test)]
+mod contains_tests {
+    use super::*;
+    use ratatui::layout::Rect;
+
+    #[test]
+    fn inside_and_outside() {
+        let rect = Rect {
+            x: 2,
+            y: 3,
+            width: 4,
+            height: 2,
+        };
+
+        assert!(contains(&rect, 2, 3));
+        assert!(contains(&rect, 5, 4));
+        assert!(!contains(&rect, 6, 4));
+        assert!(!contains(&rect, 5, 5));
+        assert!(!contains(&rect, 1, 3));
+    }
+}
+
 #[cfg(test)]
 mod compact_file_path_tests {
     use super::*;
@@ -415,6 +482,60 @@ mod compact_file_path_tests {
         let result = compact_file_path(path, 5);
         assert_eq!(result, ".../d");
     }
+
+    #[test]
+    fn measures_characters() {
+        assert_eq!(compact_file_path("éé", 3), "éé");
+        assert_eq!(compact_file_path("éé/é/éé", 6), ".../éé");
+    }
+}
+
+#[cfg(test)]
+mod highlight_file_path_tests {
+    use super::*;
+    use ratatui::style::Color;
+
+    fn contents(spans: &[Span]) -> Vec<(String, bool)> {
+        spans
+            .iter()
+            .map(|span| (span.content.to_string(), span.style.fg == Some(Color::Red)))
+            .collect()
+    }
+
+    #[test]
+    fn full_path() {
+        let style = Style::default().fg(Color::Red);
+        let spans = highlight_file_path("lib/foo.ex", "lib/foo.ex", &[4, 5, 9], style);
+
+        assert_eq!(
+            contents(&spans),
+            vec![
+                (String::from("lib/"), false),
+                (String::from("fo"), true),
+                (String::from("o.e"), false),
+                (String::from("x"), true),
+            ]
+        );
+    }
+
+    #[test]
+    fn compacted_path() {
+        let style = Style::default().fg(Color::Red);
+        let spans = highlight_file_path("a/b/c/d", ".../d", &[0, 6], style);
+
+        assert_eq!(
+            contents(&spans),
+            vec![(String::from(".../"), false), (String::from("d"), true)]
+        );
+    }
+
+    #[test]
+    fn path_shorter_than_the_ellipsis() {
+        let style = Style::default().fg(Color::Red);
+        let spans = highlight_file_path("ab", "...", &[0, 1], style);
+
+        assert_eq!(contents(&spans), vec![(String::from("..."), false)]);
+    }
 }
 
 #[cfg(test)]